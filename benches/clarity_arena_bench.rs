@@ -0,0 +1,57 @@
+extern crate criterion;
+
+use std::io::Cursor;
+
+use bumpalo::Bump;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use stacks_encoding_native_js::clarity_value::arena::decode_clarity_value_in;
+use stacks_encoding_native_js::clarity_value::types::ClarityValue;
+use stacks_encoding_native_js::hex::decode_hex;
+
+// (list (tuple (address 'SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR) (can-burn false) (can-mint false) (name "aip10-arkadiko-update-tvl-liquidation-ratio") (qualified-name 'SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR.aip10-arkadiko-update-tvl-liquidation-ratio)))
+const TUPLE_LIST: &str = "0x0b000000010c0000000507616464726573730516982f3ec112a5f5928a5c96a914bd733793b896a50863616e2d6275726e040863616e2d6d696e7404046e616d650d0000002b61697031302d61726b6164696b6f2d7570646174652d74766c2d6c69717569646174696f6e2d726174696f0e7175616c69666965642d6e616d650616982f3ec112a5f5928a5c96a914bd733793b896a52b61697031302d61726b6164696b6f2d7570646174652d74766c2d6c69717569646174696f6e2d726174696f";
+// (ok (list (tuple (a 0x0102) (b (some u1))) (tuple (a 0x03) (b none))))
+const NESTED: &str = "0x070b000000020c0000000201610200000002010201620a01000000000000000000000000000000010c000000020161020000000103016209";
+// "AIP10 Update LTVs and Liquidation Ratios"
+const STRING: &str =
+    "0x0e00000028414950313020557064617465204c54567320616e64204c69717569646174696f6e20526174696f73";
+
+/// Roughly the number of Clarity values (contract-call args and events) in a busy block.
+const BLOCK_VALUE_COUNT: usize = 3000;
+
+fn block_values() -> Vec<Box<[u8]>> {
+    let samples = [TUPLE_LIST, NESTED, STRING];
+    (0..BLOCK_VALUE_COUNT)
+        .map(|i| decode_hex(samples[i % samples.len()]).unwrap())
+        .collect()
+}
+
+fn bench_block_decoding(c: &mut Criterion) {
+    let values = block_values();
+    let mut group = c.benchmark_group("Clarity block decoding");
+    group.throughput(Throughput::Elements(values.len() as u64));
+
+    group.bench_function("ClarityValue::deserialize", |b| {
+        b.iter(|| {
+            for bytes in values.iter() {
+                let mut cursor = Cursor::new(bytes.as_ref());
+                ClarityValue::deserialize(&mut cursor, false).unwrap();
+            }
+        })
+    });
+
+    let mut arena = Bump::new();
+    group.bench_function("decode_clarity_value_in", |b| {
+        b.iter(|| {
+            for bytes in values.iter() {
+                decode_clarity_value_in(bytes, &arena).unwrap();
+            }
+            arena.reset();
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_block_decoding);
+criterion_main!(benches);
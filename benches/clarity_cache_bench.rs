@@ -0,0 +1,56 @@
+extern crate criterion;
+
+use std::io::Cursor;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use stacks_encoding_native_js::clarity_value::cache::DecodeCache;
+use stacks_encoding_native_js::clarity_value::types::ClarityValue;
+use stacks_encoding_native_js::hex::decode_hex;
+
+// (tuple (action "swap") (pool 'SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR.arkadiko-swap-v2-1))
+const SWAP_EVENT: &str = "0x0c0000000206616374696f6e0d000000047377617004706f6f6c0616982f3ec112a5f5928a5c96a914bd733793b896a51261726b6164696b6f2d737761702d76322d31";
+// (ok (list (tuple (a 0x0102) (b (some u1))) (tuple (a 0x03) (b none))))
+const NESTED: &str = "0x070b000000020c0000000201610200000002010201620a01000000000000000000000000000000010c000000020161020000000103016209";
+
+/// Number of values in the stream, most of them repeats of a few constants.
+const EVENT_COUNT: usize = 3000;
+
+fn event_stream() -> Vec<Box<[u8]>> {
+    (0..EVENT_COUNT)
+        .map(|i| match i % 10 {
+            // one in ten values is unique
+            0 => decode_hex(format!("0x01{:032x}", i)).unwrap(),
+            x if x % 2 == 0 => decode_hex(SWAP_EVENT).unwrap(),
+            _ => decode_hex(NESTED).unwrap(),
+        })
+        .collect()
+}
+
+fn bench_event_stream_decoding(c: &mut Criterion) {
+    let values = event_stream();
+    let mut group = c.benchmark_group("Clarity event stream decoding");
+    group.throughput(Throughput::Elements(values.len() as u64));
+
+    group.bench_function("ClarityValue::deserialize", |b| {
+        b.iter(|| {
+            for bytes in values.iter() {
+                let mut cursor = Cursor::new(bytes.as_ref());
+                ClarityValue::deserialize(&mut cursor, true).unwrap();
+            }
+        })
+    });
+
+    group.bench_function("DecodeCache::decode", |b| {
+        let mut cache = DecodeCache::new(64);
+        b.iter(|| {
+            for bytes in values.iter() {
+                cache.decode(bytes).unwrap();
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_event_stream_decoding);
+criterion_main!(benches);
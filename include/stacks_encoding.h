@@ -0,0 +1,57 @@
+#ifndef STACKS_ENCODING_H
+#define STACKS_ENCODING_H
+
+/* Generated with cbindgen, do not edit by hand. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define CABI_OK 0
+
+#define CABI_ERR_NULL_POINTER -1
+
+#define CABI_ERR_INVALID_INPUT -2
+
+#define CABI_ERR_BUFFER_TOO_SMALL -3
+
+/**
+ * Encode a c32check Stacks address from a version byte and 20 byte hash160.
+ *
+ * # Safety
+ * `hash160` must point to `hash160_len` readable bytes, `out` to `out_cap` writable bytes,
+ * and `out_len` must be a valid pointer.
+ */
+int32_t c32_address_encode(uint8_t version,
+                           const uint8_t *hash160,
+                           size_t hash160_len,
+                           uint8_t *out,
+                           size_t out_cap,
+                           size_t *out_len);
+
+/**
+ * Decode a c32check Stacks address into its version byte and 20 byte hash160.
+ *
+ * # Safety
+ * `address` must point to `address_len` readable bytes, `version_out` must be a valid
+ * pointer, and `hash160_out` must point to 20 writable bytes.
+ */
+int32_t c32_address_decode(const uint8_t *address,
+                           size_t address_len,
+                           uint8_t *version_out,
+                           uint8_t *hash160_out);
+
+/**
+ * Decode a serialized Clarity value into a JSON string, shaped like the `decodeClarityValue`
+ * JS output.
+ *
+ * # Safety
+ * `bytes` must point to `bytes_len` readable bytes, `out` to `out_cap` writable bytes,
+ * and `out_len` must be a valid pointer.
+ */
+int32_t clarity_decode_to_json(const uint8_t *bytes,
+                               size_t bytes_len,
+                               uint8_t *out,
+                               size_t out_cap,
+                               size_t *out_len);
+
+#endif /* STACKS_ENCODING_H */
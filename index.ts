@@ -21,7 +21,31 @@ export interface DecodedTxResult {
     post_conditions: TxPostCondition[];
     /** Hex string */
     post_conditions_buffer: string;
-    payload: TxPayloadTokenTransfer | TxPayloadSmartContract | TxPayloadContractCall | TxPayloadPoisonMicroblock | TxPayloadCoinbase;
+    payload: TxPayloadTokenTransfer | TxPayloadSmartContract | TxPayloadVersionedSmartContract | TxPayloadContractCall | TxPayloadPoisonMicroblock | TxPayloadCoinbase | TxPayloadTenureChange;
+}
+
+export interface DecodedPrintEvent<T extends ClarityValue = ClarityValue> {
+    /** Matches the stacks-node event observer `contract_event` type */
+    type: 'contract_event';
+    contract_event: {
+        contract_identifier: string;
+        topic: 'print';
+        value: T;
+        /** Hex encoded string of the serialized Clarity value */
+        raw_value: string;
+    };
+}
+
+export interface DecodedStxTransferEvent {
+    /** Matches the stacks-node event observer `stx_transfer_event` type */
+    type: 'stx_transfer_event';
+    stx_transfer_event: {
+        sender: string;
+        recipient: string;
+        amount: string;
+        /** The `stx-transfer-memo?` memo, normalized like `memoToString`. Absent without a memo. */
+        memo?: string;
+    };
 }
 
 export enum PostConditionAssetInfoID {
@@ -156,6 +180,13 @@ export interface TxPayloadSmartContract {
     code_body: string;
 }
 
+export interface TxPayloadVersionedSmartContract {
+    type_id: TxPayloadTypeID.VersionedSmartContract;
+    clarity_version: number;
+    contract_name: string;
+    code_body: string;
+}
+
 export interface TxPayloadContractCall {
     type_id: TxPayloadTypeID.ContractCall;
     address_version: number;
@@ -181,12 +212,37 @@ export interface TxPayloadCoinbase {
     payload_buffer: string;
 }
 
+export enum TenureChangeCause {
+    /** A valid winning block-commit, the tenure starts */
+    BlockFound = 0,
+    /** The current tenure's execution budget is extended */
+    Extended = 1,
+}
+
+export interface TxPayloadTenureChange {
+    type_id: TxPayloadTypeID.TenureChange;
+    /** Hex string */
+    tenure_consensus_hash: string;
+    /** Hex string */
+    prev_tenure_consensus_hash: string;
+    /** Hex string */
+    burn_view_consensus_hash: string;
+    /** Hex string */
+    previous_tenure_end: string;
+    previous_tenure_blocks: number;
+    cause: TenureChangeCause;
+    /** Hex string */
+    pubkey_hash: string;
+}
+
 export enum TxPayloadTypeID {
     TokenTransfer = 0,
     SmartContract = 1,
     ContractCall = 2,
     PoisonMicroblock = 3,
     Coinbase = 4,
+    VersionedSmartContract = 6,
+    TenureChange = 7,
 }
 
 export enum PostConditionAuthFlag {
@@ -360,8 +416,11 @@ export interface ClarityValueBoolFalse extends ClarityValueCommon {
 
 export interface ClarityValueBuffer extends ClarityValueCommon {
     type_id: ClarityTypeID.Buffer;
-    /** Hex string */
-    buffer: string;
+    /**
+     * Hex string, or base64 string when decoded with `buffer_encoding: 'base64'`, or a `Buffer`
+     * when decoded with `buffers_as_bytes: true`
+     */
+    buffer: string | Buffer;
 }
 
 export interface ClarityValueList<T extends ClarityValue = ClarityValue> extends ClarityValueCommon {
@@ -1,4 +1,4 @@
-import type { DecodedPostConditionsResult, DecodedTxResult, ClarityValue, ClarityValueAbstract } from ".";
+import type { DecodedPostConditionsResult, DecodedTxResult, ClarityValue, ClarityValueAbstract, DecodedPrintEvent, DecodedStxTransferEvent } from ".";
 
 export function getVersion(): string;
 
@@ -8,7 +8,14 @@ export function decodeClarityValueToRepr(arg: string | Buffer): string;
 
 export function decodeClarityValueToTypeName(arg: string | Buffer): string;
 
-export function decodeClarityValue<T extends ClarityValue = ClarityValue>(arg: string | Buffer): T;
+export interface DecodeClarityValueOptions {
+    /** How Clarity buffer values are rendered, defaults to `hex` */
+    buffer_encoding?: 'hex' | 'base64';
+    /** Return Clarity buffer values as a Node `Buffer` instead of a string, defaults to `false` */
+    buffers_as_bytes?: boolean;
+}
+
+export function decodeClarityValue<T extends ClarityValue = ClarityValue>(arg: string | Buffer, options?: DecodeClarityValueOptions): T;
 
 /**
  * 
@@ -32,12 +39,19 @@ export function decodeClarityValueList(arg: string | Buffer, deep: true): Clarit
 
 export function decodePostConditions(arg: string | Buffer): DecodedPostConditionsResult;
 
+export function decodePrintEvent<T extends ClarityValue = ClarityValue>(arg: string | Buffer, contractId: string): DecodedPrintEvent<T>;
+
+/** Decodes a stacks-node event observer `stx_transfer_event` JSON string */
+export function decodeStxTransferEvent(eventJson: string): DecodedStxTransferEvent;
+
 export function stacksToBitcoinAddress(stackAddress: string): string;
 
 export function bitcoinToStacksAddress(bitcoinAddress: string): string;
 
 export function isValidStacksAddress(address: string): boolean;
 
+export function isValidContractName(contractName: string): boolean;
+
 export function decodeStacksAddress(address: string): [version: number, hash160: string];
 
 export function decodeClarityValueToPrincipal(clarityValue: string | Buffer) : string;
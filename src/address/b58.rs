@@ -16,7 +16,7 @@
 
 use std::{fmt, str};
 
-use sha2::{Digest, Sha256};
+use crate::hash::sha256d;
 
 static BASE58_CHARS: &'static [u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
 
@@ -158,7 +158,7 @@ pub fn from(data: &str) -> Result<Vec<u8>, String> {
     // Build in base 256
     for d58 in data.bytes() {
         // Compute "X = X * 58 + next_digit" in base 256
-        if d58 as usize > BASE58_DIGITS.len() {
+        if d58 as usize >= BASE58_DIGITS.len() {
             return Err(format!("invalid base58 character 0x{:x}", d58));
         }
         let mut carry = match BASE58_DIGITS[d58 as usize] {
@@ -187,7 +187,7 @@ pub fn from(data: &str) -> Result<Vec<u8>, String> {
 }
 
 pub fn get_checksum(data: &[u8]) -> u32 {
-    let hash = Sha256::digest(Sha256::digest(data));
+    let hash = sha256d(data);
     u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]])
 }
 
@@ -284,7 +284,7 @@ pub fn encode_slice(data: &[u8]) -> String {
 /// Obtain a string with the base58check encoding of a slice
 /// (Tack the first 4 256-digits of the object's Bitcoin hash onto the end.)
 pub fn check_encode_slice(data: &[u8]) -> String {
-    let checksum = Sha256::digest(Sha256::digest(data));
+    let checksum = sha256d(data);
     encode_iter(data.iter().cloned().chain(checksum[0..4].iter().cloned()))
 }
 
@@ -292,7 +292,7 @@ pub fn check_encode_slice(data: &[u8]) -> String {
 /// (Tack the first 4 256-digits of the object's Bitcoin hash onto the end.)
 #[allow(dead_code)]
 pub fn check_encode_slice_to_fmt(fmt: &mut fmt::Formatter, data: &[u8]) -> fmt::Result {
-    let checksum = Sha256::digest(Sha256::digest(data));
+    let checksum = sha256d(data);
     let iter = data.iter().cloned().chain(checksum[0..4].iter().cloned());
     encode_iter_to_fmt(fmt, iter)
 }
@@ -1,9 +1,12 @@
-use sha2::Digest;
-use sha2::Sha256;
+use crate::base64::{decode_base64url, encode_base64url};
+use crate::hash::{checksum_bit_distance, sha256d};
 use std::convert::TryFrom;
 use std::convert::TryInto;
 
-const C32_CHARACTERS: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+#[cfg(feature = "smallvec")]
+use smallvec::SmallVec;
+
+pub(crate) const C32_CHARACTERS: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
 
 /// C32 chars as an array, indexed by their ASCII code for O(1) lookups.
 /// Supports lookups by uppercase and lowercase.
@@ -31,7 +34,7 @@ const C32_CHARACTERS: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
 ///     table[pair.0.to_ascii_lowercase() as usize] = Some(i as u8);
 /// }
 /// ```
-const C32_CHARACTERS_MAP: [Option<u8>; 128] = [
+pub(crate) const C32_CHARACTERS_MAP: [Option<u8>; 128] = [
     None,
     None,
     None,
@@ -171,6 +174,13 @@ fn c32_encode(input_bytes: &[u8]) -> String {
     String::from_utf8(buffer).unwrap()
 }
 
+/// Same as `c32_encode`, for bytes produced by an iterator. The encoding works from the last byte
+/// backwards, so the bytes are collected into a buffer before encoding.
+pub fn c32_encode_iter<I: Iterator<Item = u8>>(iter: I) -> String {
+    let input_bytes: Vec<u8> = iter.collect();
+    c32_encode(&input_bytes)
+}
+
 /// Calculate the maximum C32 encoded output size given an input size.
 /// Each C32 character encodes 5 bits.
 pub fn get_max_c32_encode_output_len(input_len: usize) -> usize {
@@ -206,19 +216,28 @@ pub fn c32_encode_to_buffer(input_bytes: &[u8], output_buffer: &mut [u8]) -> Res
             min_len
         ))?
     }
-    let mut carry = 0;
-    let mut carry_bits = 0;
+    // Bits of the previous byte not yet written, and how many there are. At the top of each
+    // iteration `carry_bits` is in 0..5 and `carry < 1 << carry_bits`, so:
+    // * `low_bits_to_take` is in 1..=5 and `low_bits << carry_bits` stays below 32
+    // * after adding the 8 new bits, `carry_bits` is in 3..=7, so `8 - carry_bits` is in 1..=5
+    // * emitting a second character when `carry_bits >= 5` brings it back into 0..3
+    // Every shift is therefore by less than 8, the width of `u8`.
+    let mut carry: u8 = 0;
+    let mut carry_bits: u8 = 0;
     let mut position = 0;
 
     for current_value in input_bytes.iter().rev() {
-        let low_bits_to_take = 5 - carry_bits;
-        let low_bits = current_value & ((1 << low_bits_to_take) - 1);
-        let c32_value = (low_bits << carry_bits) + carry;
+        debug_assert!(carry_bits < 5 && u16::from(carry) < 1 << carry_bits);
+        let low_bits_to_take: u8 = 5 - carry_bits;
+        let low_bits: u8 = current_value & ((1 << low_bits_to_take) - 1);
+        let c32_value: u8 = (low_bits << carry_bits) + carry;
+        debug_assert!(c32_value < 32);
 
         output_buffer[position] = C32_CHARACTERS[c32_value as usize];
         position += 1;
 
         carry_bits = (8 + carry_bits) - 5;
+        debug_assert!((3..8).contains(&carry_bits));
         carry = current_value >> (8 - carry_bits);
 
         if carry_bits >= 5 {
@@ -256,33 +275,98 @@ pub fn c32_encode_to_buffer(input_bytes: &[u8], output_buffer: &mut [u8]) -> Res
     Ok(position)
 }
 
+/// Fails at the first non-ASCII byte, reporting its index.
+fn ensure_ascii(input: &[u8]) -> Result<(), String> {
+    match input.iter().position(|b| !b.is_ascii()) {
+        Some(index) => Err(format!(
+            "Invalid crockford 32 string, non-ascii byte at index {}",
+            index
+        )),
+        None => Ok(()),
+    }
+}
+
 #[allow(dead_code)]
 fn c32_decode(input_str: &str) -> Result<Vec<u8>, String> {
     // must be ASCII
-    if !input_str.is_ascii() {
-        return Err("Invalid crockford 32 string".into());
-    }
+    ensure_ascii(input_str.as_bytes())?;
     c32_decode_ascii(input_str.as_bytes())
 }
 
 fn c32_decode_ascii(input_str: &[u8]) -> Result<Vec<u8>, String> {
-    // let initial_capacity = 1 + ((input_str.len() * 5) / 8);
-    let initial_capacity = input_str.len();
-    let mut result = Vec::with_capacity(initial_capacity);
+    let mut result = Vec::with_capacity(input_str.len());
+    c32_decode_ascii_into(input_str, &mut result)?;
+    Ok(result)
+}
+
+/// Same as `c32_decode`, but also returns how many leading zero bytes were reconstructed from
+/// leading `0` characters, which the encoded value itself doesn't carry.
+pub fn c32_decode_with_info(input_str: &str) -> Result<(Vec<u8>, usize), String> {
+    // must be ASCII
+    ensure_ascii(input_str.as_bytes())?;
+    let mut result = Vec::with_capacity(input_str.len());
+    let leading_zeros = c32_decode_ascii_into(input_str.as_bytes(), &mut result)?;
+    Ok((result, leading_zeros))
+}
+
+/// Same as `c32_decode`, but outputs of up to 32 bytes (e.g. address hashes) are kept inline
+/// on the stack instead of being heap allocated.
+#[cfg(feature = "smallvec")]
+#[allow(dead_code)]
+pub fn c32_decode_small(input_str: &str) -> Result<SmallVec<[u8; 32]>, String> {
+    // must be ASCII
+    ensure_ascii(input_str.as_bytes())?;
+    let mut result = SmallVec::new();
+    c32_decode_ascii_into(input_str.as_bytes(), &mut result)?;
+    Ok(result)
+}
+
+/// Byte buffer that c32 decoding can write into.
+trait C32DecodeOutput {
+    fn push(&mut self, value: u8);
+    fn truncate(&mut self, len: usize);
+    fn as_mut_slice(&mut self) -> &mut [u8];
+}
+
+impl C32DecodeOutput for Vec<u8> {
+    fn push(&mut self, value: u8) {
+        Vec::push(self, value)
+    }
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len)
+    }
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        Vec::as_mut_slice(self)
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl C32DecodeOutput for SmallVec<[u8; 32]> {
+    fn push(&mut self, value: u8) {
+        SmallVec::push(self, value)
+    }
+    fn truncate(&mut self, len: usize) {
+        SmallVec::truncate(self, len)
+    }
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        SmallVec::as_mut_slice(self)
+    }
+}
+
+/// Returns the number of leading zero bytes reconstructed from leading `0` characters.
+fn c32_decode_ascii_into<T: C32DecodeOutput>(
+    input_str: &[u8],
+    result: &mut T,
+) -> Result<usize, String> {
     let mut carry: u16 = 0;
     let mut carry_bits = 0; // can be up to 5
 
-    let mut c32_digits = vec![0u8; input_str.len()];
-
-    for (i, x) in input_str.iter().rev().enumerate() {
-        c32_digits[i] = match C32_CHARACTERS_MAP.get(*x as usize) {
+    for x in input_str.iter().rev() {
+        let current_5bit = match C32_CHARACTERS_MAP.get(*x as usize) {
             Some(&Some(v)) => v,
             _ => Err("Invalid crockford 32 string".to_string())?,
         };
-    }
-
-    for current_5bit in &c32_digits {
-        carry += (*current_5bit as u16) << carry_bits;
+        carry += (current_5bit as u16) << carry_bits;
         carry_bits += 5;
 
         if carry_bits >= 8 {
@@ -297,68 +381,106 @@ fn c32_decode_ascii(input_str: &[u8]) -> Result<Vec<u8>, String> {
     }
 
     // remove leading zeros from Vec<u8> encoding
-    let mut i = result.len();
-    while i > 0 && result[i - 1] == 0 {
+    let mut i = result.as_mut_slice().len();
+    while i > 0 && result.as_mut_slice()[i - 1] == 0 {
         i -= 1;
-        result.truncate(i);
     }
+    result.truncate(i);
 
-    // add leading zeros from input.
-    for current_value in c32_digits.iter().rev() {
-        if *current_value == 0 {
+    // add leading zeros from input, all chars have already been validated.
+    let mut leading_zeros = 0;
+    for x in input_str {
+        if C32_CHARACTERS_MAP[*x as usize] == Some(0) {
             result.push(0);
+            leading_zeros += 1;
         } else {
             break;
         }
     }
 
-    result.reverse();
-    Ok(result)
+    result.as_mut_slice().reverse();
+    Ok(leading_zeros)
 }
 
-fn c32_check_encode_prefixed(version: u8, data: &[u8], prefix: u8) -> Result<Vec<u8>, String> {
-    if version >= 32 {
-        return Err(format!("Invalid version {}", version));
+/// The c32 character for an address version, which must fit in 5 bits.
+pub fn version_to_c32_char(version: u8) -> Result<u8, String> {
+    C32_CHARACTERS
+        .get(version as usize)
+        .copied()
+        .ok_or_else(|| format!("Invalid version {}", version))
+}
+
+/// The address version for a c32 character, accepting the same lowercase and ambiguous
+/// characters (`O`, `L`, `I`) as the decoder.
+pub fn c32_char_to_version(c: u8) -> Result<u8, String> {
+    match C32_CHARACTERS_MAP.get(c as usize) {
+        Some(&Some(version)) => Ok(version),
+        _ => Err(format!(
+            "Invalid crockford 32 version character {:?}",
+            c as char
+        )),
     }
+}
 
-    let data_len = data.len();
-    let mut buffer: Vec<u8> = vec![0; data_len + 4];
+/// Payloads (version + data + 4 byte checksum) up to this size are assembled on the stack.
+const CHECK_ENCODE_STACK_BUFFER_LEN: usize = 64;
 
-    let checksum_buffer = Sha256::digest({
-        Sha256::new()
-            .chain_update(&[version])
-            .chain_update(data)
-            .finalize()
-    });
+fn c32_check_encode_prefixed(version: u8, data: &[u8], prefix: u8) -> Result<Vec<u8>, String> {
+    let mut result = Vec::new();
+    c32_check_encode_prefixed_into(version, data, prefix, &mut result)?;
+    Ok(result)
+}
 
-    buffer[..data_len].copy_from_slice(data);
-    buffer[data_len..(data_len + 4)].copy_from_slice(&checksum_buffer[0..4]);
+/// C32check encodes into `result`, replacing its contents but reusing its allocation.
+fn c32_check_encode_prefixed_into(
+    version: u8,
+    data: &[u8],
+    prefix: u8,
+    result: &mut Vec<u8>,
+) -> Result<(), String> {
+    let version_char = version_to_c32_char(version)?;
+
+    // version byte + data + 4 byte checksum, the version is only used for the checksum
+    let data_len = data.len();
+    let mut stack_buffer = [0u8; CHECK_ENCODE_STACK_BUFFER_LEN];
+    let mut heap_buffer: Vec<u8>;
+    let versioned_buffer: &mut [u8] = if data_len + 5 <= CHECK_ENCODE_STACK_BUFFER_LEN {
+        &mut stack_buffer[..data_len + 5]
+    } else {
+        heap_buffer = vec![0; data_len + 5];
+        &mut heap_buffer
+    };
+
+    versioned_buffer[0] = version;
+    versioned_buffer[1..(data_len + 1)].copy_from_slice(data);
+    let checksum_buffer = sha256d(&versioned_buffer[..(data_len + 1)]);
+    versioned_buffer[(data_len + 1)..].copy_from_slice(&checksum_buffer[0..4]);
+    let buffer = &versioned_buffer[1..];
 
     let capacity = get_max_c32_encode_output_len(buffer.len()) + 2;
-    let mut result: Vec<u8> = vec![0; capacity];
+    result.clear();
+    result.resize(capacity, 0);
 
     result[0] = prefix;
-    result[1] = C32_CHARACTERS[version as usize];
-    let bytes_written = c32_encode_to_buffer(&buffer, &mut result[2..])?;
+    result[1] = version_char;
+    let bytes_written = c32_encode_to_buffer(buffer, &mut result[2..])?;
     result.truncate(bytes_written + 2);
-    Ok(result)
+    Ok(())
 }
 
+const MISSING_PAYLOAD_ERROR: &str =
+    "Invalid crockford 32 string, address too short, missing payload";
+
+/// Expects input that has already been checked with `ensure_ascii`.
 fn c32_check_decode<TOutput>(check_data_unsanitized: &str) -> Result<(u8, TOutput), String>
 where
     TOutput: for<'a> TryFrom<&'a [u8]>,
 {
-    // must be ASCII
-    if !check_data_unsanitized.is_ascii() {
-        return Err("Invalid crockford 32 string, must be ascii".to_string());
-    }
-
-    if check_data_unsanitized.len() < 2 {
-        return Err("Invalid crockford 32 string, size less than 2".to_string());
-    }
-
-    let ascii_bytes = check_data_unsanitized.as_bytes();
-    let (version, data) = ascii_bytes.split_first().unwrap();
+    let (version, data) = match check_data_unsanitized.as_bytes().split_first() {
+        Some((version, data)) if !data.is_empty() => (version, data),
+        Some(_) => return Err(MISSING_PAYLOAD_ERROR.to_string()),
+        None => return Err("Invalid crockford 32 string, size less than 2".to_string()),
+    };
 
     let data_sum_bytes = c32_decode_ascii(data)?;
     if data_sum_bytes.len() < 4 {
@@ -366,13 +488,19 @@ where
     }
 
     let (data_bytes, expected_sum) = data_sum_bytes.split_at(data_sum_bytes.len() - 4);
-    let decoded_version = c32_decode_ascii(&[*version]).unwrap();
-    let computed_sum = Sha256::digest(
-        Sha256::new()
-            .chain_update(&decoded_version)
-            .chain_update(&data_bytes)
-            .finalize(),
-    );
+    let version = c32_char_to_version(*version)?;
+    let versioned_len = 1 + data_bytes.len();
+    let mut stack_buffer = [0u8; CHECK_ENCODE_STACK_BUFFER_LEN];
+    let mut heap_buffer: Vec<u8>;
+    let versioned_data: &mut [u8] = if versioned_len <= CHECK_ENCODE_STACK_BUFFER_LEN {
+        &mut stack_buffer[..versioned_len]
+    } else {
+        heap_buffer = vec![0; versioned_len];
+        &mut heap_buffer
+    };
+    versioned_data[0] = version;
+    versioned_data[1..].copy_from_slice(data_bytes);
+    let computed_sum = sha256d(versioned_data);
     let checksum_ok = {
         computed_sum[0] == expected_sum[0]
             && computed_sum[1] == expected_sum[1]
@@ -391,12 +519,13 @@ where
             | ((expected_sum[3] as u32) << 24);
 
         return Err(format!(
-            "base58ck checksum 0x{:x} does not match expected 0x{:x}",
-            computed_sum_u32, expected_sum_u32
+            "base58ck checksum 0x{:x} does not match expected 0x{:x} (bit distance {})",
+            computed_sum_u32,
+            expected_sum_u32,
+            checksum_bit_distance(computed_sum_u32, expected_sum_u32)
         ));
     }
 
-    let version = decoded_version[0];
     let data: TOutput = data_bytes
         .try_into()
         .map_err(|_| format!("Could not convert decoded c32 bytes"))?;
@@ -404,18 +533,192 @@ where
 }
 
 pub fn c32_address_decode(c32_address_str: &str) -> Result<(u8, [u8; 20]), String> {
-    if c32_address_str.len() <= 5 {
+    if c32_address_str.len() <= 2 {
+        // just the `S` prefix and version character, or less
+        Err(MISSING_PAYLOAD_ERROR.into())
+    } else if c32_address_str.len() <= 5 {
         Err("Invalid crockford 32 string, address string smaller than 5 bytes".into())
     } else {
+        // must be ASCII, also guarantees that slicing off the `S` prefix is on a char boundary
+        ensure_ascii(c32_address_str.as_bytes())?;
         c32_check_decode(&c32_address_str[1..])
     }
 }
 
+/// A decoded address, which may have a payload other than a 20 byte hash160.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressInfo {
+    pub version: u8,
+    pub payload: Vec<u8>,
+    pub payload_len: usize,
+    /// Whether the payload is a 20 byte hash160, as for every standard address.
+    pub is_standard_length: bool,
+}
+
+/// Decodes an address without requiring a 20 byte payload, see `AddressInfo`.
+pub fn c32_address_info(addr: &str) -> Result<AddressInfo, String> {
+    if addr.len() <= 2 {
+        return Err(MISSING_PAYLOAD_ERROR.into());
+    }
+    ensure_ascii(addr.as_bytes())?;
+    let (version, payload): (u8, Vec<u8>) = c32_check_decode(&addr[1..])?;
+    Ok(AddressInfo {
+        version,
+        payload_len: payload.len(),
+        is_standard_length: payload.len() == 20,
+        payload,
+    })
+}
+
+/// Same as `c32_address_decode`, but a payload that isn't a 20 byte hash160 is reported with its
+/// length instead of a generic conversion error.
+pub fn c32_address_decode_strict(addr: &str) -> Result<(u8, [u8; 20]), String> {
+    let info = c32_address_info(addr)?;
+    let hash160: [u8; 20] = info.payload.as_slice().try_into().map_err(|_| {
+        format!(
+            "Invalid address payload length {}, expected 20 bytes",
+            info.payload_len
+        )
+    })?;
+    Ok((info.version, hash160))
+}
+
+/// Returns the canonical form of an address (uppercase, with ambiguous `O`/`I`/`L` characters
+/// substituted), plus whether it differs from the input.
+pub fn c32_normalize_if_needed(addr: &str) -> Result<(String, bool), String> {
+    let (version, hash160) = c32_address_decode(addr)?;
+    let canonical = c32_address(version, &hash160)?;
+    let changed = canonical != addr;
+    Ok((canonical, changed))
+}
+
+/// True if the address has an all-zero hash160, i.e. a burn address like
+/// `SP000000000000000000002Q6VF78` that nobody holds the keys for.
+pub fn is_burn_address(addr: &str) -> Result<bool, String> {
+    let (_version, hash160) = c32_address_decode(addr)?;
+    Ok(hash160.iter().all(|b| *b == 0))
+}
+
+/// For inputs where a contract principal is expected: true if `input` is a valid standard
+/// address without a `.contract-name`, i.e. the contract name was likely dropped when pasting.
+pub fn looks_like_truncated_contract(input: &str) -> bool {
+    !input.contains('.') && c32_address_decode(input).is_ok()
+}
+
+/// The 4 checksum bytes of an address, the first bytes of the double SHA-256 of its version and
+/// hash160. The address is decoded, so the checksum is verified first.
+pub fn c32_address_checksum(addr: &str) -> Result<[u8; 4], String> {
+    let (version, hash160) = c32_address_decode(addr)?;
+    let mut versioned = [0u8; 21];
+    versioned[0] = version;
+    versioned[1..].copy_from_slice(&hash160);
+    let mut checksum = [0u8; 4];
+    checksum.copy_from_slice(&sha256d(&versioned)[..4]);
+    Ok(checksum)
+}
+
+/// Validates each address, keeping the decode error of the invalid ones, for reporting which
+/// entries of a list are bad.
+pub fn validate_addresses(addrs: &[&str]) -> Vec<Result<(), String>> {
+    addrs
+        .iter()
+        .map(|addr| c32_address_decode(addr).map(|_| ()))
+        .collect()
+}
+
+/// True if both addresses have the same hash160, ignoring the version, e.g. the mainnet and
+/// testnet forms of one key.
+pub fn same_account(a: &str, b: &str) -> Result<bool, String> {
+    let (_version_a, hash160_a) = c32_address_decode(a)?;
+    let (_version_b, hash160_b) = c32_address_decode(b)?;
+    Ok(hash160_a == hash160_b)
+}
+
+/// Re-encodes an address onto another version with the same hash160, e.g. a standard `SP`
+/// address as its multisig `SM` form. The checksum is recomputed for the new version.
+pub fn c32_address_reversion(addr: &str, new_version: u8) -> Result<String, String> {
+    let (_version, hash160) = c32_address_decode(addr)?;
+    c32_address(new_version, &hash160)
+}
+
+/// A `u64` tag for an address, for keying probabilistic filters like Bloom filters without
+/// storing the full string. The top byte is the version and the rest are the first 7 bytes of
+/// the hash160, which is already uniformly distributed. This is a filter tag, not a unique key:
+/// distinct addresses can share a tag, so a match must be confirmed against the full address.
+pub fn c32_address_tag(addr: &str) -> Result<u64, String> {
+    let (version, hash160) = c32_address_decode(addr)?;
+    let mut tag = [0u8; 8];
+    tag[0] = version;
+    tag[1..].copy_from_slice(&hash160[..7]);
+    Ok(u64::from_be_bytes(tag))
+}
+
+/// The shortened form of an address shown by explorers, the first 5 and last 4 characters joined
+/// by `...`, e.g. `SP2J6...9EJ7`. The address is validated first.
+pub fn c32_display_short(addr: &str) -> Result<String, String> {
+    c32_address_decode(addr)?;
+    // c32 addresses are ASCII, so these are char boundaries
+    Ok(format!("{}...{}", &addr[..5], &addr[addr.len() - 4..]))
+}
+
+/// Length of the raw `version + hash160 + checksum` form of an address.
+const COMPACT_ADDRESS_LEN: usize = 25;
+
+/// Encodes an address as the unpadded base64url of its raw 25 byte `version + hash160 + checksum`
+/// form. This is shorter than the c32 string, for use in dense contexts like QR codes and URLs.
+pub fn address_to_compact(addr: &str) -> Result<String, String> {
+    let (version, hash160) = c32_address_decode(addr)?;
+    let mut raw = [0u8; COMPACT_ADDRESS_LEN];
+    raw[0] = version;
+    raw[1..21].copy_from_slice(&hash160);
+    let checksum = sha256d(&raw[..21]);
+    raw[21..].copy_from_slice(&checksum[..4]);
+    Ok(encode_base64url(&raw))
+}
+
+/// Inverse of `address_to_compact`, returns the c32 address string.
+pub fn compact_to_address(compact: &str) -> Result<String, String> {
+    let raw = decode_base64url(compact)?;
+    let (version, hash160) = c32_check_decode_bytes(&raw)?;
+    c32_address(version, &hash160)
+}
+
+/// Decodes the raw 25 byte `version + hash160 + checksum` form of an address, verifying the
+/// checksum, and returns the version and hash160.
+pub fn c32_check_decode_bytes(raw: &[u8]) -> Result<(u8, Vec<u8>), String> {
+    if raw.len() != COMPACT_ADDRESS_LEN {
+        return Err(format!(
+            "Invalid c32check bytes, expected {} bytes, got {}",
+            COMPACT_ADDRESS_LEN,
+            raw.len()
+        ));
+    }
+    let (versioned_data, expected_sum) = raw.split_at(COMPACT_ADDRESS_LEN - 4);
+    let computed_sum = sha256d(versioned_data);
+    if computed_sum[..4] != *expected_sum {
+        return Err("Invalid c32check bytes, checksum mismatch".to_string());
+    }
+    Ok((versioned_data[0], versioned_data[1..].to_vec()))
+}
+
 pub fn c32_address(version: u8, data: &[u8]) -> Result<String, String> {
     let bytes = c32_check_encode_prefixed(version, data, b'S')?;
     Ok(String::from_utf8(bytes).unwrap())
 }
 
+/// Same as `c32_address`, but clears `out` and writes the address into it so that repeated
+/// calls can reuse a single allocation.
+pub fn c32_address_into(version: u8, data: &[u8], out: &mut String) -> Result<(), String> {
+    // Safety: the encoder only ever writes ASCII bytes (the prefix and `C32_CHARACTERS`),
+    // and the buffer is cleared on error, so `out` always holds valid UTF-8.
+    let out_bytes = unsafe { out.as_mut_vec() };
+    let result = c32_check_encode_prefixed_into(version, data, b'S', out_bytes);
+    if result.is_err() {
+        out_bytes.clear();
+    }
+    result
+}
+
 #[cfg(test)]
 mod test {
     use crate::hex::decode_hex;
@@ -495,6 +798,29 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_address_into() {
+        let mut out = String::from("a previous value that is longer than any c32 address");
+        let inputs = [
+            (22, "a46ff88886c2ef9762d970b4d2c63678835bd39d"),
+            (26, "0000000000000000000000000000000000000001"),
+            (20, "1000000000000000000000000000000000000000"),
+            (21, ""),
+        ];
+        for (version, hex_str) in inputs.iter() {
+            let bytes = decode_hex(hex_str).unwrap();
+            c32_address_into(*version, &bytes, &mut out).unwrap();
+            assert_eq!(out, c32_address(*version, &bytes).unwrap());
+        }
+
+        let long_data = [0xab; 100];
+        c32_address_into(22, &long_data, &mut out).unwrap();
+        assert_eq!(out, c32_address(22, &long_data).unwrap());
+
+        assert!(c32_address_into(32, &long_data, &mut out).is_err());
+        assert_eq!(out, "");
+    }
+
     #[test]
     fn test_simple() {
         let hex_strings = &[
@@ -557,6 +883,212 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_encode_iter() {
+        let inputs: [&[u8]; 4] = [&[], &[0, 0, 1], &[0xff; 21], b"hello world"];
+        for input in inputs.iter() {
+            assert_eq!(c32_encode_iter(input.iter().copied()), c32_encode(input));
+        }
+        assert_eq!(
+            c32_encode_iter((0..20u8).map(|i| i * 7)),
+            c32_encode(&(0..20u8).map(|i| i * 7).collect::<Vec<_>>())
+        );
+    }
+
+    #[test]
+    fn test_encode_output_len() {
+        // deterministic xorshift, so failures are reproducible
+        let mut state = 0x2545f491u32;
+        let mut next_byte = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        };
+        for len in 0..=64 {
+            let random: Vec<u8> = (0..len).map(|_| next_byte()).collect();
+            let mut leading_zeros = random.clone();
+            leading_zeros[..len / 2].fill(0);
+            let patterns = [vec![0u8; len], vec![0xffu8; len], random, leading_zeros];
+            for input in patterns.iter() {
+                let capacity = get_max_c32_encode_output_len(len);
+                let mut buffer = vec![0; capacity];
+                let written = c32_encode_to_buffer(input, &mut buffer).unwrap();
+                assert!(written <= capacity, "{:?}", input);
+                let encoded = std::str::from_utf8(&buffer[..written]).unwrap();
+                assert_eq!(&c32_decode(encoded).unwrap(), input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_checksum_bit_distance() {
+        let (version, hash160) =
+            c32_address_decode("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").unwrap();
+        let checksum = sha256d(&[&[version][..], &hash160[..]].concat());
+
+        // flip the lowest bit of the last checksum byte
+        let mut data = hash160.to_vec();
+        data.extend_from_slice(&checksum[..4]);
+        *data.last_mut().unwrap() ^= 0x01;
+        let address = format!("SP{}", c32_encode(&data));
+        let err = c32_address_decode(&address).unwrap_err();
+        assert!(err.ends_with("(bit distance 1)"), "{}", err);
+
+        assert_eq!(checksum_bit_distance(0x1234, 0x1234), 0);
+        assert_eq!(checksum_bit_distance(0x0000_0001, 0x8000_0001), 1);
+        assert_eq!(checksum_bit_distance(0, u32::MAX), 32);
+    }
+
+    #[test]
+    fn test_check_decode_short_input() {
+        for input in ["", "P", "P2", "00", "PZ", "P2J", "000", "ZZZ", "P\u{e9}"].iter() {
+            let result = c32_check_decode::<Vec<u8>>(input);
+            assert!(result.is_err(), "{}", input);
+        }
+        assert_eq!(
+            c32_check_decode::<Vec<u8>>("P2").unwrap_err(),
+            "Invalid crockford 32 string, decoded byte length less than 4"
+        );
+        assert_eq!(
+            c32_check_decode::<Vec<u8>>("P").unwrap_err(),
+            MISSING_PAYLOAD_ERROR
+        );
+        assert_eq!(
+            c32_check_decode::<Vec<u8>>("").unwrap_err(),
+            "Invalid crockford 32 string, size less than 2"
+        );
+    }
+
+    #[test]
+    fn test_encode_all_ones_round_trip() {
+        // 0xff bytes carry the maximum number of set bits through every shift
+        for len in 0..=40 {
+            let input = vec![0xffu8; len];
+            let encoded = c32_encode(&input);
+            assert_eq!(c32_decode(&encoded).unwrap(), input, "length {}", len);
+        }
+    }
+
+    #[test]
+    fn test_version_c32_char() {
+        for version in 0..32 {
+            let c = version_to_c32_char(version).unwrap();
+            assert_eq!(c, C32_CHARACTERS[version as usize]);
+            assert_eq!(c32_char_to_version(c).unwrap(), version);
+            assert_eq!(
+                c32_char_to_version(c.to_ascii_lowercase()).unwrap(),
+                version
+            );
+        }
+        assert_eq!(c32_char_to_version(b'O').unwrap(), 0);
+        assert_eq!(c32_char_to_version(b'i').unwrap(), 1);
+        for version in 32..=255 {
+            assert!(version_to_c32_char(version).is_err());
+        }
+        assert!(c32_char_to_version(b'U').is_err());
+        assert!(c32_char_to_version(0xc3).is_err());
+        assert!(c32_address(32, &[0; 20]).is_err());
+    }
+
+    #[test]
+    fn test_address_info() {
+        let info = c32_address_info("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").unwrap();
+        assert_eq!(info.version, 22);
+        assert_eq!(info.payload_len, 20);
+        assert!(info.is_standard_length);
+
+        let long = c32_address(22, &[0x11; 21]).unwrap();
+        let info = c32_address_info(&long).unwrap();
+        assert_eq!(info.payload, vec![0x11; 21]);
+        assert_eq!(info.payload_len, 21);
+        assert!(!info.is_standard_length);
+    }
+
+    #[test]
+    fn test_address_decode_strict() {
+        let addr = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7";
+        assert_eq!(
+            c32_address_decode_strict(addr).unwrap(),
+            c32_address_decode(addr).unwrap()
+        );
+
+        let oversized = c32_address(22, &[0x11; 21]).unwrap();
+        assert_eq!(
+            c32_address_decode_strict(&oversized).unwrap_err(),
+            "Invalid address payload length 21, expected 20 bytes"
+        );
+        let undersized = c32_address(22, &[0x11; 19]).unwrap();
+        assert_eq!(
+            c32_address_decode_strict(&undersized).unwrap_err(),
+            "Invalid address payload length 19, expected 20 bytes"
+        );
+        assert!(c32_address_decode_strict("S0").is_err());
+    }
+
+    #[test]
+    fn test_address_missing_payload() {
+        for input in ["S0", "SP", "S", ""].iter() {
+            assert_eq!(
+                c32_address_decode(input).unwrap_err(),
+                MISSING_PAYLOAD_ERROR,
+                "{}",
+                input
+            );
+        }
+        assert_eq!(
+            c32_address_decode("SP2J6").unwrap_err(),
+            "Invalid crockford 32 string, address string smaller than 5 bytes"
+        );
+    }
+
+    #[test]
+    fn test_decode_with_info() {
+        let cases: [(&str, &[u8], usize); 5] = [
+            ("Z", &[0x1f], 0),
+            ("0Z", &[0x00, 0x1f], 1),
+            ("000Z", &[0x00, 0x00, 0x00, 0x1f], 3),
+            ("000", &[0x00, 0x00, 0x00], 3),
+            ("", &[], 0),
+        ];
+        for (input, expected, leading_zeros) in cases.iter() {
+            let (bytes, zeros) = c32_decode_with_info(input).unwrap();
+            assert_eq!(&bytes[..], *expected, "{}", input);
+            assert_eq!(zeros, *leading_zeros, "{}", input);
+            assert_eq!(bytes, c32_decode(input).unwrap());
+        }
+        assert!(c32_decode_with_info("0U").is_err());
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn test_decode_small() {
+        let c32_strs = [
+            "MHQZH246RBQSERPSE2TD5HHPF21NQMWX",
+            "",
+            "00000000000000000001",
+            "20000000000000000000000000000001",
+            "001",
+            "4000000",
+            "O0o1",
+        ];
+        for c32_str in c32_strs.iter() {
+            let decoded = c32_decode_small(c32_str).unwrap();
+            assert_eq!(decoded.as_slice(), c32_decode(c32_str).unwrap().as_slice());
+            assert!(!decoded.spilled());
+        }
+
+        // outputs larger than the inline capacity spill to the heap
+        let long_bytes = [0xabu8; 40];
+        let long_c32 = c32_encode(&long_bytes);
+        let decoded = c32_decode_small(&long_c32).unwrap();
+        assert_eq!(decoded.as_slice(), &long_bytes[..]);
+        assert!(decoded.spilled());
+
+        assert!(c32_decode_small("MHQZ!").is_err());
+        assert!(c32_decode_small("MHQZ\u{e9}").is_err());
+    }
+
     #[test]
     fn test_normalize() {
         let addrs = [
@@ -580,6 +1112,246 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_normalize_if_needed() {
+        let canonical = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7";
+        assert_eq!(
+            c32_normalize_if_needed(canonical).unwrap(),
+            (canonical.to_string(), false)
+        );
+
+        let expected = "S02J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKPVKG2CE";
+        assert_eq!(
+            c32_normalize_if_needed(expected).unwrap(),
+            (expected.to_string(), false)
+        );
+        let ambiguous = [
+            "SO2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKPVKG2CE",
+            "S02J6ZY48GVLEZ5V2V5RB9MP66SW86PYKKPVKG2CE",
+            "s02j6zy48gv1ez5v2v5rb9mp66sw86pykkpvkg2ce",
+            "sO2j6zy48gvlez5v2v5rb9mp66sw86pykkpvkg2ce",
+        ];
+        for addr in ambiguous.iter() {
+            assert_eq!(
+                c32_normalize_if_needed(addr).unwrap(),
+                (expected.to_string(), true)
+            );
+        }
+
+        assert!(c32_normalize_if_needed("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8").is_err());
+    }
+
+    #[test]
+    fn test_is_burn_address() {
+        assert!(is_burn_address("SP000000000000000000002Q6VF78").unwrap());
+        assert!(is_burn_address("ST000000000000000000002AMW42H").unwrap());
+        assert!(!is_burn_address("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").unwrap());
+        assert!(is_burn_address("SP000000000000000000002Q6VF79").is_err());
+    }
+
+    #[test]
+    fn test_looks_like_truncated_contract() {
+        assert!(looks_like_truncated_contract(
+            "SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR"
+        ));
+        assert!(!looks_like_truncated_contract(
+            "SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR.arkadiko-dao"
+        ));
+        assert!(!looks_like_truncated_contract(
+            "SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZS"
+        ));
+        assert!(!looks_like_truncated_contract("arkadiko-dao"));
+        assert!(!looks_like_truncated_contract(""));
+    }
+
+    #[test]
+    fn test_address_checksum() {
+        use sha2::{Digest, Sha256};
+
+        let addr = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7";
+        let (version, hash160) = c32_address_decode(addr).unwrap();
+        let mut versioned = vec![version];
+        versioned.extend_from_slice(&hash160);
+        let expected = Sha256::digest(Sha256::digest(&versioned));
+        assert_eq!(c32_address_checksum(addr).unwrap(), expected[..4]);
+
+        // the checksum is the last 4 bytes of the decoded data
+        let decoded = c32_decode(&addr[2..]).unwrap();
+        assert_eq!(
+            c32_address_checksum(addr).unwrap(),
+            decoded[decoded.len() - 4..]
+        );
+        assert!(c32_address_checksum("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8").is_err());
+    }
+
+    #[test]
+    fn test_validate_addresses() {
+        let results = validate_addresses(&[
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7",
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8",
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJU",
+            "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ",
+            "S",
+        ]);
+        assert_eq!(results.len(), 5);
+        assert!(results[0].is_ok());
+        assert!(results[1]
+            .as_ref()
+            .unwrap_err()
+            .starts_with("base58ck checksum"));
+        assert_eq!(
+            results[2].as_ref().unwrap_err(),
+            "Invalid crockford 32 string"
+        );
+        assert!(results[3].is_ok());
+        assert_eq!(results[4].as_ref().unwrap_err(), MISSING_PAYLOAD_ERROR);
+        assert!(validate_addresses(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_same_account() {
+        let mainnet = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7";
+        let testnet = "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ";
+        assert!(same_account(mainnet, testnet).unwrap());
+        assert!(same_account(mainnet, mainnet).unwrap());
+        assert!(!same_account(mainnet, "SP000000000000000000002Q6VF78").unwrap());
+        assert!(same_account(mainnet, "SP000000000000000000002Q6VF79").is_err());
+    }
+
+    #[test]
+    fn test_address_reversion() {
+        let mainnet = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7";
+        let testnet = "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ";
+        assert_eq!(c32_address_reversion(mainnet, 26).unwrap(), testnet);
+        assert_eq!(c32_address_reversion(testnet, 22).unwrap(), mainnet);
+
+        let multisig = c32_address_reversion(mainnet, 20).unwrap();
+        assert!(multisig.starts_with("SM"));
+        let (version, hash160) = c32_address_decode(&multisig).unwrap();
+        assert_eq!(version, 20);
+        assert_eq!(hash160, c32_address_decode(mainnet).unwrap().1);
+
+        assert_eq!(
+            c32_address_reversion(mainnet, 32).err().unwrap(),
+            "Invalid version 32"
+        );
+        assert!(c32_address_reversion("SP000000000000000000002Q6VF79", 20).is_err());
+    }
+
+    #[test]
+    fn test_address_tag() {
+        let mainnet = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7";
+        let testnet = "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ";
+        let tag = c32_address_tag(mainnet).unwrap();
+        assert_eq!(tag, c32_address_tag(mainnet).unwrap());
+        assert_eq!(tag, 0x16_a46ff88886c2ef);
+        assert_ne!(tag, c32_address_tag(testnet).unwrap());
+        assert_eq!(
+            c32_address_tag("SP000000000000000000002Q6VF78").unwrap(),
+            0x16_00000000000000
+        );
+
+        let tags = (0u8..=255)
+            .map(|i| c32_address(22, &[i; 20]).unwrap())
+            .map(|addr| c32_address_tag(&addr).unwrap())
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(tags.len(), 256);
+        assert!(c32_address_tag("SP000000000000000000002Q6VF79").is_err());
+    }
+
+    #[test]
+    fn test_display_short() {
+        assert_eq!(
+            c32_display_short("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").unwrap(),
+            "SP2J6...9EJ7"
+        );
+        assert_eq!(
+            c32_display_short("SP000000000000000000002Q6VF78").unwrap(),
+            "SP000...VF78"
+        );
+        assert!(c32_display_short("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8").is_err());
+        assert!(c32_display_short("SP2J6").is_err());
+    }
+
+    #[test]
+    fn test_compact_address() {
+        let addrs = [
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7",
+            "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ",
+            "S02J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKPVKG2CE",
+        ];
+        for addr in addrs.iter() {
+            let compact = address_to_compact(addr).unwrap();
+            assert_eq!(compact.len(), 34);
+            assert!(compact.len() < addr.len());
+            assert_eq!(compact_to_address(&compact).unwrap(), *addr);
+        }
+
+        let compact = address_to_compact(addrs[0]).unwrap();
+        assert!(compact_to_address(&compact[..compact.len() - 4]).is_err());
+        // flip a bit in the hash160 so the checksum no longer matches
+        let mut raw = decode_base64url(&compact).unwrap();
+        raw[5] ^= 1;
+        assert!(compact_to_address(&encode_base64url(&raw)).is_err());
+        assert!(address_to_compact("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8").is_err());
+    }
+
+    #[test]
+    fn test_check_decode_bytes() {
+        let (version, hash160) =
+            c32_address_decode("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").unwrap();
+        let compact = address_to_compact("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").unwrap();
+        let mut raw = decode_base64url(&compact).unwrap();
+        assert_eq!(
+            c32_check_decode_bytes(&raw).unwrap(),
+            (version, hash160.to_vec())
+        );
+
+        raw[24] ^= 1;
+        assert_eq!(
+            c32_check_decode_bytes(&raw).unwrap_err(),
+            "Invalid c32check bytes, checksum mismatch"
+        );
+        assert_eq!(
+            c32_check_decode_bytes(&raw[..24]).unwrap_err(),
+            "Invalid c32check bytes, expected 25 bytes, got 24"
+        );
+    }
+
+    #[test]
+    fn test_truncated_address() {
+        let addr = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7";
+        for len in 0..addr.len() {
+            assert!(c32_address_decode(&addr[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_invalid_version_char() {
+        assert!(c32_address_decode("S!2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").is_err());
+        assert!(c32_address_decode("\u{e9}P2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").is_err());
+    }
+
+    #[test]
+    fn test_non_ascii_index() {
+        assert_eq!(
+            c32_address_decode("S\u{1D7D8}2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKPVKG2CE").unwrap_err(),
+            "Invalid crockford 32 string, non-ascii byte at index 1"
+        );
+        assert_eq!(
+            c32_address_decode("\u{e9}P2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").unwrap_err(),
+            "Invalid crockford 32 string, non-ascii byte at index 0"
+        );
+        assert_eq!(
+            c32_address_decode("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ\u{e9}").unwrap_err(),
+            "Invalid crockford 32 string, non-ascii byte at index 40"
+        );
+        assert_eq!(
+            c32_decode("MHQZH\u{e9}246RBQ").unwrap_err(),
+            "Invalid crockford 32 string, non-ascii byte at index 5"
+        );
+    }
+
     #[test]
     fn test_ascii_only() {
         match c32_address_decode("S\u{1D7D8}2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKPVKG2CE") {
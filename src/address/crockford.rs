@@ -0,0 +1,176 @@
+//! Crockford base32 as specified at <https://www.crockford.com/base32.html>, for interop with
+//! other implementations. The spec encodes numbers, so the input bytes are read as one
+//! big-endian number, padded with leading zero bits to a whole number of symbols. Unlike c32,
+//! leading zero bytes need no special handling. It uses the same alphabet as c32.
+
+use super::c32::{C32_CHARACTERS, C32_CHARACTERS_MAP};
+
+/// Extra symbols for check values 32 to 36, after the 32 encoding symbols.
+const CHECK_SYMBOLS: &[u8; 5] = b"*~$=U";
+
+pub fn crockford_encode(input: &[u8]) -> String {
+    // the zero bits before the input, up to a whole number of symbols
+    let padding_bits = (5 - input.len() * 8 % 5) % 5;
+    let mut result = String::with_capacity((input.len() * 8 + padding_bits) / 5);
+    let mut buffer: u16 = 0;
+    let mut buffer_bits = padding_bits;
+    for byte in input {
+        buffer = (buffer << 8) | *byte as u16;
+        buffer_bits += 8;
+        while buffer_bits >= 5 {
+            buffer_bits -= 5;
+            result.push(C32_CHARACTERS[((buffer >> buffer_bits) & 0x1f) as usize] as char);
+        }
+    }
+    result
+}
+
+/// Decodes case-insensitively, reads `O` as `0` and `I` and `L` as `1`, and ignores hyphens.
+/// The bits before the last whole byte only become a leading byte if they aren't zero, so both
+/// the padded output of `crockford_encode` and the shortest symbols for a number, as other
+/// implementations write them, decode to their bytes.
+pub fn crockford_decode(input: &str) -> Result<Vec<u8>, String> {
+    let symbols = input.bytes().filter(|c| *c != b'-');
+    let leading_bits = symbols.clone().count() * 5 % 8;
+    let mut result = Vec::with_capacity(input.len() * 5 / 8 + 1);
+    let mut buffer: u16 = 0;
+    // pad the leading bits to a whole byte, removed below if they're all zero
+    let mut buffer_bits = (8 - leading_bits) % 8;
+    for c in symbols {
+        let value = match C32_CHARACTERS_MAP.get(c as usize) {
+            Some(Some(value)) => *value,
+            _ => return Err(format!("Invalid crockford base32 symbol {:?}", c as char)),
+        };
+        buffer = (buffer << 5) | value as u16;
+        buffer_bits += 5;
+        if buffer_bits >= 8 {
+            buffer_bits -= 8;
+            result.push((buffer >> buffer_bits) as u8);
+        }
+    }
+    if leading_bits > 0 && result.first() == Some(&0) {
+        result.remove(0);
+    }
+    Ok(result)
+}
+
+/// The input read as a big-endian number, modulo 37, as the spec defines the check symbol.
+fn check_value(input: &[u8]) -> u8 {
+    input
+        .iter()
+        .fold(0u32, |acc, byte| (acc * 256 + *byte as u32) % 37) as u8
+}
+
+fn check_symbol(value: u8) -> u8 {
+    match value {
+        0..=31 => C32_CHARACTERS[value as usize],
+        _ => CHECK_SYMBOLS[(value - 32) as usize],
+    }
+}
+
+/// Same as `crockford_encode`, followed by the check symbol.
+pub fn crockford_encode_with_check(input: &[u8]) -> String {
+    let mut result = crockford_encode(input);
+    result.push(check_symbol(check_value(input)) as char);
+    result
+}
+
+/// Same as `crockford_decode`, for input ending with a check symbol that must match the data.
+pub fn crockford_decode_with_check(input: &str) -> Result<Vec<u8>, String> {
+    let (data, check) = match input.as_bytes().split_last() {
+        Some((check, _)) if check.is_ascii() => (&input[..input.len() - 1], *check),
+        _ => return Err("Invalid crockford base32 string, missing check symbol".to_string()),
+    };
+    let expected = match CHECK_SYMBOLS
+        .iter()
+        .position(|c| *c == check.to_ascii_uppercase())
+    {
+        Some(index) => index as u8 + 32,
+        None => match C32_CHARACTERS_MAP[check as usize] {
+            Some(value) => value,
+            None => {
+                return Err(format!(
+                    "Invalid crockford base32 check symbol {:?}",
+                    check as char
+                ))
+            }
+        },
+    };
+    let result = crockford_decode(data)?;
+    let actual = check_value(&result);
+    if actual != expected {
+        return Err(format!(
+            "Crockford base32 check symbol {:?} does not match expected {:?}",
+            check as char,
+            check_symbol(actual) as char
+        ));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crockford_encode() {
+        // the spec encodes the number 1234 as 16J, the padded 16 bit input adds a zero symbol
+        assert_eq!(crockford_encode(&[0x04, 0xd2]), "016J");
+        assert_eq!(crockford_decode("016J").unwrap(), vec![0x04, 0xd2]);
+        assert_eq!(crockford_decode("16J").unwrap(), vec![0x04, 0xd2]);
+
+        let cases: [(&[u8], &str); 6] = [
+            (b"", ""),
+            (&[0x1f], "0Z"),
+            (&[0x20], "10"),
+            (&[0xff], "7Z"),
+            (&[0x01, 0x00], "0080"),
+            (b"foobar", "36DXQP4RBJ"),
+        ];
+        for (input, expected) in cases.iter() {
+            assert_eq!(crockford_encode(input), *expected);
+            assert_eq!(crockford_decode(expected).unwrap(), *input);
+        }
+        // leading zero bytes are kept as is
+        assert_eq!(crockford_encode(&[0, 0, 1]), "00001");
+        assert_eq!(crockford_decode("00001").unwrap(), vec![0, 0, 1]);
+        // the shortest symbols for a number
+        assert_eq!(crockford_decode("Z").unwrap(), vec![0x1f]);
+        assert_eq!(crockford_decode("80").unwrap(), vec![0x01, 0x00]);
+    }
+
+    #[test]
+    fn test_crockford_decode_aliases() {
+        assert_eq!(crockford_decode("36dxqp4rbj").unwrap(), b"foobar");
+        assert_eq!(crockford_decode("36DX-QP4R-BJ").unwrap(), b"foobar");
+        assert_eq!(
+            crockford_decode("ILOo").unwrap(),
+            crockford_decode("1100").unwrap()
+        );
+
+        assert!(crockford_decode("36DXQP4RBU").is_err());
+        assert!(crockford_decode("36DXQP4RB*").is_err());
+    }
+
+    #[test]
+    fn test_crockford_check_symbol() {
+        // the spec's example number 1234, which is 13 (D) mod 37
+        assert_eq!(check_value(&[0x04, 0xd2]), 13);
+        assert_eq!(crockford_encode_with_check(&[0x04, 0xd2]), "016JD");
+        for input in ["016JD", "16JD", "16jd"].iter() {
+            assert_eq!(
+                crockford_decode_with_check(input).unwrap(),
+                vec![0x04, 0xd2]
+            );
+        }
+
+        // values 32 to 36 use the extra check symbols
+        assert_eq!(crockford_encode_with_check(&[32]), "10*");
+        assert_eq!(crockford_encode_with_check(&[36]), "14U");
+        assert_eq!(crockford_decode_with_check("14U").unwrap(), vec![36]);
+        assert_eq!(crockford_decode_with_check("14u").unwrap(), vec![36]);
+
+        assert!(crockford_decode_with_check("16JE").is_err());
+        assert!(crockford_decode_with_check("").is_err());
+    }
+}
@@ -4,7 +4,7 @@ use byteorder::ReadBytesExt;
 use neon::prelude::*;
 
 use crate::clarity_value::deserialize::TypePrefix;
-use crate::clarity_value::types::{ClarityName, StandardPrincipalData};
+use crate::clarity_value::types::{QualifiedContractIdentifier, StandardPrincipalData};
 use crate::hex::encode_hex;
 use crate::neon_util::{arg_as_bytes, arg_as_bytes_copied};
 
@@ -23,6 +23,7 @@ use self::stacks_address::{
 pub mod b58;
 pub mod bitcoin_address;
 pub mod c32;
+pub mod crockford;
 pub mod stacks_address;
 
 fn btc_to_stx_addr_version_byte(version: u8) -> Option<u8> {
@@ -132,18 +133,13 @@ fn decode_clarity_value_to_principal_inner(arg_bytes: &[u8]) -> Result<String, S
                 .or_else(|e| Err(format!("Failed to encode principal to c32 address: {}", e)))?
         }
         TypePrefix::PrincipalContract => {
-            let issuer = StandardPrincipalData::deserialize(&mut cursor).or_else(|e| {
-                Err(format!(
-                    "Failed to deserialize standard principal to string: {}",
-                    e
-                ))
-            })?;
-            let name = ClarityName::deserialize(&mut cursor).or_else(|e| {
-                Err(format!(
-                    "Failed to deserialize principal contract name to string: {}",
-                    e
-                ))
-            })?;
+            let QualifiedContractIdentifier { issuer, name } =
+                QualifiedContractIdentifier::deserialize(&mut cursor).or_else(|e| {
+                    Err(format!(
+                        "Failed to deserialize contract principal to string: {}",
+                        e
+                    ))
+                })?;
             let c32_addr = c32_address(issuer.0, &issuer.1)
                 .or_else(|e| Err(format!("Failed to encode principal to c32 address: {}", e)))?;
             format!("{}.{}", name, c32_addr)
@@ -1,12 +1,44 @@
 use std::convert::TryFrom;
 
-use super::c32::c32_address_decode;
+use crate::hash::hash160;
+use crate::stacks_tx::deserialize::NetworkId;
+
+use super::c32::{c32_address, c32_address_decode};
 
 pub const C32_ADDRESS_VERSION_MAINNET_SINGLESIG: u8 = 22; // P
 pub const C32_ADDRESS_VERSION_MAINNET_MULTISIG: u8 = 20; // M
 pub const C32_ADDRESS_VERSION_TESTNET_SINGLESIG: u8 = 26; // T
 pub const C32_ADDRESS_VERSION_TESTNET_MULTISIG: u8 = 21; // N
 
+pub fn is_known_address_version(version: u8) -> bool {
+    version == C32_ADDRESS_VERSION_MAINNET_SINGLESIG
+        || version == C32_ADDRESS_VERSION_MAINNET_MULTISIG
+        || version == C32_ADDRESS_VERSION_TESTNET_SINGLESIG
+        || version == C32_ADDRESS_VERSION_TESTNET_MULTISIG
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureType {
+    SingleSig,
+    MultiSig,
+}
+
+/// Whether an address version is for a single-sig or a multisig address, `None` for versions that
+/// aren't one of the four known mainnet/testnet versions.
+pub fn address_signature_type(version: u8) -> Option<SignatureType> {
+    match version {
+        C32_ADDRESS_VERSION_MAINNET_SINGLESIG | C32_ADDRESS_VERSION_TESTNET_SINGLESIG => {
+            Some(SignatureType::SingleSig)
+        }
+        C32_ADDRESS_VERSION_MAINNET_MULTISIG | C32_ADDRESS_VERSION_TESTNET_MULTISIG => {
+            Some(SignatureType::MultiSig)
+        }
+        _ => None,
+    }
+}
+
+/// Ordered by version, then by hash160 bytes.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct StacksAddress {
     pub version: u8,
     pub hash160_bytes: [u8; 20],
@@ -78,3 +110,155 @@ impl TryFrom<u8> for AddressHashMode {
         }
     }
 }
+
+/// The network of an address version, `None` for versions that aren't one of the four known
+/// mainnet/testnet versions. Never returns `NetworkId::Custom`, since custom networks use the
+/// testnet versions.
+pub fn address_network(version: u8) -> Option<NetworkId> {
+    match version {
+        C32_ADDRESS_VERSION_MAINNET_SINGLESIG | C32_ADDRESS_VERSION_MAINNET_MULTISIG => {
+            Some(NetworkId::Mainnet)
+        }
+        C32_ADDRESS_VERSION_TESTNET_SINGLESIG | C32_ADDRESS_VERSION_TESTNET_MULTISIG => {
+            Some(NetworkId::Testnet)
+        }
+        _ => None,
+    }
+}
+
+/// True if both addresses have the same hash160 but one is a mainnet address and the other a
+/// testnet address, i.e. the right account given for the wrong network.
+pub fn is_cross_network_mismatch(expected: &str, got: &str) -> Result<bool, String> {
+    let (expected_version, expected_hash160) = c32_address_decode(expected)?;
+    let (got_version, got_hash160) = c32_address_decode(got)?;
+    if expected_hash160 != got_hash160 {
+        return Ok(false);
+    }
+    match (
+        address_network(expected_version),
+        address_network(got_version),
+    ) {
+        (Some(expected_network), Some(got_network)) => Ok(expected_network != got_network),
+        _ => Ok(false),
+    }
+}
+
+/// The standard single-sig (p2pkh) address for a secp256k1 public key, either 33 byte compressed
+/// or 65 byte uncompressed. Custom networks such as devnets use testnet addresses.
+pub fn standard_address_from_pubkey(pubkey: &[u8], network: NetworkId) -> Result<String, String> {
+    let valid_prefixes: &[u8] = match pubkey.len() {
+        33 => &[0x02, 0x03],
+        65 => &[0x04],
+        len => {
+            return Err(format!(
+                "Invalid public key: expected a 33 byte compressed or 65 byte uncompressed key, got {} bytes",
+                len
+            ))
+        }
+    };
+    if !valid_prefixes.contains(&pubkey[0]) {
+        return Err(format!(
+            "Invalid public key: bad prefix byte 0x{:02x} for a {} byte key",
+            pubkey[0],
+            pubkey.len()
+        ));
+    }
+    let version = match network {
+        NetworkId::Mainnet => AddressHashMode::SerializeP2PKH.to_version_mainnet(),
+        NetworkId::Testnet | NetworkId::Custom(_) => {
+            AddressHashMode::SerializeP2PKH.to_version_testnet()
+        }
+    };
+    c32_address(version, &hash160(pubkey))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::decode_hex;
+
+    #[test]
+    fn test_address_signature_type() {
+        assert_eq!(address_signature_type(22), Some(SignatureType::SingleSig));
+        assert_eq!(address_signature_type(26), Some(SignatureType::SingleSig));
+        assert_eq!(address_signature_type(20), Some(SignatureType::MultiSig));
+        assert_eq!(address_signature_type(21), Some(SignatureType::MultiSig));
+        assert_eq!(address_signature_type(0), None);
+        assert_eq!(address_signature_type(23), None);
+    }
+
+    #[test]
+    fn test_is_cross_network_mismatch() {
+        let mainnet = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7";
+        let testnet = "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ";
+        assert!(is_cross_network_mismatch(mainnet, testnet).unwrap());
+        assert!(is_cross_network_mismatch(testnet, mainnet).unwrap());
+
+        assert!(!is_cross_network_mismatch(mainnet, mainnet).unwrap());
+        // same hash160, version 0 isn't on either network
+        assert!(
+            !is_cross_network_mismatch(mainnet, "S02J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKPVKG2CE")
+                .unwrap()
+        );
+        assert!(!is_cross_network_mismatch(testnet, "SP000000000000000000002Q6VF78").unwrap());
+        assert!(is_cross_network_mismatch(mainnet, "SP000000000000000000002Q6VF79").is_err());
+    }
+
+    #[test]
+    fn test_standard_address_from_pubkey() {
+        // secp256k1 generator point
+        let compressed =
+            decode_hex("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+        assert_eq!(
+            standard_address_from_pubkey(&compressed, NetworkId::Mainnet).unwrap(),
+            "SP1THWXQ8368SDN2MJGE4BMDKMCHZ2GSVTS1X0BPM"
+        );
+        assert_eq!(
+            standard_address_from_pubkey(&compressed, NetworkId::Testnet).unwrap(),
+            "ST1THWXQ8368SDN2MJGE4BMDKMCHZ2GSVTSQDA7QF"
+        );
+        assert_eq!(
+            standard_address_from_pubkey(&compressed, NetworkId::Custom(0x80000001)).unwrap(),
+            "ST1THWXQ8368SDN2MJGE4BMDKMCHZ2GSVTSQDA7QF"
+        );
+
+        let uncompressed = decode_hex("0479be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8").unwrap();
+        assert_eq!(
+            standard_address_from_pubkey(&uncompressed, NetworkId::Mainnet).unwrap(),
+            "SP28V4JZSYMM8ACMP1B38FAXG6M97P798MMKY9DW1"
+        );
+
+        assert!(standard_address_from_pubkey(&compressed[..32], NetworkId::Mainnet).is_err());
+        assert!(standard_address_from_pubkey(&uncompressed[1..34], NetworkId::Mainnet).is_err());
+        assert!(standard_address_from_pubkey(&[], NetworkId::Mainnet).is_err());
+
+        let mut bad_prefix = compressed.to_vec();
+        bad_prefix[0] = 0x05;
+        assert_eq!(
+            standard_address_from_pubkey(&bad_prefix, NetworkId::Mainnet).unwrap_err(),
+            "Invalid public key: bad prefix byte 0x05 for a 33 byte key"
+        );
+        assert_eq!(
+            standard_address_from_pubkey(&compressed[..32], NetworkId::Mainnet).unwrap_err(),
+            "Invalid public key: expected a 33 byte compressed or 65 byte uncompressed key, got 32 bytes"
+        );
+    }
+
+    #[test]
+    fn test_address_ord() {
+        let mut addrs = [
+            StacksAddress::from_string("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").unwrap(),
+            StacksAddress::from_string("ST000000000000000000002AMW42H").unwrap(),
+            StacksAddress::from_string("SP000000000000000000002Q6VF78").unwrap(),
+            StacksAddress::from_string("SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G").unwrap(),
+            StacksAddress::new(22, [0xff; 20]),
+        ];
+        addrs.sort();
+        let versions: Vec<u8> = addrs.iter().map(|a| a.version).collect();
+        assert_eq!(versions, vec![20, 22, 22, 22, 26]);
+        assert_eq!(addrs[1].hash160_bytes, [0; 20]);
+        assert_eq!(addrs[2].hash160_bytes[0], 0xa4);
+        assert_eq!(addrs[3].hash160_bytes, [0xff; 20]);
+    }
+}
@@ -0,0 +1,103 @@
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+const BASE64URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Standard (RFC 4648) padded base64 encoding.
+pub fn encode_base64(data: &[u8]) -> String {
+    encode_with_alphabet(data, BASE64_ALPHABET, true)
+}
+
+/// URL and filename safe (RFC 4648 section 5) base64 encoding, without padding.
+pub fn encode_base64url(data: &[u8]) -> String {
+    encode_with_alphabet(data, BASE64URL_ALPHABET, false)
+}
+
+/// Decodes unpadded base64url, as produced by `encode_base64url`.
+pub fn decode_base64url(input: &str) -> Result<Vec<u8>, String> {
+    let input = input.as_bytes();
+    if input.len() % 4 == 1 {
+        return Err(format!("Invalid base64url length {}", input.len()));
+    }
+    let mut result = Vec::with_capacity(input.len() * 3 / 4);
+    for chunk in input.chunks(4) {
+        let mut triple = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = BASE64URL_ALPHABET
+                .iter()
+                .position(|x| x == c)
+                .ok_or_else(|| format!("Invalid base64url character {:?}", *c as char))?;
+            triple |= (value as u32) << (18 - 6 * i);
+        }
+        let bytes = [(triple >> 16) as u8, (triple >> 8) as u8, triple as u8];
+        let byte_count = chunk.len() - 1;
+        if bytes[byte_count..].iter().any(|b| *b != 0) {
+            return Err("Invalid base64url, non-zero trailing bits".to_string());
+        }
+        result.extend_from_slice(&bytes[..byte_count]);
+    }
+    Ok(result)
+}
+
+fn encode_with_alphabet(data: &[u8], alphabet: &[u8; 64], pad: bool) -> String {
+    // at most one quad per started 3 byte chunk
+    let mut result = String::with_capacity(data.len() / 3 * 4 + 4);
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = *chunk.get(1).unwrap_or(&0) as u32;
+        let b2 = *chunk.get(2).unwrap_or(&0) as u32;
+        let triple = (b0 << 16) | (b1 << 8) | b2;
+
+        result.push(alphabet[(triple >> 18 & 0x3f) as usize] as char);
+        result.push(alphabet[(triple >> 12 & 0x3f) as usize] as char);
+        if chunk.len() > 1 {
+            result.push(alphabet[(triple >> 6 & 0x3f) as usize] as char);
+        } else if pad {
+            result.push('=');
+        }
+        if chunk.len() > 2 {
+            result.push(alphabet[(triple & 0x3f) as usize] as char);
+        } else if pad {
+            result.push('=');
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64(b"foob"), "Zm9vYg==");
+        assert_eq!(encode_base64(b"hello world"), "aGVsbG8gd29ybGQ=");
+        assert_eq!(encode_base64(&[0xff, 0xfe, 0xfd]), "//79");
+    }
+
+    #[test]
+    fn test_base64url() {
+        assert_eq!(encode_base64url(b"f"), "Zg");
+        assert_eq!(encode_base64url(b"fo"), "Zm8");
+        assert_eq!(encode_base64url(&[0xff, 0xfe, 0xfd]), "__79");
+        for data in [
+            &b""[..],
+            b"f",
+            b"fo",
+            b"foo",
+            b"foob",
+            &[0xff, 0xfe, 0xfd, 0xfc],
+        ] {
+            assert_eq!(decode_base64url(&encode_base64url(data)).unwrap(), data);
+        }
+        assert!(decode_base64url("Zg==").is_err());
+        assert!(decode_base64url("Z").is_err());
+        assert!(decode_base64url("Zh").is_err());
+        assert!(decode_base64url("//79").is_err());
+    }
+}
@@ -0,0 +1,232 @@
+//! C ABI for calling the core encoding functions from non-Node consumers (C, Go, Python, etc).
+//!
+//! Enabled with the `cabi` feature. The `include/stacks_encoding.h` header is generated by
+//! cbindgen, see `npm run build:cabi-header`.
+//!
+//! Output is written into caller-allocated buffers. On success, and when the buffer is too
+//! small, `out_len` is set to the number of bytes the full output needs. Output strings are
+//! not null-terminated.
+
+use std::io::Cursor;
+use std::{ptr, slice, str};
+
+use crate::address::c32;
+use crate::clarity_value::json::clarity_value_to_json;
+use crate::clarity_value::types::ClarityValue;
+
+pub const CABI_OK: i32 = 0;
+pub const CABI_ERR_NULL_POINTER: i32 = -1;
+pub const CABI_ERR_INVALID_INPUT: i32 = -2;
+pub const CABI_ERR_BUFFER_TOO_SMALL: i32 = -3;
+
+unsafe fn input_slice<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    if len == 0 {
+        Some(&[])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(data, len))
+    }
+}
+
+unsafe fn write_output(output: &[u8], out: *mut u8, out_cap: usize, out_len: *mut usize) -> i32 {
+    *out_len = output.len();
+    if output.len() > out_cap {
+        return CABI_ERR_BUFFER_TOO_SMALL;
+    }
+    if !output.is_empty() {
+        ptr::copy_nonoverlapping(output.as_ptr(), out, output.len());
+    }
+    CABI_OK
+}
+
+/// Encode a c32check Stacks address from a version byte and 20 byte hash160.
+///
+/// # Safety
+/// `hash160` must point to `hash160_len` readable bytes, `out` to `out_cap` writable bytes,
+/// and `out_len` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn c32_address_encode(
+    version: u8,
+    hash160: *const u8,
+    hash160_len: usize,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> i32 {
+    if out_len.is_null() || (out.is_null() && out_cap > 0) {
+        return CABI_ERR_NULL_POINTER;
+    }
+    let hash160 = match input_slice(hash160, hash160_len) {
+        Some(hash160) => hash160,
+        None => return CABI_ERR_NULL_POINTER,
+    };
+    match c32::c32_address(version, hash160) {
+        Ok(address) => write_output(address.as_bytes(), out, out_cap, out_len),
+        Err(_) => CABI_ERR_INVALID_INPUT,
+    }
+}
+
+/// Decode a c32check Stacks address into its version byte and 20 byte hash160.
+///
+/// # Safety
+/// `address` must point to `address_len` readable bytes, `version_out` must be a valid
+/// pointer, and `hash160_out` must point to 20 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn c32_address_decode(
+    address: *const u8,
+    address_len: usize,
+    version_out: *mut u8,
+    hash160_out: *mut u8,
+) -> i32 {
+    if version_out.is_null() || hash160_out.is_null() {
+        return CABI_ERR_NULL_POINTER;
+    }
+    let address = match input_slice(address, address_len) {
+        Some(address) => address,
+        None => return CABI_ERR_NULL_POINTER,
+    };
+    let address = match str::from_utf8(address) {
+        Ok(address) => address,
+        Err(_) => return CABI_ERR_INVALID_INPUT,
+    };
+    match c32::c32_address_decode(address) {
+        Ok((version, hash160)) => {
+            *version_out = version;
+            ptr::copy_nonoverlapping(hash160.as_ptr(), hash160_out, hash160.len());
+            CABI_OK
+        }
+        Err(_) => CABI_ERR_INVALID_INPUT,
+    }
+}
+
+/// Decode a serialized Clarity value into a JSON string, shaped like the `decodeClarityValue`
+/// JS output.
+///
+/// # Safety
+/// `bytes` must point to `bytes_len` readable bytes, `out` to `out_cap` writable bytes,
+/// and `out_len` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn clarity_decode_to_json(
+    bytes: *const u8,
+    bytes_len: usize,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> i32 {
+    if out_len.is_null() || (out.is_null() && out_cap > 0) {
+        return CABI_ERR_NULL_POINTER;
+    }
+    let bytes = match input_slice(bytes, bytes_len) {
+        Some(bytes) => bytes,
+        None => return CABI_ERR_NULL_POINTER,
+    };
+    let mut cursor = Cursor::new(bytes);
+    let json = ClarityValue::deserialize(&mut cursor, true)
+        .map_err(|e| e.as_string())
+        .and_then(|val| clarity_value_to_json(&val));
+    match json {
+        Ok(json) => write_output(json.as_bytes(), out, out_cap, out_len),
+        Err(_) => CABI_ERR_INVALID_INPUT,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::decode_hex;
+
+    #[test]
+    fn test_cabi_address_roundtrip() {
+        let address = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7";
+        let mut version = 0u8;
+        let mut hash160 = [0u8; 20];
+        let code = unsafe {
+            c32_address_decode(
+                address.as_ptr(),
+                address.len(),
+                &mut version,
+                hash160.as_mut_ptr(),
+            )
+        };
+        assert_eq!(code, CABI_OK);
+        assert_eq!(version, 22);
+
+        // query the required length first
+        let mut out_len = 0usize;
+        let code = unsafe {
+            c32_address_encode(
+                version,
+                hash160.as_ptr(),
+                hash160.len(),
+                ptr::null_mut(),
+                0,
+                &mut out_len,
+            )
+        };
+        assert_eq!(code, CABI_ERR_BUFFER_TOO_SMALL);
+        assert_eq!(out_len, address.len());
+
+        let mut out = vec![0u8; out_len];
+        let code = unsafe {
+            c32_address_encode(
+                version,
+                hash160.as_ptr(),
+                hash160.len(),
+                out.as_mut_ptr(),
+                out.len(),
+                &mut out_len,
+            )
+        };
+        assert_eq!(code, CABI_OK);
+        assert_eq!(&out[..out_len], address.as_bytes());
+
+        let bad_address = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8";
+        let code = unsafe {
+            c32_address_decode(
+                bad_address.as_ptr(),
+                bad_address.len(),
+                &mut version,
+                hash160.as_mut_ptr(),
+            )
+        };
+        assert_eq!(code, CABI_ERR_INVALID_INPUT);
+    }
+
+    #[test]
+    fn test_cabi_clarity_decode_to_json() {
+        let bytes = decode_hex("0x0100000000000000000000000000000001").unwrap();
+        let mut out = [0u8; 256];
+        let mut out_len = 0usize;
+        let code = unsafe {
+            clarity_decode_to_json(
+                bytes.as_ptr(),
+                bytes.len(),
+                out.as_mut_ptr(),
+                out.len(),
+                &mut out_len,
+            )
+        };
+        assert_eq!(code, CABI_OK);
+        assert_eq!(
+            str::from_utf8(&out[..out_len]).unwrap(),
+            r#"{"repr":"u1","hex":"0x0100000000000000000000000000000001","type_id":1,"value":"1"}"#
+        );
+
+        let code = unsafe {
+            clarity_decode_to_json(
+                bytes.as_ptr(),
+                bytes.len() - 1,
+                out.as_mut_ptr(),
+                out.len(),
+                &mut out_len,
+            )
+        };
+        assert_eq!(code, CABI_ERR_INVALID_INPUT);
+
+        let code = unsafe {
+            clarity_decode_to_json(ptr::null(), 4, out.as_mut_ptr(), out.len(), &mut out_len)
+        };
+        assert_eq!(code, CABI_ERR_NULL_POINTER);
+    }
+}
@@ -0,0 +1,260 @@
+//! Decoding into a `bumpalo` arena, enabled with the `arena` feature.
+//!
+//! Decoding many values (e.g. every value in a block) with `ClarityValue::deserialize` creates
+//! and drops a `Vec` or `String` for every buffer, string, list and tuple. Decoding with
+//! `decode_clarity_value_in` instead allocates them all in an arena that is freed at once when
+//! the caller drops or resets it.
+
+use bumpalo::collections::Vec as BumpVec;
+use bumpalo::Bump;
+use byteorder::ReadBytesExt;
+use std::io::{Cursor, Read};
+
+use crate::serialize_util::{ensure_remaining, read_uint_be, DeserializeError};
+
+use super::deserialize::{ensure_contract_name_not_empty, TypePrefix};
+use super::types::{MAX_STRING_LEN, MAX_VALUE_SIZE};
+
+/// A decoded Clarity value whose buffers, strings, lists and tuples are allocated in an arena.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClarityValueRef<'a> {
+    Int(i128),
+    UInt(u128),
+    Bool(bool),
+    Buffer(&'a [u8]),
+    List(&'a [ClarityValueRef<'a>]),
+    StringASCII(&'a [u8]),
+    StringUTF8(&'a str),
+    PrincipalStandard {
+        version: u8,
+        hash160: [u8; 20],
+    },
+    PrincipalContract {
+        version: u8,
+        hash160: [u8; 20],
+        name: &'a str,
+    },
+    /// Fields in wire order.
+    Tuple(&'a [(&'a str, ClarityValueRef<'a>)]),
+    OptionalSome(&'a ClarityValueRef<'a>),
+    OptionalNone,
+    ResponseOk(&'a ClarityValueRef<'a>),
+    ResponseErr(&'a ClarityValueRef<'a>),
+}
+
+/// Decode a serialized Clarity value, allocating everything it contains in `arena`.
+pub fn decode_clarity_value_in<'a>(
+    bytes: &[u8],
+    arena: &'a Bump,
+) -> Result<ClarityValueRef<'a>, DeserializeError> {
+    let mut cursor = Cursor::new(bytes);
+    decode_value(&mut cursor, 0, arena)
+}
+
+fn read_len(r: &mut Cursor<&[u8]>, type_name: &str) -> Result<u32, DeserializeError> {
+    let len = read_uint_be::<4>(r)? as u32;
+    if len > MAX_VALUE_SIZE {
+        return Err(format!("Illegal {} type size", type_name).into());
+    }
+    Ok(len)
+}
+
+fn read_bytes_in<'a>(
+    r: &mut Cursor<&[u8]>,
+    len: usize,
+    arena: &'a Bump,
+) -> Result<&'a [u8], DeserializeError> {
+    ensure_remaining(r, len as u64)?;
+    let start = r.position() as usize;
+    r.set_position((start + len) as u64);
+    Ok(arena.alloc_slice_copy(&r.get_ref()[start..start + len]))
+}
+
+fn read_principal(r: &mut Cursor<&[u8]>) -> Result<(u8, [u8; 20]), DeserializeError> {
+    let version = r.read_u8()?;
+    // must be encodable as a single c32 character
+    if version >= 32 {
+        return Err(format!(
+            "Failed to deserialize principal: invalid address version {}",
+            version
+        )
+        .into());
+    }
+    let mut hash160 = [0; 20];
+    r.read_exact(&mut hash160)?;
+    Ok((version, hash160))
+}
+
+fn read_clarity_name_in<'a>(
+    r: &mut Cursor<&[u8]>,
+    arena: &'a Bump,
+) -> Result<&'a str, DeserializeError> {
+    let len_byte = r.read_u8()?;
+    if len_byte > MAX_STRING_LEN {
+        return Err(format!("Failed to deserialize clarity name: too long: {}", len_byte).into());
+    }
+    let bytes = read_bytes_in(r, len_byte as usize, arena)?;
+    std::str::from_utf8(bytes).map_err(|e| {
+        format!(
+            "Failed to parse Clarity name: could not contruct from utf8: {}",
+            e
+        )
+        .into()
+    })
+}
+
+fn decode_value<'a>(
+    r: &mut Cursor<&[u8]>,
+    depth: u8,
+    arena: &'a Bump,
+) -> Result<ClarityValueRef<'a>, DeserializeError> {
+    if depth >= 16 {
+        return Err(format!("TypeSignatureTooDeep: {}", depth).into());
+    }
+
+    let offset = r.position() as usize;
+    let prefix_byte = r.read_u8()?;
+    let prefix = TypePrefix::from_u8(prefix_byte)
+        .ok_or_else(|| DeserializeError::from(format!("Bad type prefix 0x{:02x}", prefix_byte)))
+        .map_err(|err| err.with_offset(offset))?;
+
+    let value = match prefix {
+        TypePrefix::Int => ClarityValueRef::Int(read_uint_be::<16>(r)? as i128),
+        TypePrefix::UInt => ClarityValueRef::UInt(read_uint_be::<16>(r)?),
+        TypePrefix::Buffer => {
+            let len = read_len(r, "buffer")?;
+            ClarityValueRef::Buffer(read_bytes_in(r, len as usize, arena)?)
+        }
+        TypePrefix::BoolTrue => ClarityValueRef::Bool(true),
+        TypePrefix::BoolFalse => ClarityValueRef::Bool(false),
+        TypePrefix::PrincipalStandard => {
+            let (version, hash160) = read_principal(r)?;
+            ClarityValueRef::PrincipalStandard { version, hash160 }
+        }
+        TypePrefix::PrincipalContract => {
+            let (version, hash160) = read_principal(r)?;
+            let name = read_clarity_name_in(r, arena)?;
+            ensure_contract_name_not_empty(name)?;
+            ClarityValueRef::PrincipalContract {
+                version,
+                hash160,
+                name,
+            }
+        }
+        TypePrefix::ResponseOk => {
+            ClarityValueRef::ResponseOk(arena.alloc(decode_value(r, depth + 1, arena)?))
+        }
+        TypePrefix::ResponseErr => {
+            ClarityValueRef::ResponseErr(arena.alloc(decode_value(r, depth + 1, arena)?))
+        }
+        TypePrefix::OptionalNone => ClarityValueRef::OptionalNone,
+        TypePrefix::OptionalSome => {
+            ClarityValueRef::OptionalSome(arena.alloc(decode_value(r, depth + 1, arena)?))
+        }
+        TypePrefix::List => {
+            let len = read_len(r, "list")?;
+            // every item is at least one byte
+            ensure_remaining(r, len as u64)?;
+            let mut items = BumpVec::with_capacity_in(len as usize, arena);
+            for _i in 0..len {
+                items.push(decode_value(r, depth + 1, arena)?);
+            }
+            ClarityValueRef::List(items.into_bump_slice())
+        }
+        TypePrefix::Tuple => {
+            let len = read_len(r, "tuple")?;
+            // every item is at least one byte
+            ensure_remaining(r, len as u64)?;
+            let mut fields = BumpVec::with_capacity_in(len as usize, arena);
+            for _i in 0..len {
+                let name = read_clarity_name_in(r, arena)?;
+                fields.push((name, decode_value(r, depth + 1, arena)?));
+            }
+            ClarityValueRef::Tuple(fields.into_bump_slice())
+        }
+        TypePrefix::StringASCII => {
+            let len = read_len(r, "string-ascii")?;
+            ClarityValueRef::StringASCII(read_bytes_in(r, len as usize, arena)?)
+        }
+        TypePrefix::StringUTF8 => {
+            let len = read_len(r, "string-utf8")?;
+            ensure_remaining(r, len as u64)?;
+            let start = r.position() as usize;
+            r.set_position((start + len as usize) as u64);
+            let data = String::from_utf8_lossy(&r.get_ref()[start..start + len as usize]);
+            ClarityValueRef::StringUTF8(arena.alloc_str(&data))
+        }
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::decode_hex;
+
+    #[test]
+    fn test_decode_in_arena() {
+        let arena = Bump::new();
+
+        // (ok (list (tuple (a 0x0102) (b (some u1))) (tuple (a 0x03) (b none))))
+        let bytes = decode_hex("0x070b000000020c0000000201610200000002010201620a01000000000000000000000000000000010c000000020161020000000103016209").unwrap();
+        let value = decode_clarity_value_in(&bytes, &arena).unwrap();
+        let list = match value {
+            ClarityValueRef::ResponseOk(ClarityValueRef::List(list)) => list,
+            _ => panic!("expected (ok (list ...))"),
+        };
+        assert_eq!(list.len(), 2);
+        assert_eq!(
+            list[0],
+            ClarityValueRef::Tuple(&[
+                ("a", ClarityValueRef::Buffer(&[1, 2])),
+                (
+                    "b",
+                    ClarityValueRef::OptionalSome(&ClarityValueRef::UInt(1))
+                ),
+            ])
+        );
+        assert_eq!(
+            list[1],
+            ClarityValueRef::Tuple(&[
+                ("a", ClarityValueRef::Buffer(&[3])),
+                ("b", ClarityValueRef::OptionalNone),
+            ])
+        );
+
+        // 'SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.pox
+        let bytes = decode_hex("0x0616a46ff88886c2ef9762d970b4d2c63678835bd39d03706f78").unwrap();
+        match decode_clarity_value_in(&bytes, &arena).unwrap() {
+            ClarityValueRef::PrincipalContract {
+                version,
+                hash160,
+                name,
+            } => {
+                assert_eq!(version, 22);
+                assert_eq!(hash160[0], 0xa4);
+                assert_eq!(name, "pox");
+            }
+            _ => panic!("expected a contract principal"),
+        }
+
+        // u"a\u{e9}"
+        let bytes = decode_hex("0x0e0000000361c3a9").unwrap();
+        assert_eq!(
+            decode_clarity_value_in(&bytes, &arena).unwrap(),
+            ClarityValueRef::StringUTF8("a\u{e9}")
+        );
+    }
+
+    #[test]
+    fn test_decode_in_arena_invalid() {
+        let arena = Bump::new();
+        let bytes = decode_hex("0x070b000000020c00000002016102").unwrap();
+        assert!(decode_clarity_value_in(&bytes, &arena)
+            .unwrap_err()
+            .is_unexpected_eof());
+        let err = decode_clarity_value_in(&[0x0a, 0xff], &arena).unwrap_err();
+        assert_eq!(err.to_string(), "Bad type prefix 0xff");
+        assert_eq!(err.at_offset, Some(1));
+    }
+}
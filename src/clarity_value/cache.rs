@@ -0,0 +1,173 @@
+//! A small LRU cache of decoded values, enabled with the `cache` feature.
+//!
+//! Event streams often contain the same serialized value many times (e.g. a constant passed to
+//! every call of a contract). `DecodeCache::decode` returns the previously decoded value for
+//! bytes it has seen recently, instead of parsing them again. Values are shared through an `Arc`,
+//! since cloning a deep value costs about as much as decoding it.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::io::Cursor;
+use std::sync::Arc;
+
+use crate::serialize_util::DeserializeError;
+
+use super::types::ClarityValue;
+
+struct CacheEntry {
+    bytes: Vec<u8>,
+    value: Arc<ClarityValue>,
+    last_used: u64,
+}
+
+pub struct DecodeCache {
+    capacity: usize,
+    /// Entries by the hash of their bytes. Colliding entries share a bucket and are told apart by
+    /// comparing the full bytes.
+    entries: HashMap<u64, Vec<CacheEntry>>,
+    /// Hash of the entry last used at each tick, oldest first.
+    recency: BTreeMap<u64, u64>,
+    tick: u64,
+}
+
+impl DecodeCache {
+    /// A cache holding up to `capacity` decoded values. A capacity of zero disables caching.
+    pub fn new(capacity: usize) -> DecodeCache {
+        DecodeCache {
+            capacity,
+            entries: HashMap::with_capacity(capacity),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.recency.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recency.is_empty()
+    }
+
+    /// Same as `ClarityValue::deserialize` with `with_bytes` enabled, but returns the cached value
+    /// if `bytes` were decoded recently. Errors aren't cached.
+    pub fn decode(&mut self, bytes: &[u8]) -> Result<Arc<ClarityValue>, DeserializeError> {
+        let hash = hash_bytes(bytes);
+        self.tick += 1;
+        let tick = self.tick;
+
+        let bucket = self.entries.entry(hash).or_default();
+        if let Some(entry) = bucket.iter_mut().find(|entry| entry.bytes == bytes) {
+            self.recency.remove(&entry.last_used);
+            self.recency.insert(tick, hash);
+            entry.last_used = tick;
+            return Ok(Arc::clone(&entry.value));
+        }
+
+        let mut cursor = Cursor::new(bytes);
+        let value = ClarityValue::deserialize(&mut cursor, true).map(Arc::new);
+        let value = match value {
+            Ok(value) if self.capacity > 0 => value,
+            _ => {
+                if bucket.is_empty() {
+                    self.entries.remove(&hash);
+                }
+                return value;
+            }
+        };
+        bucket.push(CacheEntry {
+            bytes: bytes.to_vec(),
+            value: Arc::clone(&value),
+            last_used: tick,
+        });
+        self.recency.insert(tick, hash);
+
+        if self.recency.len() > self.capacity {
+            self.evict_oldest();
+        }
+        Ok(value)
+    }
+
+    fn evict_oldest(&mut self) {
+        let (last_used, hash) = match self.recency.iter().next() {
+            Some((last_used, hash)) => (*last_used, *hash),
+            None => return,
+        };
+        self.recency.remove(&last_used);
+        if let Some(bucket) = self.entries.get_mut(&hash) {
+            bucket.retain(|entry| entry.last_used != last_used);
+            if bucket.is_empty() {
+                self.entries.remove(&hash);
+            }
+        }
+    }
+}
+
+fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::decode_hex;
+
+    #[test]
+    fn test_decode_cache() {
+        let u1 = decode_hex("0x0100000000000000000000000000000001").unwrap();
+        let u2 = decode_hex("0x0100000000000000000000000000000002").unwrap();
+        let some_u1 = decode_hex("0x0a0100000000000000000000000000000001").unwrap();
+
+        let mut cache = DecodeCache::new(2);
+        let first = cache.decode(&u1).unwrap();
+        assert_eq!(first.value.repr_string(), "u1");
+        assert_eq!(cache.decode(&u2).unwrap().value.repr_string(), "u2");
+        assert_eq!(cache.len(), 2);
+
+        // hit refreshes u1, so u2 is the one evicted
+        let hit = cache.decode(&u1).unwrap();
+        assert!(Arc::ptr_eq(&first, &hit));
+        assert_eq!(hit.serialized_bytes.as_ref().unwrap(), u1.as_ref());
+        cache.decode(&some_u1).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(cache
+            .entries
+            .values()
+            .flatten()
+            .all(|e| e.bytes != u2.as_ref()));
+        assert!(cache
+            .entries
+            .values()
+            .flatten()
+            .any(|e| e.bytes == u1.as_ref()));
+
+        assert!(cache.decode(&u1[..10]).is_err());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_decode_cache_collision() {
+        let u1 = decode_hex("0x0100000000000000000000000000000001").unwrap();
+        let u2 = decode_hex("0x0100000000000000000000000000000002").unwrap();
+
+        // force both values into the same bucket, as if their hashes collided
+        let mut cache = DecodeCache::new(4);
+        let value = cache.decode(&u1).unwrap();
+        let bucket = cache.entries.remove(&hash_bytes(&u1)).unwrap();
+        cache.entries.insert(hash_bytes(&u2), bucket);
+        assert_eq!(value.value.repr_string(), "u1");
+        assert_eq!(cache.decode(&u2).unwrap().value.repr_string(), "u2");
+        assert_eq!(cache.entries[&hash_bytes(&u2)].len(), 2);
+
+        let mut cache = DecodeCache::new(0);
+        cache.decode(&u1).unwrap();
+        assert!(cache.is_empty());
+    }
+}
@@ -1,10 +1,13 @@
 use byteorder::ReadBytesExt;
+use std::collections::BTreeMap;
 
 use super::types::*;
-use std::collections::BTreeMap;
 use std::io::{Cursor, Read};
 
-use crate::serialize_util::DeserializeError;
+use crate::address::stacks_address::is_known_address_version;
+use crate::hash::sha512_256;
+use crate::hex::decode_hex;
+use crate::serialize_util::{ensure_remaining, read_uint_be, DeserializeError};
 
 macro_rules! define_u8_enum {
     ($Name:ident { $($Variant:ident = $Val:literal),+ }) =>
@@ -99,29 +102,156 @@ impl ClarityName {
 
         Ok(ClarityName(s))
     }
+
+    /// Reads the name of a contract principal. Unlike other Clarity names, it can't be empty.
+    pub fn deserialize_contract_name(fd: &mut Cursor<&[u8]>) -> Result<Self, DeserializeError> {
+        let name = ClarityName::deserialize(fd)?;
+        ensure_contract_name_not_empty(&name)?;
+        Ok(name)
+    }
+}
+
+pub(crate) fn ensure_contract_name_not_empty(name: &str) -> Result<(), DeserializeError> {
+    if name.is_empty() {
+        return Err("Failed to deserialize contract principal: empty contract name".into());
+    }
+    Ok(())
 }
 
 impl StandardPrincipalData {
     pub fn deserialize(r: &mut Cursor<&[u8]>) -> Result<Self, DeserializeError> {
         let version = r.read_u8()?;
+        // must be encodable as a single c32 character
+        if version >= 32 {
+            return Err(format!(
+                "Failed to deserialize principal: invalid address version {}",
+                version
+            ))?;
+        }
         let mut data = [0; 20];
         r.read_exact(&mut data)?;
         Ok(StandardPrincipalData(version, data))
     }
 }
 
+impl QualifiedContractIdentifier {
+    /// Reads the issuer and name of a contract principal, following its type prefix.
+    pub fn deserialize(r: &mut Cursor<&[u8]>) -> Result<Self, DeserializeError> {
+        let issuer = StandardPrincipalData::deserialize(r)?;
+        let name = ClarityName::deserialize_contract_name(r)?;
+        Ok(QualifiedContractIdentifier { issuer, name })
+    }
+}
+
+/// Order of the fields in decoded tuples.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum TupleOrder {
+    /// The order the fields were serialized in, so that re-serializing reproduces the input bytes.
+    #[default]
+    WireOrder,
+    /// Sorted by field name.
+    Alphabetical,
+}
+
+/// What to do when a value has a type prefix this crate doesn't recognize.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum UnknownTypeMode {
+    /// Fail with the unrecognized byte and its offset.
+    #[default]
+    Abort,
+    /// Decode the value as `Value::Unknown` holding the rest of the input, so values decoded before
+    /// it are kept. Every list and tuple containing it stops reading further items, and keeps its
+    /// `serialized_bytes` so it serializes to the original input.
+    Capture,
+}
+
+#[derive(Debug, Clone)]
+pub struct DecodeOptions {
+    /// Reject values whose serialized length exceeds `MAX_VALUE_SIZE`, matching the consensus
+    /// limit enforced by stacks-node.
+    pub strict_max_value_size: bool,
+    /// Reject principals whose version isn't one of the known mainnet/testnet
+    /// singlesig/multisig address versions.
+    pub strict_address_versions: bool,
+    pub tuple_order: TupleOrder,
+    pub on_unknown: UnknownTypeMode,
+    /// Reject contract principals whose name doesn't match `CONTRACT_NAME_REGEX`. When disabled
+    /// any ASCII name is accepted, for displaying historical data with technically invalid names.
+    /// Names are limited to `MAX_STRING_LEN` bytes in both modes, the 40 character limit only
+    /// applies to new contracts. Enabled by default.
+    pub strict_contract_names: bool,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        DecodeOptions {
+            strict_max_value_size: false,
+            strict_address_versions: false,
+            tuple_order: TupleOrder::default(),
+            on_unknown: UnknownTypeMode::default(),
+            strict_contract_names: true,
+        }
+    }
+}
+
+impl DecodeOptions {
+    fn check_contract_name(&self, name: &str) -> Result<(), DeserializeError> {
+        if self.strict_contract_names {
+            if !CONTRACT_NAME_REGEX.is_match(name) {
+                return Err(format!("Invalid contract name {:?}", name).into());
+            }
+        } else if !name.is_ascii() {
+            return Err(format!("Invalid contract name {:?}, must be ASCII", name).into());
+        }
+        Ok(())
+    }
+
+    fn check_address_version(&self, version: u8) -> Result<(), DeserializeError> {
+        if self.strict_address_versions && !is_known_address_version(version) {
+            return Err(format!("Unknown principal address version {}", version).into());
+        }
+        Ok(())
+    }
+}
+
 impl ClarityValue {
     pub fn deserialize(
         r: &mut Cursor<&[u8]>,
         with_bytes: bool,
     ) -> Result<ClarityValue, DeserializeError> {
-        Self::inner_deserialize_read(r, 0, with_bytes)
+        Self::inner_deserialize_read(r, 0, with_bytes, &DecodeOptions::default())
+    }
+
+    pub fn deserialize_with_options(
+        r: &mut Cursor<&[u8]>,
+        with_bytes: bool,
+        options: &DecodeOptions,
+    ) -> Result<ClarityValue, DeserializeError> {
+        let start = r.position();
+        let value = Self::inner_deserialize_read(r, 0, with_bytes, options)?;
+        if options.strict_max_value_size && r.position() - start > MAX_VALUE_SIZE as u64 {
+            return Err("value exceeds MAX_VALUE_SIZE".into());
+        }
+        Ok(value)
     }
 
+    /// Errors are tagged with the offset of the innermost value that failed to decode.
     fn inner_deserialize_read(
         r: &mut Cursor<&[u8]>,
         depth: u8,
         with_bytes: bool,
+        options: &DecodeOptions,
+    ) -> Result<ClarityValue, DeserializeError> {
+        let offset = r.position() as usize;
+        Self::inner_deserialize_value(r, depth, with_bytes, options)
+            .map_err(|err| err.with_offset(offset))
+    }
+
+    fn inner_deserialize_value(
+        r: &mut Cursor<&[u8]>,
+        depth: u8,
+        with_bytes: bool,
+        options: &DecodeOptions,
     ) -> Result<ClarityValue, DeserializeError> {
         use super::types::Value::*;
 
@@ -134,26 +264,33 @@ impl ClarityValue {
         let mut header = [0];
         r.read_exact(&mut header)?;
 
-        let prefix = TypePrefix::from_u8(header[0]).ok_or_else(|| "Bad type prefix")?;
+        let prefix = match TypePrefix::from_u8(header[0]) {
+            Some(prefix) => prefix,
+            None if options.on_unknown == UnknownTypeMode::Capture => {
+                let mut raw = vec![];
+                r.read_to_end(&mut raw)?;
+                let value = Value::Unknown {
+                    type_id: header[0],
+                    raw,
+                };
+                return Ok(if with_bytes {
+                    ClarityValue::new_with_bytes(&r.get_ref()[cursor_start..], value)
+                } else {
+                    ClarityValue::new(value)
+                });
+            }
+            None => return Err(format!("Bad type prefix 0x{:02x}", header[0]).into()),
+        };
 
         let clarity_value = match prefix {
-            TypePrefix::Int => {
-                let mut int_buffer = [0; 16];
-                r.read_exact(&mut int_buffer)?;
-                Int(i128::from_be_bytes(int_buffer))
-            }
-            TypePrefix::UInt => {
-                let mut int_buffer = [0; 16];
-                r.read_exact(&mut int_buffer)?;
-                UInt(u128::from_be_bytes(int_buffer))
-            }
+            TypePrefix::Int => Int(read_uint_be::<16>(r)? as i128),
+            TypePrefix::UInt => UInt(read_uint_be::<16>(r)?),
             TypePrefix::Buffer => {
-                let mut buffer_len = [0; 4];
-                r.read_exact(&mut buffer_len)?;
-                let buffer_len = u32::from_be_bytes(buffer_len);
+                let buffer_len = read_uint_be::<4>(r)? as u32;
                 if buffer_len > MAX_VALUE_SIZE {
                     return Err("Illegal buffer type size".into());
                 }
+                ensure_remaining(r, buffer_len as u64)?;
                 let mut data = vec![0; buffer_len as usize];
                 r.read_exact(&mut data[..])?;
                 Value::Buffer(data)
@@ -162,80 +299,91 @@ impl ClarityValue {
             TypePrefix::BoolFalse => Bool(false),
             TypePrefix::PrincipalStandard => {
                 let principal = StandardPrincipalData::deserialize(r)?;
+                options.check_address_version(principal.0)?;
                 Value::PrincipalStandard(principal)
             }
             TypePrefix::PrincipalContract => {
-                let issuer = StandardPrincipalData::deserialize(r)?;
-                let name = ClarityName::deserialize(r)?;
-                Value::PrincipalContract(QualifiedContractIdentifier { issuer, name })
+                let contract_identifier = QualifiedContractIdentifier::deserialize(r)?;
+                options.check_address_version(contract_identifier.issuer.0)?;
+                options.check_contract_name(&contract_identifier.name)?;
+                Value::PrincipalContract(contract_identifier)
             }
             TypePrefix::ResponseOk => {
-                let value = Self::inner_deserialize_read(r, depth + 1, with_bytes)?;
+                let value = Self::inner_deserialize_read(r, depth + 1, with_bytes, options)?;
                 Value::ResponseOk(Box::new(value))
             }
             TypePrefix::ResponseErr => {
-                let value = Self::inner_deserialize_read(r, depth + 1, with_bytes)?;
+                let value = Self::inner_deserialize_read(r, depth + 1, with_bytes, options)?;
                 Value::ResponseErr(Box::new(value))
             }
             TypePrefix::OptionalNone => Value::OptionalNone,
             TypePrefix::OptionalSome => {
-                let value = Self::inner_deserialize_read(r, depth + 1, with_bytes)?;
+                let value = Self::inner_deserialize_read(r, depth + 1, with_bytes, options)?;
                 Value::OptionalSome(Box::new(value))
             }
             TypePrefix::List => {
-                let mut len = [0; 4];
-                r.read_exact(&mut len)?;
-                let len = u32::from_be_bytes(len);
+                let len = read_uint_be::<4>(r)? as u32;
                 if len > MAX_VALUE_SIZE {
                     return Err("Illegal list type size".into());
                 }
+                // every item is at least one byte
+                ensure_remaining(r, len as u64)?;
                 let mut items = Vec::with_capacity(len as usize);
                 for _i in 0..len {
-                    let value = Self::inner_deserialize_read(r, depth + 1, with_bytes)?;
+                    let value = Self::inner_deserialize_read(r, depth + 1, with_bytes, options)?;
+                    let ends_in_capture = value.value.ends_in_capture();
                     items.push(value);
+                    if ends_in_capture {
+                        break;
+                    }
                 }
                 Value::List(items)
             }
             TypePrefix::Tuple => {
-                let mut len = [0; 4];
-                r.read_exact(&mut len)?;
-                let len = u32::from_be_bytes(len);
+                let len = read_uint_be::<4>(r)? as u32;
                 if len > MAX_VALUE_SIZE {
                     return Err("Illegal tuple type size".into());
                 }
-                let mut data = BTreeMap::new();
+                // every item is at least one byte
+                ensure_remaining(r, len as u64)?;
+                let mut data = TupleData::new();
                 for _i in 0..len {
                     let key = ClarityName::deserialize(r)?;
-                    let value = Self::inner_deserialize_read(r, depth + 1, with_bytes)?;
-                    data.insert(key, value);
+                    let value = Self::inner_deserialize_read(r, depth + 1, with_bytes, options)?;
+                    let ends_in_capture = value.value.ends_in_capture();
+                    data.push(key, value);
+                    if ends_in_capture {
+                        break;
+                    }
+                }
+                if options.tuple_order == TupleOrder::Alphabetical {
+                    data.sort_by_name();
                 }
                 Value::Tuple(data)
             }
             TypePrefix::StringASCII => {
-                let mut buffer_len = [0; 4];
-                r.read_exact(&mut buffer_len)?;
-                let buffer_len = u32::from_be_bytes(buffer_len);
+                let buffer_len = read_uint_be::<4>(r)? as u32;
                 if buffer_len > MAX_VALUE_SIZE {
                     return Err("Illegal string-ascii type size".into());
                 }
+                ensure_remaining(r, buffer_len as u64)?;
                 let mut data = vec![0; buffer_len as usize];
                 r.read_exact(&mut data[..])?;
                 Value::StringASCII(data)
             }
             TypePrefix::StringUTF8 => {
-                let mut total_len = [0; 4];
-                r.read_exact(&mut total_len)?;
-                let total_len = u32::from_be_bytes(total_len);
+                let total_len = read_uint_be::<4>(r)? as u32;
                 if total_len > MAX_VALUE_SIZE {
                     return Err("Illegal string-utf8 type size".into());
                 }
+                ensure_remaining(r, total_len as u64)?;
                 let mut data: Vec<u8> = vec![0; total_len as usize];
                 r.read_exact(&mut data[..])?;
                 Value::string_utf8(data)
             }
         };
 
-        if with_bytes {
+        if with_bytes || clarity_value.ends_in_capture() {
             let bytes = &r.get_ref()[cursor_start..r.position() as usize];
             Ok(ClarityValue::new_with_bytes(bytes, clarity_value))
         } else {
@@ -243,3 +391,790 @@ impl ClarityValue {
         }
     }
 }
+
+/// Decode a serialized `(list N uint)` directly into its values, without building a
+/// `ClarityValue` per element. Errors on the first element that isn't a uint.
+pub fn clarity_list_as_u128(bytes: &[u8]) -> Result<Vec<u128>, DeserializeError> {
+    decode_numeric_list(bytes, TypePrefix::UInt, |n| n)
+}
+
+/// Decode a serialized `(list N int)` directly into its values, without building a
+/// `ClarityValue` per element. Errors on the first element that isn't an int.
+pub fn clarity_list_as_i128(bytes: &[u8]) -> Result<Vec<i128>, DeserializeError> {
+    decode_numeric_list(bytes, TypePrefix::Int, |n| n as i128)
+}
+
+fn decode_numeric_list<T>(
+    bytes: &[u8],
+    item_prefix: TypePrefix,
+    from_u128: fn(u128) -> T,
+) -> Result<Vec<T>, DeserializeError> {
+    let mut r = Cursor::new(bytes);
+    let prefix = r.read_u8()?;
+    if prefix != TypePrefix::List as u8 {
+        return Err(format!("Expected a list, found type prefix 0x{:02x}", prefix).into());
+    }
+    let len = read_uint_be::<4>(&mut r)? as u32;
+    if len > MAX_VALUE_SIZE {
+        return Err("Illegal list type size".into());
+    }
+    // every item is at least one byte
+    ensure_remaining(&r, len as u64)?;
+    let mut result = Vec::with_capacity(len as usize);
+    for i in 0..len {
+        let offset = r.position() as usize;
+        let prefix = r.read_u8()?;
+        if prefix != item_prefix.to_u8() {
+            let err: DeserializeError = format!(
+                "Expected {:?} list element at index {}, found type prefix 0x{:02x}",
+                item_prefix, i, prefix
+            )
+            .into();
+            return Err(err.with_offset(offset));
+        }
+        result.push(from_u128(read_uint_be::<16>(&mut r)?));
+    }
+    Ok(result)
+}
+
+/// Decode a serialized buffer, erroring if it is longer than `max_len`, as when checking a value
+/// against a declared `(buff max_len)` type. The length is checked before the data is read.
+pub fn clarity_decode_buffer_max(
+    bytes: &[u8],
+    max_len: usize,
+) -> Result<Vec<u8>, DeserializeError> {
+    let mut r = Cursor::new(bytes);
+    let prefix = r.read_u8()?;
+    if prefix != TypePrefix::Buffer as u8 {
+        return Err(format!("Expected a buffer, found type prefix 0x{:02x}", prefix).into());
+    }
+    let len = read_uint_be::<4>(&mut r)? as u32;
+    if len as usize > max_len {
+        let err: DeserializeError = format!(
+            "Buffer length {} exceeds declared max length {}",
+            len, max_len
+        )
+        .into();
+        return Err(err.with_offset(1));
+    }
+    if len > MAX_VALUE_SIZE {
+        return Err("Illegal buffer type size".into());
+    }
+    ensure_remaining(&r, len as u64)?;
+    let mut data = vec![0; len as usize];
+    r.read_exact(&mut data[..])?;
+    Ok(data)
+}
+
+/// Decode a serialized tuple into a map from field name to value, for callers that look fields
+/// up by name rather than caring about their order. Errors if a field name appears twice.
+pub fn clarity_tuple_to_map(
+    bytes: &[u8],
+) -> Result<BTreeMap<String, ClarityValue>, DeserializeError> {
+    let mut r = Cursor::new(bytes);
+    let prefix = r.read_u8()?;
+    if prefix != TypePrefix::Tuple as u8 {
+        return Err(format!("Expected a tuple, found type prefix 0x{:02x}", prefix).into());
+    }
+    let len = read_uint_be::<4>(&mut r)? as u32;
+    if len > MAX_VALUE_SIZE {
+        return Err("Illegal tuple type size".into());
+    }
+    // every item is at least one byte
+    ensure_remaining(&r, len as u64)?;
+    let mut result = BTreeMap::new();
+    for _i in 0..len {
+        let offset = r.position() as usize;
+        let name = ClarityName::deserialize(&mut r)?;
+        if result.contains_key(name.as_str()) {
+            let err: DeserializeError = format!("Duplicate tuple field name {}", name).into();
+            return Err(err.with_offset(offset));
+        }
+        let value =
+            ClarityValue::inner_deserialize_read(&mut r, 1, true, &DecodeOptions::default())?;
+        result.insert(name.0, value);
+    }
+    Ok(result)
+}
+
+/// Decode a serialized list, keeping the items decoded before the first one that fails. Returns
+/// the decoded prefix along with the error, if any, for salvaging partially corrupt data.
+pub fn decode_clarity_list_lenient(bytes: &[u8]) -> (Vec<ClarityValue>, Option<DeserializeError>) {
+    let mut r = Cursor::new(bytes);
+    let mut items = Vec::new();
+    let result = decode_list_items(&mut r, &mut items);
+    (items, result.err())
+}
+
+fn decode_list_items(
+    r: &mut Cursor<&[u8]>,
+    items: &mut Vec<ClarityValue>,
+) -> Result<(), DeserializeError> {
+    let prefix = r.read_u8()?;
+    if prefix != TypePrefix::List as u8 {
+        return Err(format!("Expected a list, found type prefix 0x{:02x}", prefix).into());
+    }
+    let len = read_uint_be::<4>(r)? as u32;
+    if len > MAX_VALUE_SIZE {
+        return Err("Illegal list type size".into());
+    }
+    for _i in 0..len {
+        items.push(ClarityValue::inner_deserialize_read(
+            r,
+            1,
+            true,
+            &DecodeOptions::default(),
+        )?);
+    }
+    Ok(())
+}
+
+/// Whether a serialized value is a scalar (int, uint, bool, buffer, string or principal) rather
+/// than a list, tuple, optional or response. Only the type byte is read, the rest of the value
+/// isn't validated.
+pub fn clarity_is_scalar(bytes: &[u8]) -> Result<bool, DeserializeError> {
+    let type_id = Cursor::new(bytes).read_u8()?;
+    let prefix =
+        TypePrefix::from_u8(type_id).ok_or_else(|| format!("Bad type prefix 0x{:02x}", type_id))?;
+    Ok(match prefix {
+        TypePrefix::Int
+        | TypePrefix::UInt
+        | TypePrefix::BoolTrue
+        | TypePrefix::BoolFalse
+        | TypePrefix::Buffer
+        | TypePrefix::StringASCII
+        | TypePrefix::StringUTF8
+        | TypePrefix::PrincipalStandard
+        | TypePrefix::PrincipalContract => true,
+        TypePrefix::List
+        | TypePrefix::Tuple
+        | TypePrefix::OptionalNone
+        | TypePrefix::OptionalSome
+        | TypePrefix::ResponseOk
+        | TypePrefix::ResponseErr => false,
+    })
+}
+
+/// The type name of a serialized value, e.g. `"principal-standard"`, from its type prefix byte.
+/// Only the first byte is read; the rest of the value isn't validated.
+pub fn clarity_type_name(bytes: &[u8]) -> Result<&'static str, DeserializeError> {
+    let type_id = Cursor::new(bytes).read_u8()?;
+    let prefix =
+        TypePrefix::from_u8(type_id).ok_or_else(|| format!("Bad type prefix 0x{:02x}", type_id))?;
+    Ok(match prefix {
+        TypePrefix::Int => "int",
+        TypePrefix::UInt => "uint",
+        TypePrefix::Buffer => "buffer",
+        TypePrefix::BoolTrue | TypePrefix::BoolFalse => "bool",
+        TypePrefix::PrincipalStandard => "principal-standard",
+        TypePrefix::PrincipalContract => "principal-contract",
+        TypePrefix::ResponseOk => "response-ok",
+        TypePrefix::ResponseErr => "response-err",
+        TypePrefix::OptionalNone => "optional-none",
+        TypePrefix::OptionalSome => "optional-some",
+        TypePrefix::List => "list",
+        TypePrefix::Tuple => "tuple",
+        TypePrefix::StringASCII => "string-ascii",
+        TypePrefix::StringUTF8 => "string-utf8",
+    })
+}
+
+/// Which layer `decode_clarity_value_hex` failed in.
+#[derive(Debug)]
+pub enum HexValueError {
+    /// The input isn't valid hex.
+    Hex(String),
+    /// The input is valid hex, but not a valid serialized Clarity value.
+    Value(DeserializeError),
+}
+
+impl std::fmt::Display for HexValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HexValueError::Hex(e) => write!(f, "Hex parsing error: {}", e),
+            HexValueError::Value(e) => write!(f, "Error deserializing Clarity value: {}", e),
+        }
+    }
+}
+
+/// Decode a hex encoded serialized Clarity value, with or without a `0x` prefix.
+pub fn decode_clarity_value_hex(hex: &str) -> Result<ClarityValue, HexValueError> {
+    let bytes = decode_hex(hex).map_err(|e| HexValueError::Hex(e.to_string()))?;
+    let mut cursor = Cursor::new(bytes.as_ref());
+    ClarityValue::deserialize(&mut cursor, true).map_err(HexValueError::Value)
+}
+
+/// SHA-512/256 of a serialized Clarity value. The bytes are decoded first, so that only a single
+/// valid value with no trailing bytes gets hashed.
+pub fn clarity_value_hash(bytes: &[u8]) -> Result<[u8; 32], DeserializeError> {
+    let mut cursor = Cursor::new(bytes);
+    ClarityValue::deserialize(&mut cursor, false)?;
+    ensure_no_trailing_bytes(&cursor)?;
+    Ok(sha512_256(bytes))
+}
+
+/// Decode a serialized Clarity value that must span all of `bytes`, e.g. a lone `0x09` is
+/// `none`, but `0x09` followed by anything is an error rather than `none` with the rest ignored.
+pub fn decode_clarity_value_exact(bytes: &[u8]) -> Result<ClarityValue, DeserializeError> {
+    let mut cursor = Cursor::new(bytes);
+    let value = ClarityValue::deserialize(&mut cursor, true)?;
+    ensure_no_trailing_bytes(&cursor)?;
+    Ok(value)
+}
+
+fn ensure_no_trailing_bytes(cursor: &Cursor<&[u8]>) -> Result<(), DeserializeError> {
+    let len = cursor.get_ref().len() as u64;
+    if cursor.position() != len {
+        Err(format!(
+            "Unexpected trailing bytes after Clarity value: {}",
+            len - cursor.position()
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clarity_value_hash() {
+        let bytes = decode_hex("0x0100000000000000000000000000000001").unwrap();
+        assert_eq!(
+            crate::hex::encode_hex(&clarity_value_hash(&bytes).unwrap()).as_ref(),
+            "0x164709a3d395697ea9c50e0761a1c50368f88f57b66beee1dbb45a4c903063c6"
+        );
+
+        let err = clarity_value_hash(&[&bytes[..], &[0x03]].concat()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unexpected trailing bytes after Clarity value: 1"
+        );
+        assert!(clarity_value_hash(&bytes[..10]).is_err());
+    }
+
+    #[test]
+    fn test_decode_lone_none() {
+        let mut cursor = Cursor::new(&[0x09][..]);
+        let value = ClarityValue::deserialize(&mut cursor, true).unwrap();
+        assert_eq!(value.value.repr_string(), "none");
+        assert_eq!(cursor.position(), 1);
+        assert_eq!(value.serialized_bytes.unwrap(), vec![0x09]);
+
+        let value = decode_clarity_value_exact(&[0x09]).unwrap();
+        assert_eq!(value.value.repr_string(), "none");
+        assert_eq!(
+            decode_clarity_value_exact(&[0x09, 0x00])
+                .err()
+                .unwrap()
+                .to_string(),
+            "Unexpected trailing bytes after Clarity value: 1"
+        );
+        // (some <missing>) still needs its inner value
+        assert!(decode_clarity_value_exact(&[0x0a])
+            .err()
+            .unwrap()
+            .is_unexpected_eof());
+    }
+
+    #[test]
+    fn test_decode_clarity_list_lenient() {
+        // (list u1 u2 <bad type prefix 0x0f> u4)
+        let bytes = decode_hex(
+            "0x0b00000004010000000000000000000000000000000101000000000000000000000000000000020f0100000000000000000000000000000004",
+        )
+        .unwrap();
+        let (items, err) = decode_clarity_list_lenient(&bytes);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].value.repr_string(), "u1");
+        assert_eq!(items[1].value.repr_string(), "u2");
+        assert_eq!(items[1].serialized_bytes.as_ref().unwrap(), &bytes[22..39]);
+        let err = err.unwrap();
+        assert_eq!(err.to_string(), "Bad type prefix 0x0f");
+        assert_eq!(err.at_offset, Some(39));
+
+        // truncated after the second item
+        let (items, err) = decode_clarity_list_lenient(&bytes[..39]);
+        assert_eq!(items.len(), 2);
+        assert!(err.unwrap().is_unexpected_eof());
+        // the same two items with a length of 2
+        let mut complete = bytes[..39].to_vec();
+        complete[4] = 2;
+        let (items, err) = decode_clarity_list_lenient(&complete);
+        assert_eq!(items.len(), 2);
+        assert!(err.is_none());
+
+        let (items, err) = decode_clarity_list_lenient(&bytes[5..22]);
+        assert!(items.is_empty());
+        assert_eq!(
+            err.unwrap().to_string(),
+            "Expected a list, found type prefix 0x01"
+        );
+    }
+
+    #[test]
+    fn test_clarity_is_scalar() {
+        let scalars = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x0d, 0x0e];
+        let containers = [0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c];
+        for type_id in scalars.iter() {
+            assert!(clarity_is_scalar(&[*type_id]).unwrap(), "0x{:02x}", type_id);
+        }
+        for type_id in containers.iter() {
+            assert!(
+                !clarity_is_scalar(&[*type_id]).unwrap(),
+                "0x{:02x}",
+                type_id
+            );
+        }
+        // u1
+        assert!(
+            clarity_is_scalar(&decode_hex("0x0100000000000000000000000000000001").unwrap())
+                .unwrap()
+        );
+        assert_eq!(
+            clarity_is_scalar(&[0x0f]).err().unwrap().to_string(),
+            "Bad type prefix 0x0f"
+        );
+        assert!(clarity_is_scalar(&[]).err().unwrap().is_unexpected_eof());
+    }
+
+    #[test]
+    fn test_clarity_type_name() {
+        let names = [
+            "int",
+            "uint",
+            "buffer",
+            "bool",
+            "bool",
+            "principal-standard",
+            "principal-contract",
+            "response-ok",
+            "response-err",
+            "optional-none",
+            "optional-some",
+            "list",
+            "tuple",
+            "string-ascii",
+            "string-utf8",
+        ];
+        for (type_id, name) in names.iter().enumerate() {
+            assert_eq!(clarity_type_name(&[type_id as u8]).unwrap(), *name);
+        }
+        // (some u1)
+        let bytes = decode_hex("0x0a0100000000000000000000000000000001").unwrap();
+        assert_eq!(clarity_type_name(&bytes).unwrap(), "optional-some");
+        assert_eq!(
+            clarity_type_name(&[0x0f]).err().unwrap().to_string(),
+            "Bad type prefix 0x0f"
+        );
+        assert!(clarity_type_name(&[]).err().unwrap().is_unexpected_eof());
+    }
+
+    #[test]
+    fn test_clarity_decode_buffer_max() {
+        // 0x010203
+        let bytes = decode_hex("0x0200000003010203").unwrap();
+        assert_eq!(clarity_decode_buffer_max(&bytes, 3).unwrap(), vec![1, 2, 3]);
+        assert_eq!(
+            clarity_decode_buffer_max(&bytes, 32).unwrap(),
+            vec![1, 2, 3]
+        );
+        let err = clarity_decode_buffer_max(&bytes, 2).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Buffer length 3 exceeds declared max length 2"
+        );
+        assert_eq!(err.at_offset, Some(1));
+        let empty = decode_hex("0x0200000000").unwrap();
+        assert!(clarity_decode_buffer_max(&empty, 0).unwrap().is_empty());
+        assert!(clarity_decode_buffer_max(&bytes[..6], 3)
+            .err()
+            .unwrap()
+            .is_unexpected_eof());
+        // u1
+        let uint = decode_hex("0x0100000000000000000000000000000001").unwrap();
+        assert_eq!(
+            clarity_decode_buffer_max(&uint, 32)
+                .err()
+                .unwrap()
+                .to_string(),
+            "Expected a buffer, found type prefix 0x01"
+        );
+    }
+
+    #[test]
+    fn test_clarity_tuple_to_map() {
+        // (tuple (b u2) (a (some 0x01)))
+        let bytes =
+            decode_hex("0x0c000000020162010000000000000000000000000000000201610a020000000101")
+                .unwrap();
+        let map = clarity_tuple_to_map(&bytes).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["a"].value.repr_string(), "(some 0x01)");
+        assert_eq!(map["b"].value.repr_string(), "u2");
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert!(map["a"].serialized_bytes.is_some());
+        assert!(!map.contains_key("c"));
+
+        // (tuple (a u2) (a u3))
+        let bytes = decode_hex(
+            "0x0c000000020161010000000000000000000000000000000201610100000000000000000000000000000003",
+        )
+        .unwrap();
+        let err = clarity_tuple_to_map(&bytes).err().unwrap();
+        assert_eq!(err.to_string(), "Duplicate tuple field name a");
+        assert_eq!(err.at_offset, Some(24));
+
+        let err =
+            clarity_tuple_to_map(&decode_hex("0x0100000000000000000000000000000002").unwrap())
+                .err()
+                .unwrap();
+        assert_eq!(err.to_string(), "Expected a tuple, found type prefix 0x01");
+        assert!(clarity_tuple_to_map(&bytes[..20])
+            .err()
+            .unwrap()
+            .is_unexpected_eof());
+    }
+
+    #[test]
+    fn test_decode_clarity_value_hex() {
+        let prefixed = decode_clarity_value_hex("0x0a0100000000000000000000000000000005").unwrap();
+        let unprefixed = decode_clarity_value_hex("0a0100000000000000000000000000000005").unwrap();
+        assert_eq!(prefixed.value.repr_string(), "(some u5)");
+        assert_eq!(prefixed.serialized_bytes, unprefixed.serialized_bytes);
+
+        for bad_hex in ["0x0a01zz", "0x0a0", "0x 0a"].iter() {
+            match decode_clarity_value_hex(bad_hex) {
+                Err(HexValueError::Hex(_)) => {}
+                Err(e) => panic!("expected a hex error for {}, got {}", bad_hex, e),
+                Ok(_) => panic!("expected a hex error for {}", bad_hex),
+            }
+        }
+
+        match decode_clarity_value_hex("0x0a01000000") {
+            Err(HexValueError::Value(e)) => assert!(e.is_unexpected_eof()),
+            _ => panic!("expected a value error"),
+        }
+        let err = match decode_clarity_value_hex("0xff") {
+            Err(e) => e,
+            Ok(_) => panic!("expected an error"),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Error deserializing Clarity value: Bad type prefix 0xff"
+        );
+    }
+
+    #[test]
+    fn test_truncated_values() {
+        let inputs = [
+            "0x0b000000010c0000000507616464726573730516982f3ec112a5f5928a5c96a914bd733793b896a50863616e2d6275726e040863616e2d6d696e7404046e616d650d0000002b61697031302d61726b6164696b6f2d7570646174652d74766c2d6c69717569646174696f6e2d726174696f0e7175616c69666965642d6e616d650616982f3ec112a5f5928a5c96a914bd733793b896a52b61697031302d61726b6164696b6f2d7570646174652d74766c2d6c69717569646174696f6e2d726174696f",
+            "0x070a0100000000000000000000000000000005",
+            "0x0e0000000568656c6c6f",
+            "0x020000000401020304",
+        ];
+        for input in inputs.iter() {
+            let bytes = decode_hex(input).unwrap();
+            for len in 0..bytes.len() {
+                let mut cursor = Cursor::new(&bytes[..len]);
+                let err = ClarityValue::deserialize(&mut cursor, true).err().unwrap();
+                assert!(err.is_unexpected_eof(), "{}", err);
+            }
+            let mut cursor = Cursor::new(bytes.as_ref());
+            assert!(ClarityValue::deserialize(&mut cursor, true).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_length_prefix_exceeds_input() {
+        // buffer, list and string-utf8 claiming far more items/bytes than are present
+        let inputs = ["0x0200100000aabb", "0x0b000fffff01", "0x0e000fffff41"];
+        for input in inputs.iter() {
+            let bytes = decode_hex(input).unwrap();
+            let mut cursor = Cursor::new(bytes.as_ref());
+            let err = ClarityValue::deserialize(&mut cursor, false).err().unwrap();
+            assert!(err.is_unexpected_eof(), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_numeric_lists() {
+        // (list u1 u340282366920938463463374607431768211455)
+        let bytes = decode_hex(
+            "0x0b00000002010000000000000000000000000000000101ffffffffffffffffffffffffffffffff",
+        )
+        .unwrap();
+        assert_eq!(clarity_list_as_u128(&bytes).unwrap(), vec![1, u128::MAX]);
+        assert!(clarity_list_as_i128(&bytes).is_err());
+
+        // (list -1 2)
+        let bytes = decode_hex(
+            "0x0b0000000200ffffffffffffffffffffffffffffffff0000000000000000000000000000000002",
+        )
+        .unwrap();
+        assert_eq!(clarity_list_as_i128(&bytes).unwrap(), vec![-1, 2]);
+
+        // (list)
+        let bytes = decode_hex("0x0b00000000").unwrap();
+        assert!(clarity_list_as_u128(&bytes).unwrap().is_empty());
+
+        // (list u1 true u2), errors at the second element
+        let bytes = decode_hex(
+            "0x0b00000003010000000000000000000000000000000103010000000000000000000000000000000002",
+        )
+        .unwrap();
+        let err = clarity_list_as_u128(&bytes).unwrap_err();
+        assert_eq!(err.at_offset, Some(22));
+        assert!(err.error.contains("index 1"), "{}", err);
+
+        // not a list
+        let bytes = decode_hex("0x0100000000000000000000000000000001").unwrap();
+        assert!(clarity_list_as_u128(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_error_offset() {
+        // (list (ok u1) (some <corrupt>)), the inner value's type byte at offset 24 is 0x20
+        let input = "0x0b000000020701000000000000000000000000000000010a20";
+        let bytes = decode_hex(input).unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        let err = ClarityValue::deserialize(&mut cursor, true).err().unwrap();
+        assert_eq!(err.at_offset, Some(24));
+        assert_eq!(err.to_string(), "Bad type prefix 0x20");
+
+        // truncated input reports the innermost value being read
+        let mut cursor = Cursor::new(&bytes[..20]);
+        let err = ClarityValue::deserialize(&mut cursor, true).err().unwrap();
+        assert!(err.is_unexpected_eof());
+        assert_eq!(err.at_offset, Some(6));
+    }
+
+    #[test]
+    fn test_tuple_order() {
+        // (tuple (b u1) (a u2)), non-canonical field order
+        let input = "0x0c000000020162010000000000000000000000000000000101610100000000000000000000000000000002";
+        let bytes = decode_hex(input).unwrap();
+        let field_names = |val: &ClarityValue| match &val.value {
+            Value::Tuple(data) => data
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .collect::<Vec<_>>(),
+            _ => panic!("expected a tuple"),
+        };
+
+        let mut cursor = Cursor::new(bytes.as_ref());
+        let val = ClarityValue::deserialize(&mut cursor, true).unwrap();
+        assert_eq!(field_names(&val), ["b", "a"]);
+        assert_eq!(val.value.repr_string(), "(tuple (b u1) (a u2))");
+        assert_eq!(val.serialize_to_vec(), bytes.as_ref());
+
+        let options = DecodeOptions {
+            tuple_order: TupleOrder::Alphabetical,
+            ..Default::default()
+        };
+        let mut cursor = Cursor::new(bytes.as_ref());
+        let val = ClarityValue::deserialize_with_options(&mut cursor, true, &options).unwrap();
+        assert_eq!(field_names(&val), ["a", "b"]);
+        assert_eq!(val.value.repr_string(), "(tuple (a u2) (b u1))");
+        match &val.value {
+            Value::Tuple(data) => assert_eq!(data.get("b").unwrap().value.repr_string(), "u1"),
+            _ => panic!("expected a tuple"),
+        }
+    }
+
+    #[test]
+    fn test_strict_max_value_size() {
+        let strict = DecodeOptions {
+            strict_max_value_size: true,
+            ..Default::default()
+        };
+        let buffer_value = |data_len: u32| {
+            let mut bytes = vec![TypePrefix::Buffer as u8];
+            bytes.extend_from_slice(&data_len.to_be_bytes());
+            bytes.resize(bytes.len() + data_len as usize, 0);
+            bytes
+        };
+
+        // prefix + length + data is exactly MAX_VALUE_SIZE
+        let bytes = buffer_value(MAX_VALUE_SIZE - 5);
+        let mut cursor = Cursor::new(bytes.as_ref());
+        assert!(ClarityValue::deserialize_with_options(&mut cursor, false, &strict).is_ok());
+
+        let bytes = buffer_value(MAX_VALUE_SIZE - 4);
+        let mut cursor = Cursor::new(bytes.as_ref());
+        match ClarityValue::deserialize_with_options(&mut cursor, false, &strict) {
+            Err(err) => assert_eq!(err.as_string(), "value exceeds MAX_VALUE_SIZE"),
+            Ok(_) => panic!("expected MAX_VALUE_SIZE error"),
+        }
+
+        // not enforced by default
+        let mut cursor = Cursor::new(bytes.as_ref());
+        let options = DecodeOptions::default();
+        assert!(ClarityValue::deserialize_with_options(&mut cursor, false, &options).is_ok());
+    }
+
+    #[test]
+    fn test_principal_address_version() {
+        let principal = "0x0516a46ff88886c2ef9762d970b4d2c63678835bd39d";
+        let bytes = decode_hex(principal).unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        assert!(ClarityValue::deserialize(&mut cursor, false).is_ok());
+
+        // version byte out of the c32 range
+        let bytes = decode_hex("0x0520a46ff88886c2ef9762d970b4d2c63678835bd39d").unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        assert!(ClarityValue::deserialize(&mut cursor, false).is_err());
+        let bytes = decode_hex("0x06ffa46ff88886c2ef9762d970b4d2c63678835bd39d0161").unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        assert!(ClarityValue::deserialize(&mut cursor, false).is_err());
+
+        // valid c32 version, but not a known address version
+        let strict = DecodeOptions {
+            strict_address_versions: true,
+            ..Default::default()
+        };
+        let bytes = decode_hex("0x0500a46ff88886c2ef9762d970b4d2c63678835bd39d").unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        assert!(ClarityValue::deserialize(&mut cursor, false).is_ok());
+        let mut cursor = Cursor::new(bytes.as_ref());
+        assert!(ClarityValue::deserialize_with_options(&mut cursor, false, &strict).is_err());
+
+        let bytes = decode_hex(principal).unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        assert!(ClarityValue::deserialize_with_options(&mut cursor, false, &strict).is_ok());
+    }
+
+    #[test]
+    fn test_bad_type_prefix() {
+        let bytes = decode_hex("0xff").unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        assert!(ClarityValue::deserialize(&mut cursor, false).is_err());
+    }
+
+    #[test]
+    fn test_empty_contract_name() {
+        // 'SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR. with a zero length name
+        let bytes = decode_hex("0x0616982f3ec112a5f5928a5c96a914bd733793b896a500").unwrap();
+        let lenient = DecodeOptions {
+            strict_contract_names: false,
+            ..Default::default()
+        };
+        for options in [DecodeOptions::default(), lenient].iter() {
+            let mut cursor = Cursor::new(bytes.as_ref());
+            let err = ClarityValue::deserialize_with_options(&mut cursor, false, options)
+                .err()
+                .unwrap();
+            assert_eq!(
+                err.to_string(),
+                "Failed to deserialize contract principal: empty contract name"
+            );
+            assert_eq!(err.at_offset, Some(0));
+        }
+    }
+
+    #[test]
+    fn test_strict_contract_names() {
+        // 'SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR.1vault, names can't start with a digit
+        let bytes =
+            decode_hex("0x0616982f3ec112a5f5928a5c96a914bd733793b896a506317661756c74").unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        let err = ClarityValue::deserialize(&mut cursor, false).err().unwrap();
+        assert_eq!(err.to_string(), "Invalid contract name \"1vault\"");
+        assert_eq!(err.at_offset, Some(0));
+
+        let lenient = DecodeOptions {
+            strict_contract_names: false,
+            ..Default::default()
+        };
+        let mut cursor = Cursor::new(bytes.as_ref());
+        let val = ClarityValue::deserialize_with_options(&mut cursor, false, &lenient).unwrap();
+        assert_eq!(
+            val.value.repr_string(),
+            "'SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR.1vault"
+        );
+
+        // non-ASCII names are rejected in both modes
+        let bytes =
+            decode_hex("0x0616982f3ec112a5f5928a5c96a914bd733793b896a5067661c3bc6c74").unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        assert!(ClarityValue::deserialize(&mut cursor, false).is_err());
+        let mut cursor = Cursor::new(bytes.as_ref());
+        assert!(ClarityValue::deserialize_with_options(&mut cursor, false, &lenient).is_err());
+
+        let bytes =
+            decode_hex("0x0616982f3ec112a5f5928a5c96a914bd733793b896a5057661756c74").unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        assert!(ClarityValue::deserialize(&mut cursor, false).is_ok());
+    }
+
+    #[test]
+    fn test_on_unknown_type() {
+        // (list u1 <0x0f type>) followed by raw bytes of the unknown type
+        let bytes =
+            decode_hex("0x0b0000000201000000000000000000000000000000010f0000000201020304").unwrap();
+
+        let mut cursor = Cursor::new(bytes.as_ref());
+        let err = ClarityValue::deserialize(&mut cursor, true).err().unwrap();
+        assert_eq!(err.to_string(), "Bad type prefix 0x0f");
+        assert_eq!(err.at_offset, Some(22));
+
+        let options = DecodeOptions {
+            on_unknown: UnknownTypeMode::Capture,
+            ..Default::default()
+        };
+        let mut cursor = Cursor::new(bytes.as_ref());
+        let val = ClarityValue::deserialize_with_options(&mut cursor, true, &options).unwrap();
+        assert_eq!(
+            val.value.repr_string(),
+            "(list u1 (unknown 0x0f 0x0000000201020304))"
+        );
+        let items = match &val.value {
+            Value::List(items) => items,
+            _ => panic!("expected a list"),
+        };
+        match &items[1].value {
+            Value::Unknown { type_id, raw } => {
+                assert_eq!(*type_id, 0x0f);
+                assert_eq!(raw, &[0, 0, 0, 2, 1, 2, 3, 4]);
+            }
+            _ => panic!("expected an unknown value"),
+        }
+        assert_eq!(items[1].value.type_id(), 0x0f);
+        assert_eq!(items[1].serialized_bytes.as_ref().unwrap(), &bytes[22..]);
+        assert_eq!(val.serialize_to_vec(), bytes.as_ref());
+    }
+
+    #[test]
+    fn test_on_unknown_type_nested() {
+        let options = DecodeOptions {
+            on_unknown: UnknownTypeMode::Capture,
+            tuple_order: TupleOrder::Alphabetical,
+            ..Default::default()
+        };
+        let decode = |hex: &str| {
+            let bytes = decode_hex(hex).unwrap();
+            let mut cursor = Cursor::new(bytes.as_ref());
+            let val = ClarityValue::deserialize_with_options(&mut cursor, false, &options).unwrap();
+            assert!(val.value.ends_in_capture());
+            // the containers stopped early, so they serialize from their original bytes
+            assert_eq!(val.serialize_to_vec(), bytes.as_ref());
+            val.value.repr_string()
+        };
+
+        // (tuple (a (list <0x0f 0xaa>)) (b u1))
+        assert_eq!(
+            decode("0x0c0000000201610b000000010faa01620100000000000000000000000000000001"),
+            "(tuple (a (list (unknown 0x0f 0xaa01620100000000000000000000000000000001))))"
+        );
+        // (list (some <0x0f 0xaa>) u1)
+        assert_eq!(
+            decode("0x0b000000020a0faa0100000000000000000000000000000001"),
+            "(list (some (unknown 0x0f 0xaa0100000000000000000000000000000001)))"
+        );
+        // (tuple (z u1) (a <0x0f 0xaa>) ...), the captured field isn't last once sorted
+        assert_eq!(
+            decode("0x0c00000003017a010000000000000000000000000000000101610faa"),
+            "(tuple (a (unknown 0x0f 0xaa)) (z u1))"
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use byteorder::ReadBytesExt;
+use std::io::Cursor;
+
+use crate::address::c32::c32_address;
+use crate::serialize_util::{ensure_remaining, read_uint_be, DeserializeError};
+
+use super::deserialize::TypePrefix;
+use super::types::{MAX_STRING_LEN, MAX_VALUE_SIZE};
+
+// Rough V8 heap sizes, in bytes.
+const JS_OBJECT_OVERHEAD: usize = 56;
+const JS_PROPERTY_OVERHEAD: usize = 8;
+const JS_STRING_OVERHEAD: usize = 16;
+const JS_ARRAY_OVERHEAD: usize = 32;
+const JS_ARRAY_ELEMENT_OVERHEAD: usize = 8;
+
+/// Estimate the JS heap size in bytes of the object that a deep `decodeClarityValue` would
+/// build for a serialized value, without decoding it. Lets callers reject values that would
+/// expand into huge JS objects before crossing into JS.
+pub fn estimate_js_size(bytes: &[u8]) -> Result<usize, DeserializeError> {
+    let mut cursor = Cursor::new(bytes);
+    let mut total = 0;
+    estimate_value(&mut cursor, 0, &mut total)?;
+    Ok(total)
+}
+
+fn js_string(len: usize) -> usize {
+    JS_PROPERTY_OVERHEAD + JS_STRING_OVERHEAD + len
+}
+
+fn js_hex_string(byte_len: usize) -> usize {
+    js_string(2 + byte_len * 2)
+}
+
+fn decimal_len(mut n: u128) -> usize {
+    let mut len = 1;
+    while n >= 10 {
+        n /= 10;
+        len += 1;
+    }
+    len
+}
+
+/// Length of a byte after `std::ascii::escape_default`, which is how reprs escape strings.
+fn escaped_len(c: u8) -> usize {
+    match c {
+        b'\t' | b'\r' | b'\n' | b'\'' | b'"' | b'\\' => 2,
+        0x20..=0x7e => 1,
+        _ => 4,
+    }
+}
+
+fn read_len(r: &mut Cursor<&[u8]>, type_name: &str) -> Result<u32, DeserializeError> {
+    let len = read_uint_be::<4>(r)? as u32;
+    if len > MAX_VALUE_SIZE {
+        return Err(format!("Illegal {} type size", type_name).into());
+    }
+    ensure_remaining(r, len as u64)?;
+    Ok(len)
+}
+
+fn read_bytes<'a>(r: &mut Cursor<&'a [u8]>, len: usize) -> Result<&'a [u8], DeserializeError> {
+    ensure_remaining(r, len as u64)?;
+    let start = r.position() as usize;
+    r.set_position((start + len) as u64);
+    Ok(&r.get_ref()[start..start + len])
+}
+
+fn read_clarity_name<'a>(r: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], DeserializeError> {
+    let len_byte = r.read_u8()?;
+    if len_byte > MAX_STRING_LEN {
+        return Err(format!("Failed to deserialize clarity name: too long: {}", len_byte).into());
+    }
+    read_bytes(r, len_byte as usize)
+}
+
+fn principal_address_len(r: &mut Cursor<&[u8]>) -> Result<usize, DeserializeError> {
+    let version = r.read_u8()?;
+    let hash_bytes = read_bytes(r, 20)?;
+    let address = c32_address(version, hash_bytes)
+        .map_err(|e| format!("Error converting to C32 address: {}", e))?;
+    Ok(address.len())
+}
+
+/// Adds the estimated JS size of the value and its children to `total`, and returns the length
+/// of the value's repr string, which its parent's repr includes.
+fn estimate_value(
+    r: &mut Cursor<&[u8]>,
+    depth: u8,
+    total: &mut usize,
+) -> Result<usize, DeserializeError> {
+    if depth >= 16 {
+        return Err(format!("TypeSignatureTooDeep: {}", depth).into());
+    }
+
+    let start = r.position() as usize;
+    let prefix = TypePrefix::from_u8(r.read_u8()?).ok_or("Bad type prefix")?;
+
+    // type-specific properties, and the length of the repr string
+    let (fields_size, repr_len) = match prefix {
+        TypePrefix::Int => {
+            let val = read_uint_be::<16>(r)? as i128;
+            let len = decimal_len(val.unsigned_abs()) + (val < 0) as usize;
+            (js_string(len), len)
+        }
+        TypePrefix::UInt => {
+            let len = decimal_len(read_uint_be::<16>(r)?);
+            (js_string(len), len + 1)
+        }
+        TypePrefix::BoolTrue => (JS_PROPERTY_OVERHEAD, 4),
+        TypePrefix::BoolFalse => (JS_PROPERTY_OVERHEAD, 5),
+        TypePrefix::Buffer => {
+            let len = read_len(r, "buffer")? as usize;
+            read_bytes(r, len)?;
+            (js_hex_string(len), 2 + len * 2)
+        }
+        TypePrefix::StringASCII => {
+            let len = read_len(r, "string-ascii")? as usize;
+            let data = read_bytes(r, len)?;
+            let escaped: usize = data.iter().map(|c| escaped_len(*c)).sum();
+            (js_string(len), escaped + 2)
+        }
+        TypePrefix::StringUTF8 => {
+            let len = read_len(r, "string-utf8")? as usize;
+            let data = String::from_utf8_lossy(read_bytes(r, len)?);
+            let escaped: usize = data
+                .chars()
+                .map(|c| match c.len_utf8() {
+                    1 => escaped_len(c as u8),
+                    // `\u{...}` around the hex encoded bytes
+                    n => 4 + n * 2,
+                })
+                .sum();
+            (js_string(data.len()), escaped + 3)
+        }
+        TypePrefix::PrincipalStandard => {
+            let address_len = principal_address_len(r)?;
+            let fields = JS_PROPERTY_OVERHEAD + js_hex_string(20) + js_string(address_len);
+            (fields, address_len + 1)
+        }
+        TypePrefix::PrincipalContract => {
+            let address_len = principal_address_len(r)?;
+            let name_len = read_clarity_name(r)?.len();
+            let fields = JS_PROPERTY_OVERHEAD
+                + js_hex_string(20)
+                + js_string(address_len)
+                + js_string(name_len);
+            (fields, address_len + name_len + 2)
+        }
+        TypePrefix::OptionalNone => (JS_PROPERTY_OVERHEAD, 4),
+        TypePrefix::OptionalSome | TypePrefix::ResponseOk | TypePrefix::ResponseErr => {
+            let inner_repr_len = estimate_value(r, depth + 1, total)?;
+            let wrapper_len = match prefix {
+                TypePrefix::OptionalSome => "(some )".len(),
+                TypePrefix::ResponseOk => "(ok )".len(),
+                _ => "(err )".len(),
+            };
+            (JS_PROPERTY_OVERHEAD, inner_repr_len + wrapper_len)
+        }
+        TypePrefix::List => {
+            let len = read_len(r, "list")?;
+            let mut repr_len = "(list)".len();
+            for _i in 0..len {
+                repr_len += 1 + estimate_value(r, depth + 1, total)?;
+            }
+            let fields =
+                JS_PROPERTY_OVERHEAD + JS_ARRAY_OVERHEAD + len as usize * JS_ARRAY_ELEMENT_OVERHEAD;
+            (fields, repr_len)
+        }
+        TypePrefix::Tuple => {
+            let len = read_len(r, "tuple")?;
+            let mut repr_len = "(tuple)".len();
+            let mut fields = JS_PROPERTY_OVERHEAD + JS_OBJECT_OVERHEAD;
+            for _i in 0..len {
+                let name_len = read_clarity_name(r)?.len();
+                // ` (name value)`
+                repr_len += name_len + 4 + estimate_value(r, depth + 1, total)?;
+                fields += JS_PROPERTY_OVERHEAD + name_len;
+            }
+            (fields, repr_len)
+        }
+    };
+
+    let byte_len = r.position() as usize - start;
+    // the object, its repr and hex strings, and its type_id
+    *total += JS_OBJECT_OVERHEAD
+        + js_string(repr_len)
+        + js_hex_string(byte_len)
+        + JS_PROPERTY_OVERHEAD
+        + fields_size;
+    Ok(repr_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clarity_value::types::{ClarityValue, Value};
+    use crate::hex::decode_hex;
+
+    /// The estimate computed from a fully decoded value.
+    fn decoded_js_size(val: &ClarityValue) -> usize {
+        let fields_size = match &val.value {
+            Value::Int(n) => js_string(n.to_string().len()),
+            Value::UInt(n) => js_string(n.to_string().len()),
+            Value::Bool(_) | Value::OptionalNone => JS_PROPERTY_OVERHEAD,
+            Value::Buffer(buff) => js_hex_string(buff.len()),
+            Value::StringASCII(data) => js_string(data.len()),
+            Value::StringUTF8(data) => js_string(data.iter().map(|c| c.len()).sum()),
+            Value::PrincipalStandard(principal) => {
+                let address = c32_address(principal.0, &principal.1).unwrap();
+                JS_PROPERTY_OVERHEAD + js_hex_string(20) + js_string(address.len())
+            }
+            Value::PrincipalContract(contract) => {
+                let address = c32_address(contract.issuer.0, &contract.issuer.1).unwrap();
+                JS_PROPERTY_OVERHEAD
+                    + js_hex_string(20)
+                    + js_string(address.len())
+                    + js_string(contract.name.len())
+            }
+            Value::OptionalSome(inner) | Value::ResponseOk(inner) | Value::ResponseErr(inner) => {
+                JS_PROPERTY_OVERHEAD + decoded_js_size(inner)
+            }
+            Value::List(items) => {
+                JS_PROPERTY_OVERHEAD
+                    + JS_ARRAY_OVERHEAD
+                    + items
+                        .iter()
+                        .map(|item| JS_ARRAY_ELEMENT_OVERHEAD + decoded_js_size(item))
+                        .sum::<usize>()
+            }
+            Value::Tuple(data) => {
+                JS_PROPERTY_OVERHEAD
+                    + JS_OBJECT_OVERHEAD
+                    + data
+                        .iter()
+                        .map(|(name, value)| {
+                            JS_PROPERTY_OVERHEAD + name.len() + decoded_js_size(value)
+                        })
+                        .sum::<usize>()
+            }
+            Value::Unknown { raw, .. } => js_hex_string(raw.len()),
+            Value::Truncated(inner) => return decoded_js_size(inner),
+        };
+        JS_OBJECT_OVERHEAD
+            + js_string(val.value.repr_string().len())
+            + js_hex_string(val.serialized_bytes.as_ref().unwrap().len())
+            + JS_PROPERTY_OVERHEAD
+            + fields_size
+    }
+
+    #[test]
+    fn test_estimate_js_size() {
+        let inputs = [
+            // -170141183460469231731687303715884105728
+            "0x0080000000000000000000000000000000",
+            // u1
+            "0x0100000000000000000000000000000001",
+            // 0x0102
+            "0x02000000020102",
+            // (ok (list (tuple (a 0x0102) (b (some u1))) (tuple (a 0x03) (b none))))
+            "0x070b000000020c0000000201610200000002010201620a01000000000000000000000000000000010c000000020161020000000103016209",
+            // "hi\n\"\x01"
+            "0x0d0000000568690a2201",
+            // u"a\u{e9}\u{1f600}"
+            "0x0e0000000761c3a9f09f9880",
+            // (err 'SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.pox)
+            "0x080616a46ff88886c2ef9762d970b4d2c63678835bd39d03706f78",
+            // (list 'S000000000000000000006EKBDDS false)
+            "0x0b000000020500000000000000000000000000000000000000000004",
+        ];
+        for input in inputs.iter() {
+            let bytes = decode_hex(input).unwrap();
+            let val = ClarityValue::deserialize(&mut Cursor::new(bytes.as_ref()), true).unwrap();
+            assert_eq!(
+                estimate_js_size(&bytes).unwrap(),
+                decoded_js_size(&val),
+                "{}",
+                input
+            );
+        }
+
+        // nested values repeat their descendants' repr and hex strings
+        let small = estimate_js_size(&decode_hex(inputs[1]).unwrap()).unwrap();
+        let wrapped =
+            estimate_js_size(&decode_hex("0x0a0a0100000000000000000000000000000001").unwrap())
+                .unwrap();
+        assert!(wrapped > small * 2);
+    }
+
+    #[test]
+    fn test_estimate_js_size_invalid() {
+        let bytes = decode_hex("0x070b000000020c00000002016102").unwrap();
+        assert!(estimate_js_size(&bytes).unwrap_err().is_unexpected_eof());
+        assert!(estimate_js_size(&[0xff]).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt::Write;
+
+use crate::address::c32::c32_address;
+use crate::hex::encode_hex;
+
+use super::types::{ClarityValue, Value};
+
+/// Serialize a Clarity value to a JSON string with the same shape as the deep `decodeClarityValue`
+/// JS output. The value must have been deserialized with `with_bytes` enabled.
+pub fn clarity_value_to_json(val: &ClarityValue) -> Result<String, String> {
+    let mut json = String::new();
+    write_clarity_value_json(&mut json, val)?;
+    Ok(json)
+}
+
+pub(crate) fn write_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                write!(out, "\\u{:04x}", c as u32).unwrap();
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_principal_json(out: &mut String, version: u8, hash_bytes: &[u8]) -> Result<(), String> {
+    let address = c32_address(version, hash_bytes)
+        .map_err(|e| format!("Error converting to C32 address: {}", e))?;
+    write!(out, ",\"address_version\":{}", version).unwrap();
+    out.push_str(",\"address_hash_bytes\":");
+    write_json_string(out, &encode_hex(hash_bytes));
+    out.push_str(",\"address\":");
+    write_json_string(out, &address);
+    Ok(())
+}
+
+fn write_clarity_value_json(out: &mut String, val: &ClarityValue) -> Result<(), String> {
+    if let Value::Truncated(inner) = &val.value {
+        return write_clarity_value_json(out, inner);
+    }
+    let bytes = val
+        .serialized_bytes
+        .as_ref()
+        .ok_or("Clarity value was deserialized without bytes")?;
+
+    out.push_str("{\"repr\":");
+    write_json_string(out, &val.value.repr_string());
+    out.push_str(",\"hex\":");
+    write_json_string(out, &encode_hex(bytes));
+    write!(out, ",\"type_id\":{}", val.value.type_id()).unwrap();
+
+    match &val.value {
+        Value::Int(val) => {
+            out.push_str(",\"value\":");
+            write_json_string(out, &val.to_string());
+        }
+        Value::UInt(val) => {
+            out.push_str(",\"value\":");
+            write_json_string(out, &val.to_string());
+        }
+        Value::Bool(val) => {
+            write!(out, ",\"value\":{}", val).unwrap();
+        }
+        Value::Buffer(buff) => {
+            out.push_str(",\"buffer\":");
+            write_json_string(out, &encode_hex(buff));
+        }
+        Value::List(data) => {
+            out.push_str(",\"list\":[");
+            for (i, item) in data.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_clarity_value_json(out, item)?;
+            }
+            out.push(']');
+        }
+        Value::StringASCII(str_data) => {
+            out.push_str(",\"data\":");
+            write_json_string(out, &String::from_utf8_lossy(str_data));
+        }
+        Value::StringUTF8(str_data) => {
+            let utf8_bytes: Vec<u8> = str_data.iter().flatten().copied().collect();
+            out.push_str(",\"data\":");
+            write_json_string(out, &String::from_utf8_lossy(&utf8_bytes));
+        }
+        Value::PrincipalStandard(standard_principal) => {
+            write_principal_json(out, standard_principal.0, &standard_principal.1)?;
+        }
+        Value::PrincipalContract(contract_identifier) => {
+            let issuer = &contract_identifier.issuer;
+            write_principal_json(out, issuer.0, &issuer.1)?;
+            out.push_str(",\"contract_name\":");
+            write_json_string(out, contract_identifier.name.as_str());
+        }
+        Value::Tuple(data) => {
+            out.push_str(",\"data\":{");
+            for (i, (key, value)) in data.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_json_string(out, key.as_str());
+                out.push(':');
+                write_clarity_value_json(out, value)?;
+            }
+            out.push('}');
+        }
+        Value::OptionalSome(data) | Value::ResponseOk(data) | Value::ResponseErr(data) => {
+            out.push_str(",\"value\":");
+            write_clarity_value_json(out, data)?;
+        }
+        Value::OptionalNone => {
+            out.push_str(",\"value\":null");
+        }
+        Value::Unknown { raw, .. } => {
+            out.push_str(",\"raw\":");
+            write_json_string(out, &encode_hex(raw));
+        }
+        Value::Truncated(_) => unreachable!(),
+    }
+    out.push('}');
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::decode_hex;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_clarity_value_to_json() {
+        // (tuple (a (ok u1)) (b "hi\n"))
+        let bytes =
+            decode_hex("0x0c00000002016107010000000000000000000000000000000101620d0000000368690a")
+                .unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        let val = ClarityValue::deserialize(&mut cursor, true).unwrap();
+        let json = clarity_value_to_json(&val).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"repr":"(tuple (a (ok u1)) (b \"hi\\n\"))","#,
+                r#""hex":"0x0c00000002016107010000000000000000000000000000000101620d0000000368690a","#,
+                r#""type_id":12,"data":{"#,
+                r#""a":{"repr":"(ok u1)","hex":"0x070100000000000000000000000000000001","type_id":7,"#,
+                r#""value":{"repr":"u1","hex":"0x0100000000000000000000000000000001","type_id":1,"value":"1"}},"#,
+                r#""b":{"repr":"\"hi\\n\"","hex":"0x0d0000000368690a","type_id":13,"data":"hi\n"}}}"#
+            )
+        );
+
+        let val = ClarityValue::deserialize(&mut Cursor::new(bytes.as_ref()), false).unwrap();
+        assert!(clarity_value_to_json(&val).is_err());
+    }
+}
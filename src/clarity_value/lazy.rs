@@ -0,0 +1,174 @@
+use byteorder::ReadBytesExt;
+use std::io::{Cursor, Read};
+
+use crate::serialize_util::{read_uint_be, DeserializeError};
+
+use super::deserialize::TypePrefix;
+use super::stats::{skip_bytes, skip_value};
+use super::types::ClarityValue;
+
+#[cfg(test)]
+thread_local! {
+    // not a `const` initializer, which needs a newer compiler than the crate otherwise does
+    #[allow(clippy::missing_const_for_thread_local)]
+    static DECODE_COUNT: std::cell::Cell<u32> = std::cell::Cell::new(0);
+}
+
+/// A serialized Clarity value that is only decoded on demand. List items, tuple fields and
+/// optional/response inner values are returned as lazy handles, sibling values are skipped over
+/// without being decoded.
+#[derive(Debug, Clone, Copy)]
+pub struct LazyClarityValue<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> LazyClarityValue<'a> {
+    /// Wraps the first serialized value in `bytes`, any trailing bytes are ignored.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, DeserializeError> {
+        let mut cursor = Cursor::new(bytes);
+        skip_value(&mut cursor)?;
+        Ok(LazyClarityValue {
+            bytes: &bytes[..cursor.position() as usize],
+        })
+    }
+
+    /// The serialized bytes of this value.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn type_prefix(&self) -> TypePrefix {
+        // the prefix was validated when the value was skipped over
+        TypePrefix::from_u8(self.bytes[0]).unwrap()
+    }
+
+    /// Fully decode this value and all of its children.
+    pub fn decode(&self) -> Result<ClarityValue, DeserializeError> {
+        #[cfg(test)]
+        DECODE_COUNT.with(|count| count.set(count.get() + 1));
+
+        let mut cursor = Cursor::new(self.bytes);
+        ClarityValue::deserialize(&mut cursor, true)
+    }
+
+    /// Number of items in a list, or fields in a tuple.
+    pub fn len(&self) -> Result<u32, DeserializeError> {
+        match self.type_prefix() {
+            TypePrefix::List | TypePrefix::Tuple => {
+                let mut cursor = Cursor::new(&self.bytes[1..]);
+                Ok(read_uint_be::<4>(&mut cursor)? as u32)
+            }
+            prefix => Err(format!("Expected a list or tuple, found {:?}", prefix).into()),
+        }
+    }
+
+    pub fn is_empty(&self) -> Result<bool, DeserializeError> {
+        Ok(self.len()? == 0)
+    }
+
+    /// The list item at `index`, or `None` if out of bounds.
+    pub fn get(&self, index: u32) -> Result<Option<LazyClarityValue<'a>>, DeserializeError> {
+        if self.type_prefix() != TypePrefix::List {
+            return Err(format!("Expected a list, found {:?}", self.type_prefix()).into());
+        }
+        let len = self.len()?;
+        if index >= len {
+            return Ok(None);
+        }
+        let mut cursor = Cursor::new(self.bytes);
+        cursor.set_position(5);
+        for _i in 0..index {
+            skip_value(&mut cursor)?;
+        }
+        Ok(Some(self.child_at(&mut cursor)?))
+    }
+
+    /// The tuple field named `name`, or `None` if the tuple doesn't contain it.
+    pub fn field(&self, name: &str) -> Result<Option<LazyClarityValue<'a>>, DeserializeError> {
+        if self.type_prefix() != TypePrefix::Tuple {
+            return Err(format!("Expected a tuple, found {:?}", self.type_prefix()).into());
+        }
+        let len = self.len()?;
+        let mut cursor = Cursor::new(self.bytes);
+        cursor.set_position(5);
+        for _i in 0..len {
+            let name_len = cursor.read_u8()?;
+            let mut field_name = vec![0u8; name_len as usize];
+            cursor.read_exact(&mut field_name)?;
+            if field_name == name.as_bytes() {
+                return Ok(Some(self.child_at(&mut cursor)?));
+            }
+            skip_value(&mut cursor)?;
+        }
+        Ok(None)
+    }
+
+    /// The wrapped value of a `(some ...)`, `(ok ...)` or `(err ...)`, or `None` for `none`.
+    pub fn inner(&self) -> Result<Option<LazyClarityValue<'a>>, DeserializeError> {
+        match self.type_prefix() {
+            TypePrefix::OptionalSome | TypePrefix::ResponseOk | TypePrefix::ResponseErr => {
+                let mut cursor = Cursor::new(self.bytes);
+                skip_bytes(&mut cursor, 1)?;
+                Ok(Some(self.child_at(&mut cursor)?))
+            }
+            TypePrefix::OptionalNone => Ok(None),
+            prefix => Err(format!("Expected an optional or response, found {:?}", prefix).into()),
+        }
+    }
+
+    fn child_at(&self, cursor: &mut Cursor<&'a [u8]>) -> Result<Self, DeserializeError> {
+        let start = cursor.position() as usize;
+        skip_value(cursor)?;
+        Ok(LazyClarityValue {
+            bytes: &self.bytes[start..cursor.position() as usize],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::decode_hex;
+
+    fn decode_count() -> u32 {
+        DECODE_COUNT.with(|count| count.get())
+    }
+
+    #[test]
+    fn test_lazy_deep_field() {
+        // (ok (list (tuple (a 0x0102) (b (some u1))) (tuple (a 0x03) (b none))))
+        let bytes = decode_hex("0x070b000000020c0000000201610200000002010201620a01000000000000000000000000000000010c000000020161020000000103016209").unwrap();
+        let root = LazyClarityValue::new(&bytes).unwrap();
+        assert_eq!(root.bytes().len(), bytes.len());
+
+        let list = root.inner().unwrap().unwrap();
+        assert_eq!(list.len().unwrap(), 2);
+        let second = list.get(1).unwrap().unwrap();
+        let field = second.field("a").unwrap().unwrap();
+
+        // navigating to the field didn't decode the list, tuples or sibling values
+        assert_eq!(decode_count(), 0);
+        let value = field.decode().unwrap();
+        assert_eq!(decode_count(), 1);
+        assert_eq!(value.value.repr_string(), "0x03");
+
+        let first_b = list.get(0).unwrap().unwrap().field("b").unwrap().unwrap();
+        assert_eq!(first_b.type_prefix(), TypePrefix::OptionalSome);
+        let first_b_inner = first_b.inner().unwrap().unwrap();
+        assert_eq!(first_b_inner.decode().unwrap().value.repr_string(), "u1");
+
+        let second_b = second.field("b").unwrap().unwrap();
+        assert!(second_b.inner().unwrap().is_none());
+
+        assert!(list.get(2).unwrap().is_none());
+        assert!(second.field("c").unwrap().is_none());
+        assert!(root.get(0).is_err());
+        assert!(list.field("a").is_err());
+    }
+
+    #[test]
+    fn test_lazy_truncated() {
+        let bytes = decode_hex("0x070b000000020c00000002016102").unwrap();
+        assert!(LazyClarityValue::new(&bytes).is_err());
+    }
+}
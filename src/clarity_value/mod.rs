@@ -1,28 +1,83 @@
-use std::{convert::TryInto, io::Cursor};
+use std::{
+    convert::{TryFrom, TryInto},
+    io::Cursor,
+};
 
 use neon::prelude::*;
 
-use crate::neon_util::{arg_as_bytes, arg_as_bytes_copied};
+use crate::neon_util::{arg_as_bytes, arg_as_bytes_copied, NeonJsSerialize};
 
+use self::neon_encoder::{decode_clarity_val_with_encoding, BufferEncoding};
+use self::print_event::{decode_print_event, decode_stx_transfer_event};
+use self::types::is_valid_contract_name;
 use self::{neon_encoder::decode_clarity_val, types::ClarityValue};
 
+#[cfg(feature = "arena")]
+pub mod arena;
+#[cfg(feature = "cache")]
+pub mod cache;
 pub mod deserialize;
+pub mod js_size;
+pub mod json;
+pub mod lazy;
 pub mod neon_encoder;
+pub mod print_event;
+pub mod serialize;
+pub mod sip018;
+pub mod stacking;
+pub mod stats;
 pub mod types;
 
 pub fn decode_clarity_value(mut cx: FunctionContext) -> JsResult<JsObject> {
     let val_bytes = arg_as_bytes_copied(&mut cx, 0)?;
 
     let mut cursor: Cursor<&[u8]> = Cursor::new(&val_bytes);
-    let clarity_value = ClarityValue::deserialize(&mut cursor, true)
-        .or_else(|e| cx.throw_error(format!("Error deserializing Clarity value: {}", e)))?;
+    let clarity_value = ClarityValue::deserialize(&mut cursor, true).or_else(|e| {
+        cx.throw_error(format!(
+            "Error deserializing Clarity value: {}",
+            e.as_string()
+        ))
+    })?;
+
+    let buffer_encoding = arg_buffer_encoding(&mut cx, 1)?;
 
     let root_obj = cx.empty_object();
-    decode_clarity_val(&mut cx, &root_obj, &clarity_value, true, val_bytes)?;
+    decode_clarity_val_with_encoding(
+        &mut cx,
+        &root_obj,
+        &clarity_value,
+        true,
+        val_bytes,
+        buffer_encoding,
+    )?;
 
     return Ok(root_obj);
 }
 
+/// Reads the `buffer_encoding` and `buffers_as_bytes` properties from an optional options
+/// object argument.
+fn arg_buffer_encoding(cx: &mut FunctionContext, arg_index: i32) -> NeonResult<BufferEncoding> {
+    let options = match cx.argument_opt(arg_index) {
+        Some(arg) if !arg.is_a::<JsUndefined, _>(cx) => arg.downcast_or_throw::<JsObject, _>(cx)?,
+        _ => return Ok(BufferEncoding::default()),
+    };
+    let encoding = options
+        .get_opt::<JsString, _, _>(cx, "buffer_encoding")?
+        .map(|encoding| encoding.value(cx));
+    let as_bytes = options
+        .get_opt::<JsBoolean, _, _>(cx, "buffers_as_bytes")?
+        .map(|as_bytes| as_bytes.value(cx))
+        .unwrap_or(false);
+    match (encoding, as_bytes) {
+        (Some(_), true) => cx.throw_error("Cannot set both buffer_encoding and buffers_as_bytes"),
+        (None, true) => Ok(BufferEncoding::Bytes),
+        (Some(encoding), false) => {
+            BufferEncoding::try_from(encoding.as_str()).or_else(|e| cx.throw_error(e))
+        }
+        (None, false) => Ok(BufferEncoding::default()),
+    }
+}
+
 pub fn decode_clarity_value_type_name(mut cx: FunctionContext) -> JsResult<JsString> {
     let type_string = arg_as_bytes(&mut cx, 0, |val_bytes| {
         let mut cursor = Cursor::new(val_bytes);
@@ -70,8 +125,13 @@ pub fn decode_clarity_value_array(mut cx: FunctionContext) -> JsResult<JsArray>
         let mut i: u32 = 0;
         while byte_cursor.position() < val_len {
             let cursor_pos = byte_cursor.position();
-            let clarity_value = ClarityValue::deserialize(&mut byte_cursor, deep)
-                .or_else(|e| cx.throw_error(format!("Error deserializing Clarity value: {}", e)))?;
+            // offsets are reported relative to the whole input, after the 4 byte length
+            let clarity_value = ClarityValue::deserialize(&mut byte_cursor, deep).or_else(|e| {
+                cx.throw_error(format!(
+                    "Error deserializing Clarity value: {}",
+                    e.offset_by(4).as_string()
+                ))
+            })?;
             let decoded_bytes =
                 &byte_cursor.get_ref()[cursor_pos as usize..byte_cursor.position() as usize];
             let value_obj = cx.empty_object();
@@ -82,3 +142,34 @@ pub fn decode_clarity_value_array(mut cx: FunctionContext) -> JsResult<JsArray>
     }
     Ok(array_result)
 }
+
+pub fn decode_clarity_print_event(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let contract_id = cx.argument::<JsString>(1)?.value(&mut cx);
+    let event = arg_as_bytes(&mut cx, 0, |val_bytes| {
+        decode_print_event(val_bytes, &contract_id).map_err(|err| err.as_string())
+    })
+    .or_else(|e| cx.throw_error(format!("Error decoding print event: {}", e)))?;
+
+    let event_obj = cx.empty_object();
+    event.neon_js_serialize(&mut cx, &event_obj, &())?;
+    Ok(event_obj)
+}
+
+pub fn decode_clarity_stx_transfer_event(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let event_json = cx.argument::<JsString>(0)?.value(&mut cx);
+    let event = decode_stx_transfer_event(&event_json).or_else(|e| {
+        cx.throw_error(format!(
+            "Error decoding STX transfer event: {}",
+            e.as_string()
+        ))
+    })?;
+
+    let event_obj = cx.empty_object();
+    event.neon_js_serialize(&mut cx, &event_obj, &())?;
+    Ok(event_obj)
+}
+
+pub fn is_valid_clarity_contract_name(mut cx: FunctionContext) -> JsResult<JsBoolean> {
+    let contract_name = cx.argument::<JsString>(0)?.value(&mut cx);
+    Ok(cx.boolean(is_valid_contract_name(&contract_name)))
+}
@@ -1,7 +1,63 @@
 use crate::address::c32::c32_address;
+use crate::base64::encode_base64;
 use crate::clarity_value;
 use crate::hex::encode_hex;
+use crate::neon_util::NeonJsSerialize;
 use neon::prelude::*;
+use std::convert::TryFrom;
+
+use super::print_event::{
+    PrintEvent, StxTransferEvent, PRINT_EVENT_TOPIC, PRINT_EVENT_TYPE, STX_TRANSFER_EVENT_TYPE,
+};
+
+/// How Clarity buffer values are rendered in the JS output.
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub enum BufferEncoding {
+    /// `0x` prefixed hex string
+    #[default]
+    Hex,
+    /// Standard padded base64 string
+    Base64,
+    /// Node `Buffer` holding the raw bytes
+    Bytes,
+}
+
+impl TryFrom<&str> for BufferEncoding {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "hex" => Ok(BufferEncoding::Hex),
+            "base64" => Ok(BufferEncoding::Base64),
+            _ => Err(format!(
+                "Invalid buffer encoding \"{}\", expected \"hex\" or \"base64\"",
+                value
+            )),
+        }
+    }
+}
+
+impl BufferEncoding {
+    /// Encode to a string, or `None` for encodings that aren't strings.
+    pub fn encode(&self, data: &[u8]) -> Option<String> {
+        match self {
+            BufferEncoding::Hex => Some(encode_hex(data).into()),
+            BufferEncoding::Base64 => Some(encode_base64(data)),
+            BufferEncoding::Bytes => None,
+        }
+    }
+
+    pub fn to_js_value<'a, C: Context<'a>>(
+        &self,
+        cx: &mut C,
+        data: &[u8],
+    ) -> JsResult<'a, JsValue> {
+        match self.encode(data) {
+            Some(encoded) => Ok(cx.string(encoded).upcast()),
+            None => Ok(JsBuffer::external(cx, data.to_vec()).upcast()),
+        }
+    }
+}
 
 pub fn decode_clarity_val<T: AsRef<[u8]>>(
     cx: &mut FunctionContext,
@@ -10,13 +66,27 @@ pub fn decode_clarity_val<T: AsRef<[u8]>>(
     deep: bool,
     bytes: T,
 ) -> NeonResult<()> {
+    decode_clarity_val_with_encoding(cx, cur_obj, val, deep, bytes, BufferEncoding::Hex)
+}
+
+pub fn decode_clarity_val_with_encoding<T: AsRef<[u8]>>(
+    cx: &mut FunctionContext,
+    cur_obj: &Handle<JsObject>,
+    val: &clarity_value::types::ClarityValue,
+    deep: bool,
+    bytes: T,
+    buffer_encoding: BufferEncoding,
+) -> NeonResult<()> {
+    if let clarity_value::types::Value::Truncated(inner) = &val.value {
+        return decode_clarity_val_with_encoding(cx, cur_obj, inner, deep, bytes, buffer_encoding);
+    }
     let repr_string = cx.string(val.value.repr_string());
     cur_obj.set(cx, "repr", repr_string)?;
 
     let hex = cx.string(encode_hex(bytes.as_ref()));
     cur_obj.set(cx, "hex", hex)?;
 
-    let type_id = cx.number(val.value.type_prefix().to_u8());
+    let type_id = cx.number(val.value.type_id());
     cur_obj.set(cx, "type_id", type_id)?;
 
     if deep {
@@ -35,19 +105,20 @@ pub fn decode_clarity_val<T: AsRef<[u8]>>(
                 cur_obj.set(cx, "value", val_boolean)?;
             }
             Buffer(buff) => {
-                let obj_buffer = cx.string(encode_hex(buff));
+                let obj_buffer = buffer_encoding.to_js_value(cx, buff)?;
                 cur_obj.set(cx, "buffer", obj_buffer)?;
             }
             List(data) => {
                 let list_obj = JsArray::new(cx, data.len() as u32);
                 for (i, x) in data.iter().enumerate() {
                     let item_obj = cx.empty_object();
-                    decode_clarity_val(
+                    decode_clarity_val_with_encoding(
                         cx,
                         &item_obj,
                         x,
                         deep,
                         x.serialized_bytes.as_ref().unwrap(),
+                        buffer_encoding,
                     )?;
                     list_obj.set(cx, i as u32, item_obj)?;
                 }
@@ -101,12 +172,13 @@ pub fn decode_clarity_val<T: AsRef<[u8]>>(
                 let tuple_obj = cx.empty_object();
                 for (key, value) in val.iter() {
                     let val_obj = cx.empty_object();
-                    decode_clarity_val(
+                    decode_clarity_val_with_encoding(
                         cx,
                         &val_obj,
                         value,
                         deep,
                         value.serialized_bytes.as_ref().unwrap(),
+                        buffer_encoding,
                     )?;
                     tuple_obj.set(cx, key.as_str(), val_obj)?;
                 }
@@ -114,12 +186,13 @@ pub fn decode_clarity_val<T: AsRef<[u8]>>(
             }
             OptionalSome(data) => {
                 let option_obj = cx.empty_object();
-                decode_clarity_val(
+                decode_clarity_val_with_encoding(
                     cx,
                     &option_obj,
                     data,
                     deep,
                     data.serialized_bytes.as_ref().unwrap(),
+                    buffer_encoding,
                 )?;
                 cur_obj.set(cx, "value", option_obj)?;
             }
@@ -129,16 +202,112 @@ pub fn decode_clarity_val<T: AsRef<[u8]>>(
             }
             ResponseOk(val) | ResponseErr(val) => {
                 let response_obj = cx.empty_object();
-                decode_clarity_val(
+                decode_clarity_val_with_encoding(
                     cx,
                     &response_obj,
                     &val,
                     deep,
                     val.serialized_bytes.as_ref().unwrap(),
+                    buffer_encoding,
                 )?;
                 cur_obj.set(cx, "value", response_obj)?;
             }
+            Unknown { raw, .. } => {
+                let obj_raw = cx.string(encode_hex(raw));
+                cur_obj.set(cx, "raw", obj_raw)?;
+            }
+            Truncated(_) => unreachable!(),
         };
     }
     Ok(())
 }
+
+impl NeonJsSerialize for PrintEvent {
+    fn neon_js_serialize(
+        &self,
+        cx: &mut FunctionContext,
+        obj: &Handle<JsObject>,
+        _extra_ctx: &(),
+    ) -> NeonResult<()> {
+        let event_type = cx.string(PRINT_EVENT_TYPE);
+        obj.set(cx, "type", event_type)?;
+
+        let contract_event_obj = cx.empty_object();
+
+        let contract_identifier_str = self
+            .contract_identifier_string()
+            .or_else(|e| cx.throw_error(format!("Error converting to C32 address: {}", e)))?;
+        let contract_identifier = cx.string(contract_identifier_str);
+        contract_event_obj.set(cx, "contract_identifier", contract_identifier)?;
+
+        let topic = cx.string(PRINT_EVENT_TOPIC);
+        contract_event_obj.set(cx, "topic", topic)?;
+
+        let value_bytes = self.value.serialized_bytes.as_ref().unwrap();
+        let value_obj = cx.empty_object();
+        decode_clarity_val(cx, &value_obj, &self.value, true, value_bytes)?;
+        contract_event_obj.set(cx, "value", value_obj)?;
+
+        let raw_value = cx.string(encode_hex(value_bytes));
+        contract_event_obj.set(cx, "raw_value", raw_value)?;
+
+        obj.set(cx, "contract_event", contract_event_obj)?;
+        Ok(())
+    }
+}
+
+impl NeonJsSerialize for StxTransferEvent {
+    fn neon_js_serialize(
+        &self,
+        cx: &mut FunctionContext,
+        obj: &Handle<JsObject>,
+        _extra_ctx: &(),
+    ) -> NeonResult<()> {
+        let event_type = cx.string(STX_TRANSFER_EVENT_TYPE);
+        obj.set(cx, "type", event_type)?;
+
+        let transfer_event_obj = cx.empty_object();
+
+        let sender = cx.string(&self.sender);
+        transfer_event_obj.set(cx, "sender", sender)?;
+
+        let recipient = cx.string(&self.recipient);
+        transfer_event_obj.set(cx, "recipient", recipient)?;
+
+        let amount = cx.string(self.amount.to_string());
+        transfer_event_obj.set(cx, "amount", amount)?;
+
+        if let Some(ref memo) = self.memo {
+            let memo = cx.string(memo);
+            transfer_event_obj.set(cx, "memo", memo)?;
+        }
+
+        obj.set(cx, STX_TRANSFER_EVENT_TYPE, transfer_event_obj)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_encoding() {
+        let buff = b"hello world";
+        assert_eq!(
+            BufferEncoding::Hex.encode(buff).unwrap(),
+            "0x68656c6c6f20776f726c64"
+        );
+        assert_eq!(
+            BufferEncoding::Base64.encode(buff).unwrap(),
+            "aGVsbG8gd29ybGQ="
+        );
+        assert_eq!(BufferEncoding::Bytes.encode(buff), None);
+        assert_eq!(BufferEncoding::default(), BufferEncoding::Hex);
+        assert_eq!(
+            BufferEncoding::try_from("base64"),
+            Ok(BufferEncoding::Base64)
+        );
+        assert!(BufferEncoding::try_from("utf8").is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use std::convert::TryFrom;
+use std::io::Cursor;
+
+use crate::address::c32::{c32_address, c32_address_decode};
+use crate::hex::decode_hex;
+use crate::memo::memo_normalize;
+use crate::serialize_util::DeserializeError;
+
+use super::types::{
+    is_valid_contract_name, ClarityName, ClarityValue, ContractName, QualifiedContractIdentifier,
+    StandardPrincipalData,
+};
+
+/// Event `type` used by the stacks-node event observer for contract `print` events.
+pub const PRINT_EVENT_TYPE: &str = "contract_event";
+/// Event `topic` used by the stacks-node event observer for contract `print` events.
+pub const PRINT_EVENT_TOPIC: &str = "print";
+
+/// Event `type` used by the stacks-node event observer for STX transfers, including the ones
+/// made by contracts with `stx-transfer?` and `stx-transfer-memo?`.
+pub const STX_TRANSFER_EVENT_TYPE: &str = "stx_transfer_event";
+
+/// A contract `print` event, shaped like the stacks-node event observer payload:
+/// `{ type, contract_event: { contract_identifier, topic, value, raw_value } }`
+pub struct PrintEvent {
+    pub contract_identifier: QualifiedContractIdentifier,
+    pub value: ClarityValue,
+}
+
+impl PrintEvent {
+    pub fn contract_identifier_string(&self) -> Result<String, String> {
+        let issuer = &self.contract_identifier.issuer;
+        let address = c32_address(issuer.0, &issuer.1)?;
+        Ok(format!("{}.{}", address, self.contract_identifier.name))
+    }
+}
+
+pub fn parse_contract_identifier(
+    contract_id: &str,
+) -> Result<QualifiedContractIdentifier, DeserializeError> {
+    let (address, name) = contract_id
+        .split_once('.')
+        .ok_or_else(|| format!("Invalid contract identifier: {}", contract_id))?;
+    let (version, hash160) = c32_address_decode(address)
+        .map_err(|e| format!("Invalid contract identifier address {}: {}", address, e))?;
+    if !is_valid_contract_name(name) {
+        Err(format!("Invalid contract identifier name: {}", name))?;
+    }
+    let name = ContractName::try_from(name.to_string())?;
+    Ok(QualifiedContractIdentifier {
+        issuer: StandardPrincipalData(version, hash160),
+        name: ClarityName(name.into()),
+    })
+}
+
+/// Decode the serialized Clarity value of a contract `print` event emitted by `contract_id`.
+pub fn decode_print_event(bytes: &[u8], contract_id: &str) -> Result<PrintEvent, DeserializeError> {
+    let contract_identifier = parse_contract_identifier(contract_id)?;
+    let mut cursor = Cursor::new(bytes);
+    let value = ClarityValue::deserialize(&mut cursor, true)?;
+    if cursor.position() != bytes.len() as u64 {
+        Err(format!(
+            "Unexpected trailing bytes after print event value: {}",
+            bytes.len() as u64 - cursor.position()
+        ))?;
+    }
+    Ok(PrintEvent {
+        contract_identifier,
+        value,
+    })
+}
+
+/// An STX transfer event, shaped like the stacks-node event observer payload:
+/// `{ type, stx_transfer_event: { sender, recipient, amount, memo } }`
+pub struct StxTransferEvent {
+    pub sender: String,
+    pub recipient: String,
+    pub amount: u128,
+    /// The memo given to `stx-transfer-memo?`, normalized like `memoToString`. `None` for
+    /// transfers without a memo, which the event observer reports as an empty string.
+    pub memo: Option<String>,
+}
+
+fn check_principal(principal: &str) -> Result<(), DeserializeError> {
+    if principal.contains('.') {
+        parse_contract_identifier(principal)?;
+    } else {
+        c32_address_decode(principal)
+            .map_err(|e| format!("Invalid principal {}: {}", principal, e))?;
+    }
+    Ok(())
+}
+
+/// Decode an STX transfer event as the stacks-node event observer posts it:
+/// `{ "type": "stx_transfer_event", "stx_transfer_event": { sender, recipient, amount, memo } }`.
+/// The node sends `memo` as hex without a `0x` prefix, and as an empty string, or not at all
+/// before Stacks 2.1, for transfers without a memo.
+pub fn decode_stx_transfer_event(event_json: &str) -> Result<StxTransferEvent, DeserializeError> {
+    let event: serde_json::Value = serde_json::from_str(event_json)
+        .map_err(|e| format!("Invalid STX transfer event JSON: {}", e))?;
+    if event["type"] != STX_TRANSFER_EVENT_TYPE {
+        Err(format!(
+            "Event type {} is not {}",
+            event["type"], STX_TRANSFER_EVENT_TYPE
+        ))?;
+    }
+    let data = &event[STX_TRANSFER_EVENT_TYPE];
+    let field = |name: &str| {
+        data[name]
+            .as_str()
+            .ok_or_else(|| format!("STX transfer event is missing {}", name))
+    };
+    let sender = field("sender")?;
+    let recipient = field("recipient")?;
+    let amount = field("amount")?;
+    check_principal(sender)?;
+    check_principal(recipient)?;
+    let amount = amount
+        .parse::<u128>()
+        .map_err(|_| format!("Invalid STX transfer amount: {}", amount))?;
+    let memo_hex = data["memo"].as_str().unwrap_or("");
+    let memo = decode_hex(memo_hex)
+        .map_err(|e| format!("Invalid STX transfer memo {}: {}", memo_hex, e))?;
+    let memo = if memo.is_empty() {
+        None
+    } else {
+        Some(memo_normalize(memo))
+    };
+    Ok(StxTransferEvent {
+        sender: sender.to_string(),
+        recipient: recipient.to_string(),
+        amount,
+        memo,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::decode_hex;
+
+    #[test]
+    fn test_decode_print_event() {
+        let bytes = decode_hex("0x0d0000000568656c6c6f").unwrap();
+        let event = decode_print_event(
+            &bytes,
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract",
+        )
+        .unwrap();
+        assert_eq!(
+            event.contract_identifier_string().unwrap(),
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract"
+        );
+        assert_eq!(event.value.value.repr_string(), "\"hello\"");
+        assert_eq!(event.value.serialized_bytes.unwrap(), bytes.as_ref());
+    }
+
+    #[test]
+    fn test_decode_print_event_invalid() {
+        let bytes = decode_hex("0x0d0000000568656c6c6f").unwrap();
+        assert!(decode_print_event(&bytes, "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").is_err());
+        assert!(decode_print_event(&bytes, "not-an-address.my-contract").is_err());
+        assert!(decode_print_event(
+            &bytes,
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.1-bad-name"
+        )
+        .is_err());
+
+        let trailing = decode_hex("0x0d0000000568656c6c6f00").unwrap();
+        assert!(decode_print_event(
+            &trailing,
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_decode_stx_transfer_event() {
+        // (stx-transfer-memo? u1000 tx-sender 'SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE
+        //   0x68656c6c6f20776f726c640000000000) called by a contract
+        let event_json = r#"{
+            "event_index": 1,
+            "committed": true,
+            "type": "stx_transfer_event",
+            "stx_transfer_event": {
+                "sender": "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract",
+                "recipient": "SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE",
+                "amount": "1000",
+                "memo": "68656c6c6f20776f726c640000000000"
+            }
+        }"#;
+        let event = decode_stx_transfer_event(event_json).unwrap();
+        assert_eq!(
+            event.sender,
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract"
+        );
+        assert_eq!(event.recipient, "SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE");
+        assert_eq!(event.amount, 1000);
+        assert_eq!(event.memo.as_deref(), Some("hello world"));
+
+        // stx-transfer? events have an empty memo, and pre 2.1 nodes don't send one
+        let no_memo = |memo: &str| {
+            format!(
+                r#"{{"type":"stx_transfer_event","stx_transfer_event":{{"sender":"SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7","recipient":"SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE","amount":"1"{}}}}}"#,
+                memo
+            )
+        };
+        assert!(decode_stx_transfer_event(&no_memo(r#","memo":"""#))
+            .unwrap()
+            .memo
+            .is_none());
+        assert!(decode_stx_transfer_event(&no_memo(""))
+            .unwrap()
+            .memo
+            .is_none());
+        assert!(decode_stx_transfer_event(&no_memo(r#","memo":"zz""#)).is_err());
+
+        let invalid = [
+            "not json",
+            r#"{"type":"contract_event","stx_transfer_event":{}}"#,
+            r#"{"type":"stx_transfer_event","stx_transfer_event":{"sender":"SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7","amount":"1"}}"#,
+            r#"{"type":"stx_transfer_event","stx_transfer_event":{"sender":"SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7","recipient":"not-an-address","amount":"1"}}"#,
+            r#"{"type":"stx_transfer_event","stx_transfer_event":{"sender":"SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7","recipient":"SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE","amount":"-1"}}"#,
+        ];
+        for event_json in invalid.iter() {
+            assert!(
+                decode_stx_transfer_event(event_json).is_err(),
+                "{}",
+                event_json
+            );
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use std::io::Write;
+
+use super::types::*;
+
+impl StandardPrincipalData {
+    pub fn serialize_write<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        w.write_all(&[self.0])?;
+        w.write_all(&self.1)
+    }
+}
+
+impl ClarityName {
+    pub fn serialize_write<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        w.write_all(&[self.len() as u8])?;
+        w.write_all(self.as_bytes())
+    }
+}
+
+impl ClarityValue {
+    /// Consensus serialize the value. This is re-encoded from the decoded value, the original
+    /// `serialized_bytes` are only used for values that end in a captured `Unknown` value, whose
+    /// lists and tuples may be shorter than their declared length.
+    pub fn serialize_to_vec(&self) -> Vec<u8> {
+        let mut w = Vec::new();
+        self.serialize_write(&mut w).unwrap();
+        w
+    }
+
+    fn serialize_write<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        match &self.serialized_bytes {
+            Some(bytes) if self.value.ends_in_capture() => w.write_all(bytes),
+            _ => self.value.serialize_write(w),
+        }
+    }
+}
+
+impl Value {
+    pub fn serialize_write<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        use super::types::Value::*;
+
+        if let Truncated(inner) = self {
+            return inner.serialize_write(w);
+        }
+        w.write_all(&[self.type_id()])?;
+        match self {
+            Int(value) => w.write_all(&value.to_be_bytes())?,
+            UInt(value) => w.write_all(&value.to_be_bytes())?,
+            Bool(_) | OptionalNone => {}
+            Buffer(data) | StringASCII(data) => {
+                w.write_all(&(data.len() as u32).to_be_bytes())?;
+                w.write_all(data)?;
+            }
+            StringUTF8(data) => {
+                let total_len: usize = data.iter().map(|c| c.len()).sum();
+                w.write_all(&(total_len as u32).to_be_bytes())?;
+                for c in data.iter() {
+                    w.write_all(c)?;
+                }
+            }
+            PrincipalStandard(principal) => principal.serialize_write(w)?,
+            PrincipalContract(contract_identifier) => {
+                contract_identifier.issuer.serialize_write(w)?;
+                contract_identifier.name.serialize_write(w)?;
+            }
+            OptionalSome(value) | ResponseOk(value) | ResponseErr(value) => {
+                value.serialize_write(w)?;
+            }
+            List(items) => {
+                w.write_all(&(items.len() as u32).to_be_bytes())?;
+                for item in items.iter() {
+                    item.serialize_write(w)?;
+                }
+            }
+            Tuple(data) => {
+                // written in stored order, which is name order unless decoded from non-canonical bytes
+                w.write_all(&(data.len() as u32).to_be_bytes())?;
+                for (name, value) in data.iter() {
+                    name.serialize_write(w)?;
+                    value.serialize_write(w)?;
+                }
+            }
+            Unknown { raw, .. } => w.write_all(raw)?,
+            Truncated(_) => unreachable!(),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::decode_hex;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_serialize_roundtrip() {
+        let inputs = [
+            "0x0000000000000000000000000000000005",
+            "0x0100000000000000000000000000000001",
+            "0x03",
+            "0x04",
+            "0x0200000003010203",
+            "0x0d0000000568656c6c6f",
+            "0x0e00000004f09f9880",
+            "0x0516a46ff88886c2ef9762d970b4d2c63678835bd39d",
+            "0x0616a46ff88886c2ef9762d970b4d2c63678835bd39d0b6d792d636f6e7472616374",
+            "0x09",
+            "0x0a0100000000000000000000000000000001",
+            "0x070b000000020c0000000201610200000002010201620a01000000000000000000000000000000010c000000020161020000000103016209",
+            "0x080100000000000000000000000000000001",
+        ];
+        for input in inputs.iter() {
+            let bytes = decode_hex(input).unwrap();
+            let mut cursor = Cursor::new(bytes.as_ref());
+            let val = ClarityValue::deserialize(&mut cursor, false).unwrap();
+            assert_eq!(val.serialize_to_vec(), bytes.as_ref(), "{}", input);
+        }
+    }
+}
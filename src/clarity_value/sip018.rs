@@ -0,0 +1,86 @@
+use sha2::{Digest, Sha256};
+
+use super::types::{ClarityValue, Value};
+
+/// `"SIP018"` as bytes, prepended to the structured data before hashing.
+pub const SIP018_MESSAGE_PREFIX: [u8; 6] = *b"SIP018";
+
+/// Compute the SIP-018 structured data hash that gets signed:
+/// `sha256(SIP018_MESSAGE_PREFIX || sha256(domain) || sha256(message))`, using the consensus
+/// serialization of both values.
+///
+/// The domain must be a `(tuple (name (string-ascii)) (version (string-ascii)) (chain-id uint))`.
+pub fn sip018_hash(domain: &ClarityValue, message: &ClarityValue) -> Result<[u8; 32], String> {
+    validate_domain(domain)?;
+    let domain_hash = Sha256::digest(domain.serialize_to_vec());
+    let message_hash = Sha256::digest(message.serialize_to_vec());
+    let hash = Sha256::new()
+        .chain_update(SIP018_MESSAGE_PREFIX)
+        .chain_update(domain_hash)
+        .chain_update(message_hash)
+        .finalize();
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&hash);
+    Ok(result)
+}
+
+fn validate_domain(domain: &ClarityValue) -> Result<(), String> {
+    let tuple = match &domain.value {
+        Value::Tuple(tuple) => tuple,
+        _ => return Err("SIP-018 domain must be a tuple".to_string()),
+    };
+    let valid = tuple.len() == 3
+        && matches!(
+            tuple.get("name").map(|v| &v.value),
+            Some(Value::StringASCII(_))
+        )
+        && matches!(
+            tuple.get("version").map(|v| &v.value),
+            Some(Value::StringASCII(_))
+        )
+        && matches!(
+            tuple.get("chain-id").map(|v| &v.value),
+            Some(Value::UInt(_))
+        );
+    if valid {
+        Ok(())
+    } else {
+        Err("SIP-018 domain must be a tuple of name, version and chain-id".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::{decode_hex, encode_hex};
+    use std::io::Cursor;
+
+    fn deserialize_hex(input: &str) -> ClarityValue {
+        let bytes = decode_hex(input).unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        ClarityValue::deserialize(&mut cursor, false).unwrap()
+    }
+
+    #[test]
+    fn test_sip018_hash() {
+        // SIP-018 test vector: domain `{ name: "Test App", version: "1.0.0", chain-id: u1 }`
+        // and message `"Hello World"`
+        let domain = deserialize_hex("0x0c0000000308636861696e2d69640100000000000000000000000000000001046e616d650d0000000854657374204170700776657273696f6e0d00000005312e302e30");
+        let message = deserialize_hex("0x0d0000000b48656c6c6f20576f726c64");
+        let hash = sip018_hash(&domain, &message).unwrap();
+        assert_eq!(
+            encode_hex(&hash).to_string(),
+            "0x1bfdab6d4158313ce34073fbb8d6b0fc32c154d439def12247a0f44bb2225259"
+        );
+    }
+
+    #[test]
+    fn test_sip018_invalid_domain() {
+        let message = deserialize_hex("0x0d0000000b48656c6c6f20576f726c64");
+        assert!(sip018_hash(&message, &message).is_err());
+
+        // chain-id as an int instead of uint
+        let domain = deserialize_hex("0x0c0000000308636861696e2d69640000000000000000000000000000000001046e616d650d0000000854657374204170700776657273696f6e0d00000005312e302e30");
+        assert!(sip018_hash(&domain, &message).is_err());
+    }
+}
@@ -0,0 +1,403 @@
+use std::convert::TryFrom;
+
+use crate::address::c32::c32_address;
+use crate::hex::encode_hex;
+
+use super::types::{ClarityValue, Value};
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum StackingFunction {
+    StackStx,
+    DelegateStx,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct PoxAddress {
+    pub version: u8,
+    pub hashbytes: Vec<u8>,
+}
+
+/// Length of a compressed secp256k1 public key.
+pub const SIGNER_KEY_LENGTH: usize = 33;
+
+/// A Nakamoto signer's compressed secp256k1 public key, as registered through the PoX-4
+/// `signer-key (buff 33)` argument.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct SignerKey(pub [u8; SIGNER_KEY_LENGTH]);
+
+impl SignerKey {
+    pub fn to_hex(&self) -> String {
+        encode_hex(&self.0).into()
+    }
+}
+
+impl TryFrom<&[u8]> for SignerKey {
+    type Error = String;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if value.len() != SIGNER_KEY_LENGTH {
+            return Err(format!(
+                "Invalid signer key length {}, expected {}",
+                value.len(),
+                SIGNER_KEY_LENGTH
+            ));
+        }
+        if value[0] != 0x02 && value[0] != 0x03 {
+            return Err(format!(
+                "Invalid signer key prefix 0x{:02x}, expected a compressed public key",
+                value[0]
+            ));
+        }
+        let mut key = [0u8; SIGNER_KEY_LENGTH];
+        key.copy_from_slice(value);
+        Ok(SignerKey(key))
+    }
+}
+
+/// Validate a Clarity `(buff 33)` signer key argument.
+pub fn decode_signer_key(val: &ClarityValue) -> Result<SignerKey, String> {
+    match &val.value {
+        Value::Buffer(buff) => SignerKey::try_from(buff.as_slice()),
+        _ => Err("Invalid signer key, expected a buffer".to_string()),
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct StackingInfo {
+    pub function: StackingFunction,
+    pub amount_ustx: u128,
+    /// Always set for `stack-stx`, optional for `delegate-stx`.
+    pub pox_address: Option<PoxAddress>,
+    /// Number of reward cycles, only set for `stack-stx`.
+    pub lock_period: Option<u128>,
+    /// Only set for the PoX-4 `stack-stx`.
+    pub signer_key: Option<SignerKey>,
+}
+
+/// Extract the stacking details from the arguments of a PoX `stack-stx` or `delegate-stx`
+/// contract-call. Returns `None` for other functions or if the arguments don't match the
+/// expected PoX signature.
+///
+/// * `stack-stx (amount-ustx uint) (pox-addr (tuple (version (buff 1)) (hashbytes (buff 32))))
+///   (start-burn-ht uint) (lock-period uint)`
+/// * PoX-4 `stack-stx`, which adds `(signer-sig (optional (buff 65))) (signer-key (buff 33))
+///   (max-amount uint) (auth-id uint)`
+/// * `delegate-stx (amount-ustx uint) (delegate-to principal) (until-burn-ht (optional uint))
+///   (pox-addr (optional (tuple (version (buff 1)) (hashbytes (buff 32)))))`
+pub fn decode_stacking_args(function_name: &str, args: &[ClarityValue]) -> Option<StackingInfo> {
+    match function_name {
+        "stack-stx" => {
+            let signer_key = match args.len() {
+                4 => None,
+                8 => Some(decode_signer_key(&args[5]).ok()?),
+                _ => return None,
+            };
+            Some(StackingInfo {
+                function: StackingFunction::StackStx,
+                amount_ustx: as_uint(&args[0])?,
+                pox_address: Some(as_pox_address(&args[1])?),
+                lock_period: Some(as_uint(&args[3])?),
+                signer_key,
+            })
+        }
+        "delegate-stx" => {
+            if args.len() != 4 {
+                return None;
+            }
+            let pox_address = match &args[3].value {
+                Value::OptionalSome(val) => Some(as_pox_address(val)?),
+                Value::OptionalNone => None,
+                _ => return None,
+            };
+            Some(StackingInfo {
+                function: StackingFunction::DelegateStx,
+                amount_ustx: as_uint(&args[0])?,
+                pox_address,
+                lock_period: None,
+                signer_key: None,
+            })
+        }
+        _ => None,
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct DelegateInfo {
+    pub amount_ustx: u128,
+    /// Standard or contract principal, e.g. `SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.pool`.
+    pub delegate_to: String,
+    pub until_burn_height: Option<u128>,
+    pub pox_address: Option<PoxAddress>,
+}
+
+/// Destructure the arguments of a PoX `delegate-stx` contract-call, `(amount-ustx uint)
+/// (delegate-to principal) (until-burn-ht (optional uint)) (pox-addr (optional (tuple (version
+/// (buff 1)) (hashbytes (buff 32)))))`.
+pub fn decode_delegate_stx(args: &[ClarityValue]) -> Result<DelegateInfo, String> {
+    if args.len() != 4 {
+        return Err(format!(
+            "Invalid delegate-stx args, expected 4 arguments, got {}",
+            args.len()
+        ));
+    }
+    let invalid_arg = |name: &str, val: &ClarityValue| {
+        format!(
+            "Invalid delegate-stx {} argument: {}",
+            name,
+            val.value.type_signature()
+        )
+    };
+    let amount_ustx = as_uint(&args[0]).ok_or_else(|| invalid_arg("amount-ustx", &args[0]))?;
+    let delegate_to = match &args[1].value {
+        Value::PrincipalStandard(principal) => c32_address(principal.0, &principal.1)?,
+        Value::PrincipalContract(contract) => format!(
+            "{}.{}",
+            c32_address(contract.issuer.0, &contract.issuer.1)?,
+            contract.name
+        ),
+        _ => return Err(invalid_arg("delegate-to", &args[1])),
+    };
+    let until_burn_height = match &args[2].value {
+        Value::OptionalSome(val) => {
+            Some(as_uint(val).ok_or_else(|| invalid_arg("until-burn-ht", &args[2]))?)
+        }
+        Value::OptionalNone => None,
+        _ => return Err(invalid_arg("until-burn-ht", &args[2])),
+    };
+    let pox_address = match &args[3].value {
+        Value::OptionalSome(val) => {
+            Some(as_pox_address(val).ok_or_else(|| invalid_arg("pox-addr", &args[3]))?)
+        }
+        Value::OptionalNone => None,
+        _ => return Err(invalid_arg("pox-addr", &args[3])),
+    };
+    Ok(DelegateInfo {
+        amount_ustx,
+        delegate_to,
+        until_burn_height,
+        pox_address,
+    })
+}
+
+fn as_uint(val: &ClarityValue) -> Option<u128> {
+    match val.value {
+        Value::UInt(n) => Some(n),
+        _ => None,
+    }
+}
+
+fn as_pox_address(val: &ClarityValue) -> Option<PoxAddress> {
+    let tuple = match &val.value {
+        Value::Tuple(tuple) => tuple,
+        _ => return None,
+    };
+    let version = match &tuple.get("version")?.value {
+        Value::Buffer(buff) if buff.len() == 1 => buff[0],
+        _ => return None,
+    };
+    let hashbytes = match &tuple.get("hashbytes")?.value {
+        Value::Buffer(buff) => buff.clone(),
+        _ => return None,
+    };
+    Some(PoxAddress { version, hashbytes })
+}
+
+/// Bitcoin block height of the first mainnet PoX reward cycle.
+pub const MAINNET_FIRST_BURNCHAIN_BLOCK_HEIGHT: u64 = 666050;
+/// Number of bitcoin blocks in a mainnet PoX reward cycle.
+pub const MAINNET_REWARD_CYCLE_LENGTH: u64 = 2100;
+
+/// Reward cycle that contains the given bitcoin block height. Heights before
+/// `first_cycle_height` are counted as cycle 0. `cycle_length` must be non-zero.
+pub fn burn_height_to_reward_cycle(height: u64, first_cycle_height: u64, cycle_length: u64) -> u64 {
+    height.saturating_sub(first_cycle_height) / cycle_length
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clarity_value::types::{
+        ClarityName, QualifiedContractIdentifier, StandardPrincipalData, TupleData,
+    };
+    use crate::hex::decode_hex;
+
+    fn pox_addr_tuple() -> ClarityValue {
+        let mut tuple = TupleData::new();
+        tuple.insert(
+            ClarityName::from("version"),
+            ClarityValue::new(Value::Buffer(vec![0x01])),
+        );
+        tuple.insert(
+            ClarityName::from("hashbytes"),
+            ClarityValue::new(Value::Buffer(vec![0xab; 20])),
+        );
+        ClarityValue::new(Value::Tuple(tuple))
+    }
+
+    #[test]
+    fn test_decode_stack_stx() {
+        let args = vec![
+            ClarityValue::new(Value::UInt(125_000_000_000)),
+            pox_addr_tuple(),
+            ClarityValue::new(Value::UInt(700_000)),
+            ClarityValue::new(Value::UInt(6)),
+        ];
+        let info = decode_stacking_args("stack-stx", &args).unwrap();
+        assert_eq!(
+            info,
+            StackingInfo {
+                function: StackingFunction::StackStx,
+                amount_ustx: 125_000_000_000,
+                pox_address: Some(PoxAddress {
+                    version: 0x01,
+                    hashbytes: vec![0xab; 20],
+                }),
+                lock_period: Some(6),
+                signer_key: None,
+            }
+        );
+
+        // wrong arg types
+        let bad_args = vec![
+            ClarityValue::new(Value::Int(125_000_000_000)),
+            pox_addr_tuple(),
+            ClarityValue::new(Value::UInt(700_000)),
+            ClarityValue::new(Value::UInt(6)),
+        ];
+        assert!(decode_stacking_args("stack-stx", &bad_args).is_none());
+        assert!(decode_stacking_args("stack-stx", &args[..3]).is_none());
+    }
+
+    #[test]
+    fn test_decode_pox4_stack_stx_signer_key() {
+        let signer_key_hex = "0x02778d476704afa540ac01437abde0e8ca5a0ec2a1ea8a5e7c8f6c6fb8dfb0e7d2";
+        let signer_key = decode_hex(signer_key_hex).unwrap();
+        let args = vec![
+            ClarityValue::new(Value::UInt(125_000_000_000)),
+            pox_addr_tuple(),
+            ClarityValue::new(Value::UInt(700_000)),
+            ClarityValue::new(Value::UInt(6)),
+            ClarityValue::new(Value::OptionalNone),
+            ClarityValue::new(Value::Buffer(signer_key.to_vec())),
+            ClarityValue::new(Value::UInt(125_000_000_000)),
+            ClarityValue::new(Value::UInt(1)),
+        ];
+        let info = decode_stacking_args("stack-stx", &args).unwrap();
+        assert_eq!(info.lock_period, Some(6));
+        assert_eq!(info.signer_key.unwrap().to_hex(), signer_key_hex);
+
+        // uncompressed key prefix
+        let mut bad_key = signer_key.to_vec();
+        bad_key[0] = 0x04;
+        let val = ClarityValue::new(Value::Buffer(bad_key));
+        assert!(decode_signer_key(&val).is_err());
+
+        let val = ClarityValue::new(Value::Buffer(signer_key[..32].to_vec()));
+        assert!(decode_signer_key(&val).is_err());
+
+        let val = ClarityValue::new(Value::UInt(1));
+        assert!(decode_signer_key(&val).is_err());
+    }
+
+    #[test]
+    fn test_decode_delegate_stx() {
+        let args = vec![
+            ClarityValue::new(Value::UInt(50_000_000)),
+            ClarityValue::new(Value::PrincipalStandard(StandardPrincipalData(
+                22, [0xcd; 20],
+            ))),
+            ClarityValue::new(Value::OptionalNone),
+            ClarityValue::new(Value::OptionalNone),
+        ];
+        let info = decode_stacking_args("delegate-stx", &args).unwrap();
+        assert_eq!(info.function, StackingFunction::DelegateStx);
+        assert_eq!(info.amount_ustx, 50_000_000);
+        assert_eq!(info.pox_address, None);
+        assert_eq!(info.lock_period, None);
+    }
+
+    #[test]
+    fn test_decode_delegate_stx_info() {
+        let pool = StandardPrincipalData(22, [0xcd; 20]);
+        let args = vec![
+            ClarityValue::new(Value::UInt(50_000_000)),
+            ClarityValue::new(Value::PrincipalStandard(pool.clone())),
+            ClarityValue::new(Value::OptionalNone),
+            ClarityValue::new(Value::OptionalNone),
+        ];
+        let pool_address = c32_address(22, &[0xcd; 20]).unwrap();
+        assert_eq!(
+            decode_delegate_stx(&args).unwrap(),
+            DelegateInfo {
+                amount_ustx: 50_000_000,
+                delegate_to: pool_address.clone(),
+                until_burn_height: None,
+                pox_address: None,
+            }
+        );
+
+        let args = vec![
+            ClarityValue::new(Value::UInt(50_000_000)),
+            ClarityValue::new(Value::PrincipalContract(QualifiedContractIdentifier {
+                issuer: pool,
+                name: ClarityName::from("pool-v1"),
+            })),
+            ClarityValue::new(Value::OptionalSome(Box::new(ClarityValue::new(
+                Value::UInt(800_000),
+            )))),
+            ClarityValue::new(Value::OptionalSome(Box::new(pox_addr_tuple()))),
+        ];
+        assert_eq!(
+            decode_delegate_stx(&args).unwrap(),
+            DelegateInfo {
+                amount_ustx: 50_000_000,
+                delegate_to: format!("{}.pool-v1", pool_address),
+                until_burn_height: Some(800_000),
+                pox_address: Some(PoxAddress {
+                    version: 0x01,
+                    hashbytes: vec![0xab; 20],
+                }),
+            }
+        );
+
+        // until-burn-ht must be an optional uint
+        let mut bad_args = args;
+        bad_args[2] = ClarityValue::new(Value::OptionalSome(Box::new(ClarityValue::new(
+            Value::Int(800_000),
+        ))));
+        assert_eq!(
+            decode_delegate_stx(&bad_args).unwrap_err(),
+            "Invalid delegate-stx until-burn-ht argument: (optional int)"
+        );
+        bad_args[2] = ClarityValue::new(Value::UInt(800_000));
+        assert!(decode_delegate_stx(&bad_args).is_err());
+        assert!(decode_delegate_stx(&bad_args[..3]).is_err());
+    }
+
+    #[test]
+    fn test_burn_height_to_reward_cycle() {
+        let cycle = |height| {
+            burn_height_to_reward_cycle(
+                height,
+                MAINNET_FIRST_BURNCHAIN_BLOCK_HEIGHT,
+                MAINNET_REWARD_CYCLE_LENGTH,
+            )
+        };
+        assert_eq!(cycle(0), 0);
+        assert_eq!(cycle(666049), 0);
+        assert_eq!(cycle(666050), 0);
+        assert_eq!(cycle(668149), 0);
+        assert_eq!(cycle(668150), 1);
+        assert_eq!(cycle(771049), 49);
+        assert_eq!(cycle(771050), 50);
+
+        assert_eq!(burn_height_to_reward_cycle(2000, 1000, 100), 10);
+        assert_eq!(burn_height_to_reward_cycle(1099, 1000, 100), 0);
+    }
+
+    #[test]
+    fn test_decode_unknown_function() {
+        let args = vec![ClarityValue::new(Value::UInt(1))];
+        assert!(decode_stacking_args("transfer", &args).is_none());
+    }
+}
@@ -0,0 +1,154 @@
+use byteorder::ReadBytesExt;
+use std::io::Cursor;
+
+use crate::serialize_util::{ensure_remaining, read_uint_be, DeserializeError};
+
+use super::deserialize::TypePrefix;
+use super::types::{MAX_STRING_LEN, MAX_VALUE_SIZE};
+
+const TYPE_PREFIX_COUNT: usize = TypePrefix::StringUTF8 as usize + 1;
+
+/// Summary of the shape of a serialized Clarity value.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ValueStats {
+    /// Number of values of each type, indexed by the `TypePrefix` byte.
+    pub type_counts: [u32; TYPE_PREFIX_COUNT],
+    pub total_values: u32,
+    /// Sum of the lengths of all buffer values.
+    pub total_buffer_bytes: u64,
+    /// Nesting depth of the deepest value, the root value is at depth 0.
+    pub max_depth: u8,
+}
+
+impl ValueStats {
+    pub fn count(&self, prefix: TypePrefix) -> u32 {
+        self.type_counts[prefix as usize]
+    }
+}
+
+/// Walk a serialized Clarity value and count its types, without building the decoded value.
+pub fn clarity_value_stats(bytes: &[u8]) -> Result<ValueStats, DeserializeError> {
+    let mut cursor = Cursor::new(bytes);
+    let mut stats = ValueStats::default();
+    walk_value(&mut cursor, 0, &mut stats)?;
+    Ok(stats)
+}
+
+/// Advance the cursor past one serialized Clarity value without decoding it.
+pub(crate) fn skip_value(r: &mut Cursor<&[u8]>) -> Result<(), DeserializeError> {
+    walk_value(r, 0, &mut ValueStats::default())
+}
+
+pub(crate) fn skip_bytes(r: &mut Cursor<&[u8]>, len: u64) -> Result<(), DeserializeError> {
+    ensure_remaining(r, len)?;
+    r.set_position(r.position() + len);
+    Ok(())
+}
+
+fn read_len(r: &mut Cursor<&[u8]>, type_name: &str) -> Result<u32, DeserializeError> {
+    let len = read_uint_be::<4>(r)? as u32;
+    if len > MAX_VALUE_SIZE {
+        return Err(format!("Illegal {} type size", type_name).into());
+    }
+    Ok(len)
+}
+
+fn skip_clarity_name(r: &mut Cursor<&[u8]>) -> Result<(), DeserializeError> {
+    let len_byte = r.read_u8()?;
+    if len_byte > MAX_STRING_LEN {
+        return Err(format!("Failed to deserialize clarity name: too long: {}", len_byte).into());
+    }
+    skip_bytes(r, len_byte as u64)
+}
+
+fn walk_value(
+    r: &mut Cursor<&[u8]>,
+    depth: u8,
+    stats: &mut ValueStats,
+) -> Result<(), DeserializeError> {
+    if depth >= 16 {
+        return Err(format!("TypeSignatureTooDeep: {}", depth).into());
+    }
+
+    let prefix = TypePrefix::from_u8(r.read_u8()?).ok_or("Bad type prefix")?;
+    stats.type_counts[prefix.to_u8() as usize] += 1;
+    stats.total_values += 1;
+    stats.max_depth = stats.max_depth.max(depth);
+
+    match prefix {
+        TypePrefix::Int | TypePrefix::UInt => skip_bytes(r, 16)?,
+        TypePrefix::BoolTrue | TypePrefix::BoolFalse | TypePrefix::OptionalNone => {}
+        TypePrefix::Buffer => {
+            let len = read_len(r, "buffer")?;
+            skip_bytes(r, len as u64)?;
+            stats.total_buffer_bytes += len as u64;
+        }
+        TypePrefix::StringASCII => {
+            let len = read_len(r, "string-ascii")?;
+            skip_bytes(r, len as u64)?;
+        }
+        TypePrefix::StringUTF8 => {
+            let len = read_len(r, "string-utf8")?;
+            skip_bytes(r, len as u64)?;
+        }
+        TypePrefix::PrincipalStandard => skip_bytes(r, 21)?,
+        TypePrefix::PrincipalContract => {
+            skip_bytes(r, 21)?;
+            skip_clarity_name(r)?;
+        }
+        TypePrefix::ResponseOk | TypePrefix::ResponseErr | TypePrefix::OptionalSome => {
+            walk_value(r, depth + 1, stats)?;
+        }
+        TypePrefix::List => {
+            let len = read_len(r, "list")?;
+            // every item is at least one byte
+            ensure_remaining(r, len as u64)?;
+            for _i in 0..len {
+                walk_value(r, depth + 1, stats)?;
+            }
+        }
+        TypePrefix::Tuple => {
+            let len = read_len(r, "tuple")?;
+            // every item is at least one byte
+            ensure_remaining(r, len as u64)?;
+            for _i in 0..len {
+                skip_clarity_name(r)?;
+                walk_value(r, depth + 1, stats)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::decode_hex;
+
+    #[test]
+    fn test_value_stats() {
+        // (ok (list (tuple (a 0x0102) (b (some u1))) (tuple (a 0x03) (b none))))
+        let input = "0x070b000000020c0000000201610200000002010201620a01000000000000000000000000000000010c000000020161020000000103016209";
+        let bytes = decode_hex(input).unwrap();
+        let stats = clarity_value_stats(&bytes).unwrap();
+
+        assert_eq!(stats.count(TypePrefix::ResponseOk), 1);
+        assert_eq!(stats.count(TypePrefix::List), 1);
+        assert_eq!(stats.count(TypePrefix::Tuple), 2);
+        assert_eq!(stats.count(TypePrefix::Buffer), 2);
+        assert_eq!(stats.count(TypePrefix::OptionalSome), 1);
+        assert_eq!(stats.count(TypePrefix::OptionalNone), 1);
+        assert_eq!(stats.count(TypePrefix::UInt), 1);
+        assert_eq!(stats.count(TypePrefix::Int), 0);
+        assert_eq!(stats.total_values, 9);
+        assert_eq!(stats.total_buffer_bytes, 3);
+        assert_eq!(stats.max_depth, 4);
+    }
+
+    #[test]
+    fn test_value_stats_invalid() {
+        let bytes = decode_hex("0x070b000000020c00000002016102").unwrap();
+        assert!(clarity_value_stats(&bytes).unwrap_err().is_unexpected_eof());
+        assert!(clarity_value_stats(&[0xff]).is_err());
+    }
+}
@@ -1,13 +1,14 @@
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::borrow::Borrow;
-use std::collections::BTreeMap;
+use std::cmp::Ordering;
 use std::convert::TryFrom;
 use std::fmt;
 use std::io::Write;
 use std::ops::Deref;
 
-use crate::address::c32::c32_address;
+use crate::address::c32::{c32_address, c32_address_decode};
+use crate::hash::hash160;
 use crate::hex::{encode_hex, encode_hex_no_prefix};
 
 use super::deserialize::TypePrefix;
@@ -19,6 +20,7 @@ pub const MAX_VALUE_SIZE: u32 = 1024 * 1024; // 1MB
 pub const CONTRACT_MIN_NAME_LENGTH: usize = 1;
 pub const CONTRACT_MAX_NAME_LENGTH: usize = 40;
 
+#[derive(Clone)]
 pub struct ClarityValue {
     pub serialized_bytes: Option<Vec<u8>>,
     pub value: Value,
@@ -38,8 +40,256 @@ impl ClarityValue {
             value,
         }
     }
+
+    pub fn int(value: i128) -> ClarityValue {
+        ClarityValue::new(Value::Int(value))
+    }
+
+    pub fn uint(value: u128) -> ClarityValue {
+        ClarityValue::new(Value::UInt(value))
+    }
+
+    pub fn bool(value: bool) -> ClarityValue {
+        ClarityValue::new(Value::Bool(value))
+    }
+
+    pub fn buffer(data: Vec<u8>) -> Result<ClarityValue, String> {
+        check_value_len("buff", data.len())?;
+        Ok(ClarityValue::new(Value::Buffer(data)))
+    }
+
+    pub fn string_ascii(value: &str) -> Result<ClarityValue, String> {
+        if !value.is_ascii() {
+            return Err(format!("Invalid string-ascii, not ASCII: {:?}", value));
+        }
+        check_value_len("string-ascii", value.len())?;
+        Ok(ClarityValue::new(Value::StringASCII(
+            value.as_bytes().to_vec(),
+        )))
+    }
+
+    pub fn string_utf8(value: &str) -> Result<ClarityValue, String> {
+        check_value_len("string-utf8", value.len())?;
+        Ok(ClarityValue::new(Value::string_utf8(
+            value.as_bytes().to_vec(),
+        )))
+    }
+
+    /// A standard principal from a c32 address, or a contract principal from `address.name`.
+    pub fn principal(principal: &str) -> Result<ClarityValue, String> {
+        let (address, name) = match principal.split_once('.') {
+            Some((address, name)) => (address, Some(name)),
+            None => (principal, None),
+        };
+        let (version, hash160) = c32_address_decode(address)?;
+        let issuer = StandardPrincipalData(version, hash160);
+        let value = match name {
+            Some(name) => {
+                if !is_valid_contract_name(name) {
+                    return Err(format!("Invalid contract name {:?}", name));
+                }
+                Value::PrincipalContract(QualifiedContractIdentifier {
+                    issuer,
+                    name: ClarityName(name.to_string()),
+                })
+            }
+            None => Value::PrincipalStandard(issuer),
+        };
+        Ok(ClarityValue::new(value))
+    }
+
+    pub fn none() -> ClarityValue {
+        ClarityValue::new(Value::OptionalNone)
+    }
+
+    pub fn some(inner: ClarityValue) -> ClarityValue {
+        ClarityValue::new(Value::OptionalSome(Box::new(inner)))
+    }
+
+    pub fn ok(inner: ClarityValue) -> ClarityValue {
+        ClarityValue::new(Value::ResponseOk(Box::new(inner)))
+    }
+
+    pub fn err(inner: ClarityValue) -> ClarityValue {
+        ClarityValue::new(Value::ResponseErr(Box::new(inner)))
+    }
+
+    /// A list of values of one type. As in Clarity, items only need a common supertype, e.g.
+    /// `(list (some u1) none)` and `(list 0x01 0x0203)` are valid, `(list u1 "a")` isn't.
+    pub fn list(items: Vec<ClarityValue>) -> Result<ClarityValue, String> {
+        check_value_len("list", items.len())?;
+        let mut entry_type: Option<ListEntryType> = None;
+        for (i, item) in items.iter().enumerate() {
+            let unified = ListEntryType::of(&item.value).and_then(|item_type| match entry_type {
+                Some(entry_type) => entry_type.unify(item_type),
+                None => Some(item_type),
+            });
+            entry_type = Some(unified.ok_or_else(|| {
+                format!(
+                    "Invalid list, item {} of type {} doesn't match the earlier items",
+                    i,
+                    item.value.type_signature()
+                )
+            })?);
+        }
+        Ok(ClarityValue::new(Value::List(items)))
+    }
+
+    /// A tuple with fields sorted by name, as Clarity serializes them. Field names must be valid
+    /// Clarity names and unique, and a tuple needs at least one field.
+    pub fn tuple(fields: Vec<(&str, ClarityValue)>) -> Result<ClarityValue, String> {
+        if fields.is_empty() {
+            return Err("Invalid tuple, no fields".to_string());
+        }
+        check_value_len("tuple", fields.len())?;
+        let mut data = TupleData::new();
+        for (name, value) in fields {
+            let name = ClarityName::try_from(name.to_string())?;
+            if data.get(&name).is_some() {
+                return Err(format!("Duplicate tuple field name {}", name));
+            }
+            data.insert(name, value);
+        }
+        Ok(ClarityValue::new(Value::Tuple(data)))
+    }
+}
+
+/// The type of a list entry. Lists may mix lengths, so lengths are the longest seen, as in the
+/// VM's least supertype. `None` parts are not known yet, e.g. the inner type of `none` or the err
+/// type of `(ok u1)`.
+#[derive(PartialEq)]
+enum ListEntryType {
+    Int,
+    UInt,
+    Bool,
+    Buffer(u64),
+    StringASCII(u64),
+    StringUTF8(u64),
+    Principal,
+    List(Option<Box<ListEntryType>>, u64),
+    Tuple(Vec<(String, ListEntryType)>),
+    Optional(Option<Box<ListEntryType>>),
+    Response(Option<Box<ListEntryType>>, Option<Box<ListEntryType>>),
+    Other(u8, u64),
 }
 
+impl ListEntryType {
+    /// `None` if the value itself holds a list with mismatched items.
+    fn of(value: &Value) -> Option<ListEntryType> {
+        let inner = |value: &ClarityValue| ListEntryType::of(&value.value).map(Box::new);
+        Some(match value {
+            Value::Truncated(inner) => return ListEntryType::of(&inner.value),
+            Value::Int(_) => ListEntryType::Int,
+            Value::UInt(_) => ListEntryType::UInt,
+            Value::Bool(_) => ListEntryType::Bool,
+            Value::Buffer(data) => ListEntryType::Buffer(data.len() as u64),
+            Value::StringASCII(data) => ListEntryType::StringASCII(data.len() as u64),
+            Value::StringUTF8(data) => ListEntryType::StringUTF8(data.len() as u64),
+            Value::PrincipalStandard(_) | Value::PrincipalContract(_) => ListEntryType::Principal,
+            Value::List(items) => {
+                let mut entry_type: Option<ListEntryType> = None;
+                for item in items {
+                    let item_type = ListEntryType::of(&item.value)?;
+                    entry_type = Some(match entry_type {
+                        Some(entry_type) => entry_type.unify(item_type)?,
+                        None => item_type,
+                    });
+                }
+                ListEntryType::List(entry_type.map(Box::new), items.len() as u64)
+            }
+            Value::Tuple(data) => {
+                let mut fields = data
+                    .iter()
+                    .map(|(name, value)| Some((name.to_string(), ListEntryType::of(&value.value)?)))
+                    .collect::<Option<Vec<_>>>()?;
+                fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+                ListEntryType::Tuple(fields)
+            }
+            Value::OptionalSome(value) => ListEntryType::Optional(Some(inner(value)?)),
+            Value::OptionalNone => ListEntryType::Optional(None),
+            Value::ResponseOk(value) => ListEntryType::Response(Some(inner(value)?), None),
+            Value::ResponseErr(value) => ListEntryType::Response(None, Some(inner(value)?)),
+            Value::Unknown { raw, .. } => ListEntryType::Other(value.type_id(), raw.len() as u64),
+        })
+    }
+
+    /// The common supertype of two entry types, if there is one.
+    fn unify(self, other: ListEntryType) -> Option<ListEntryType> {
+        fn unify_parts(
+            a: Option<Box<ListEntryType>>,
+            b: Option<Box<ListEntryType>>,
+        ) -> Option<Option<Box<ListEntryType>>> {
+            match (a, b) {
+                (Some(a), Some(b)) => a.unify(*b).map(|t| Some(Box::new(t))),
+                (a, None) => Some(a),
+                (None, b) => Some(b),
+            }
+        }
+        use ListEntryType::*;
+        Some(match (self, other) {
+            (Buffer(a), Buffer(b)) => Buffer(a.max(b)),
+            (StringASCII(a), StringASCII(b)) => StringASCII(a.max(b)),
+            (StringUTF8(a), StringUTF8(b)) => StringUTF8(a.max(b)),
+            (Other(a, len_a), Other(b, len_b)) if a == b => Other(a, len_a.max(len_b)),
+            (List(a, len_a), List(b, len_b)) => List(unify_parts(a, b)?, len_a.max(len_b)),
+            (Optional(a), Optional(b)) => Optional(unify_parts(a, b)?),
+            (Response(ok_a, err_a), Response(ok_b, err_b)) => {
+                Response(unify_parts(ok_a, ok_b)?, unify_parts(err_a, err_b)?)
+            }
+            (Tuple(a), Tuple(b)) => {
+                if a.len() != b.len() {
+                    return None;
+                }
+                let mut fields = Vec::with_capacity(a.len());
+                for ((name_a, a), (name_b, b)) in a.into_iter().zip(b) {
+                    if name_a != name_b {
+                        return None;
+                    }
+                    fields.push((name_a, a.unify(b)?));
+                }
+                Tuple(fields)
+            }
+            (a, b) if a == b => a,
+            _ => return None,
+        })
+    }
+
+    /// The VM's `TypeSignature::size()`. Unknown parts are the VM's `NoType`, sized 1.
+    fn size(&self) -> u64 {
+        fn part_size(part: &Option<Box<ListEntryType>>) -> u64 {
+            part.as_ref().map_or(1, |t| t.size())
+        }
+        use ListEntryType::*;
+        match self {
+            Int | UInt => 16,
+            Bool => 1,
+            Buffer(len) | StringASCII(len) => 4 + len,
+            StringUTF8(len) => 4 + 4 * len,
+            Principal => PRINCIPAL_TYPE_SIZE,
+            List(entry_type, max_len) => part_size(entry_type) * max_len + max_len,
+            Tuple(fields) => fields
+                .iter()
+                .map(|(name, t)| 2 + name.len() as u64 + t.size())
+                .sum(),
+            Optional(inner) => part_size(inner) + 1,
+            Response(ok, err) => part_size(ok).max(part_size(err)) + 1,
+            Other(_, len) => *len,
+        }
+    }
+}
+
+/// The VM's size for the principal type: a version byte and hash160, plus a length prefixed
+/// contract name of up to 128 bytes.
+const PRINCIPAL_TYPE_SIZE: u64 = 21 + 1 + 128;
+
+fn check_value_len(type_name: &str, len: usize) -> Result<(), String> {
+    if len > MAX_VALUE_SIZE as usize {
+        return Err(format!("Illegal {} type size {}", type_name, len));
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
 pub enum Value {
     Int(i128),
     UInt(u128),
@@ -50,11 +300,20 @@ pub enum Value {
     StringASCII(Vec<u8>),
     PrincipalStandard(StandardPrincipalData),
     PrincipalContract(QualifiedContractIdentifier),
-    Tuple(BTreeMap<ClarityName, ClarityValue>),
+    Tuple(TupleData),
     OptionalSome(Box<ClarityValue>),
     OptionalNone,
     ResponseOk(Box<ClarityValue>),
     ResponseErr(Box<ClarityValue>),
+    /// A type prefix this crate doesn't recognize, captured with `UnknownTypeMode::Capture`.
+    /// `raw` holds every byte after the prefix, since the length of an unknown type can't be known.
+    Unknown {
+        type_id: u8,
+        raw: Vec<u8>,
+    },
+    /// Stands in for a value nested deeper than `clarity_truncate_depth` keeps. It renders as `...`
+    /// in `repr_string` and `type_signature`, and otherwise behaves like the value it replaced.
+    Truncated(Box<ClarityValue>),
 }
 
 impl Value {
@@ -69,6 +328,16 @@ impl Value {
         Value::StringUTF8(data)
     }
 
+    /// The serialized type byte, including the raw byte of an `Unknown` value.
+    pub fn type_id(&self) -> u8 {
+        match self {
+            Value::Unknown { type_id, .. } => *type_id,
+            Value::Truncated(inner) => inner.value.type_id(),
+            _ => self.type_prefix().to_u8(),
+        }
+    }
+
+    /// Panics for `Unknown` values, which have no `TypePrefix`. Use `type_id` for those.
     pub fn type_prefix(&self) -> TypePrefix {
         use Value::*;
         match self {
@@ -87,6 +356,25 @@ impl Value {
             List(_) => TypePrefix::List,
             StringASCII(_) => TypePrefix::StringASCII,
             StringUTF8(_) => TypePrefix::StringUTF8,
+            Unknown { type_id, .. } => panic!("No type prefix for unknown type 0x{:02x}", type_id),
+            Truncated(inner) => inner.value.type_prefix(),
+        }
+    }
+
+    /// Whether the last value decoded into this one is a captured `Unknown` value, which holds
+    /// the rest of the input. Decoding stops there, so lists and tuples may hold fewer items than
+    /// their declared length.
+    pub fn ends_in_capture(&self) -> bool {
+        use Value::*;
+        match self {
+            Unknown { .. } => true,
+            OptionalSome(value) | ResponseOk(value) | ResponseErr(value) | Truncated(value) => {
+                value.value.ends_in_capture()
+            }
+            List(items) => matches!(items.last(), Some(v) if v.value.ends_in_capture()),
+            // decoding stops at the captured field, which isn't last once sorted by name
+            Tuple(data) => data.iter().any(|(_, v)| v.value.ends_in_capture()),
+            _ => false,
         }
     }
 
@@ -153,7 +441,7 @@ impl Value {
             StringASCII(data) => {
                 write!(w, "\"")?;
                 for c in data.iter() {
-                    write!(w, "{}", std::ascii::escape_default(*c))?;
+                    write_escaped_ascii(w, *c)?;
                 }
                 write!(w, "\"")
             }
@@ -165,11 +453,15 @@ impl Value {
                         write!(w, "\\u{{{}}}", encode_hex_no_prefix(c))?;
                     } else {
                         // We render an ASCII char, escaped
-                        write!(w, "{}", std::ascii::escape_default(c[0]))?;
+                        write_escaped_ascii(w, c[0])?;
                     }
                 }
                 write!(w, "\"")
             }
+            Unknown { type_id, raw } => {
+                write!(w, "(unknown 0x{:02x} {})", type_id, encode_hex(raw))
+            }
+            Truncated(_) => write!(w, "..."),
         }
     }
 
@@ -229,10 +521,63 @@ impl Value {
             StringUTF8(data) => {
                 write!(w, "(string-utf8 {})", data.len() * 4)
             }
+            Unknown { .. } => write!(w, "UnknownType"),
+            Truncated(_) => write!(w, "..."),
         }
     }
 }
 
+/// Tuple fields in order. Decoded tuples keep the order controlled by
+/// `DecodeOptions::tuple_order`, which is wire order by default.
+#[derive(Default, Clone)]
+pub struct TupleData(Vec<(ClarityName, ClarityValue)>);
+
+impl TupleData {
+    pub fn new() -> Self {
+        TupleData(Vec::new())
+    }
+
+    /// Sets a field, keeping the fields sorted by name like the consensus serialization.
+    /// An existing field with the same name is replaced.
+    pub fn insert(&mut self, name: ClarityName, value: ClarityValue) {
+        match self.0.binary_search_by(|(n, _)| n.cmp(&name)) {
+            Ok(index) => self.0[index].1 = value,
+            Err(index) => self.0.insert(index, (name, value)),
+        }
+    }
+
+    /// Appends a field in decode order. An existing field with the same name is replaced in place.
+    pub(crate) fn push(&mut self, name: ClarityName, value: ClarityValue) {
+        match self.0.iter_mut().find(|(n, _)| *n == name) {
+            Some(field) => field.1 = value,
+            None => self.0.push((name, value)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ClarityValue> {
+        self.0
+            .iter()
+            .find(|(n, _)| n.as_str() == name)
+            .map(|(_, v)| v)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&ClarityName, &ClarityValue)> {
+        self.0.iter().map(|(n, v)| (n, v))
+    }
+
+    pub fn sort_by_name(&mut self) {
+        self.0.sort_by(|(a, _), (b, _)| a.cmp(b));
+    }
+}
+
 #[derive(Clone, Eq, PartialEq)]
 pub struct StandardPrincipalData(pub u8, pub [u8; 20]);
 
@@ -315,3 +660,1149 @@ lazy_static! {
 
 guarded_string!(ClarityName, "ClarityName", CLARITY_NAME_REGEX);
 guarded_string!(ContractName, "ContractName", CONTRACT_NAME_REGEX);
+
+/// Checks a contract name against the Clarity rules: 1 to 40 characters, starting with a
+/// letter and followed by letters, digits, `-` or `_` (or the reserved `__transient` name).
+pub fn is_valid_contract_name(name: &str) -> bool {
+    name.len() >= CONTRACT_MIN_NAME_LENGTH
+        && name.len() <= CONTRACT_MAX_NAME_LENGTH
+        && CONTRACT_NAME_REGEX.is_match(name)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ResponseWrapper {
+    Ok,
+    Err,
+}
+
+/// Unwraps nested `(ok ...)` / `(err ...)` responses, returning the innermost non-response
+/// value and the wrappers encountered from the outermost inwards. For example `(ok (err u1))`
+/// returns `u1` and `[Ok, Err]`, i.e. the call succeeded but returned an inner err.
+pub fn clarity_flatten_response(val: &ClarityValue) -> (&ClarityValue, Vec<ResponseWrapper>) {
+    let mut wrappers = vec![];
+    let mut current = val;
+    loop {
+        match &current.value {
+            Value::ResponseOk(inner) => {
+                wrappers.push(ResponseWrapper::Ok);
+                current = inner;
+            }
+            Value::ResponseErr(inner) => {
+                wrappers.push(ResponseWrapper::Err);
+                current = inner;
+            }
+            _ => return (current, wrappers),
+        }
+    }
+}
+
+/// The code of an `(err uint)` value, the usual way contracts report failures. `None` for
+/// `(ok ...)` and for errors that aren't a `uint`.
+pub fn clarity_error_code(value: &ClarityValue) -> Option<u128> {
+    match &value.value {
+        Value::ResponseErr(inner) => match inner.value {
+            Value::UInt(code) => Some(code),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A `uint` that is logically a `u64`, such as a block height or nonce. Errors for other types
+/// and for values above `u64::MAX` rather than truncating them.
+pub fn clarity_as_u64(value: &ClarityValue) -> Result<u64, String> {
+    match value.value {
+        Value::UInt(n) => {
+            u64::try_from(n).map_err(|_| format!("Value u{} does not fit in a u64", n))
+        }
+        _ => Err(format!(
+            "Expected a uint, found {}",
+            value.value.type_signature()
+        )),
+    }
+}
+
+/// Converts a `(buff 20)` holding a raw hash160, as some contracts use in place of a principal,
+/// into the c32 address with the given version.
+pub fn clarity_buff20_to_address(value: &ClarityValue, version: u8) -> Result<String, String> {
+    match &value.value {
+        Value::Buffer(buff) if buff.len() == 20 => c32_address(version, buff),
+        Value::Buffer(buff) => Err(format!(
+            "Expected a 20 byte buffer, found {} bytes",
+            buff.len()
+        )),
+        _ => Err(format!(
+            "Expected a 20 byte buffer, found {}",
+            value.value.type_signature()
+        )),
+    }
+}
+
+/// A step in a `clarity_dig` path.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Accessor<'a> {
+    /// The inner value of `(some ...)`.
+    Some,
+    /// The inner value of `(ok ...)`.
+    Ok,
+    /// The inner value of `(err ...)`.
+    Err,
+    /// A tuple field by name.
+    Field(&'a str),
+    /// A list item by index.
+    Index(usize),
+}
+
+fn format_dig_path(path: &[Accessor]) -> String {
+    let mut result = String::new();
+    for accessor in path {
+        let segment = match accessor {
+            Accessor::Index(i) => {
+                result.push_str(&format!("[{}]", i));
+                continue;
+            }
+            Accessor::Some => "some",
+            Accessor::Ok => "ok",
+            Accessor::Err => "err",
+            Accessor::Field(name) => name,
+        };
+        if !result.is_empty() {
+            result.push('.');
+        }
+        result.push_str(segment);
+    }
+    result
+}
+
+/// Walks a path of accessors into nested optionals, responses, tuples and lists, e.g.
+/// `[Some, Ok, Field("amount")]` on `(some (ok (tuple (amount u1))))` returns `u1`. The error
+/// names the path up to the step that didn't match.
+pub fn clarity_dig<'v>(
+    value: &'v ClarityValue,
+    path: &[Accessor],
+) -> Result<&'v ClarityValue, String> {
+    let mut current = value;
+    for (i, accessor) in path.iter().enumerate() {
+        let next = match (accessor, &current.value) {
+            (Accessor::Some, Value::OptionalSome(inner)) => Some(inner.as_ref()),
+            (Accessor::Ok, Value::ResponseOk(inner)) => Some(inner.as_ref()),
+            (Accessor::Err, Value::ResponseErr(inner)) => Some(inner.as_ref()),
+            (Accessor::Field(name), Value::Tuple(data)) => {
+                Some(data.get(name).ok_or_else(|| {
+                    format!(
+                        "Cannot dig `{}`: tuple has no field `{}`",
+                        format_dig_path(&path[..=i]),
+                        name
+                    )
+                })?)
+            }
+            (Accessor::Index(index), Value::List(items)) => {
+                Some(items.get(*index).ok_or_else(|| {
+                    format!(
+                        "Cannot dig `{}`: index out of bounds for list of length {}",
+                        format_dig_path(&path[..=i]),
+                        items.len()
+                    )
+                })?)
+            }
+            _ => None,
+        };
+        current = next.ok_or_else(|| {
+            let expected = match accessor {
+                Accessor::Some => "(some ...)",
+                Accessor::Ok => "(ok ...)",
+                Accessor::Err => "(err ...)",
+                Accessor::Field(_) => "a tuple",
+                Accessor::Index(_) => "a list",
+            };
+            format!(
+                "Cannot dig `{}`: expected {}, found {}",
+                format_dig_path(&path[..=i]),
+                expected,
+                current.value.repr_string()
+            )
+        })?;
+    }
+    Ok(current)
+}
+
+/// A field of the tuple in an `(optional (tuple ...))`, the shape `map-get?` returns. `None` for
+/// `none`, otherwise the named field of the `some` tuple.
+pub fn clarity_optional_tuple_field(
+    value: &ClarityValue,
+    field: &str,
+) -> Result<Option<ClarityValue>, String> {
+    let inner = match &value.value {
+        Value::OptionalNone => return Ok(None),
+        Value::OptionalSome(inner) => inner,
+        _ => {
+            return Err(format!(
+                "Expected an optional tuple, found {}",
+                value.value.type_signature()
+            ))
+        }
+    };
+    match &inner.value {
+        Value::Tuple(data) => match data.get(field) {
+            Some(field_value) => Ok(Some(field_value.clone())),
+            None => Err(format!("Tuple has no field `{}`", field)),
+        },
+        _ => Err(format!(
+            "Expected an optional tuple, found {}",
+            value.value.type_signature()
+        )),
+    }
+}
+
+/// Compare two values with the semantics of Clarity's `<`, `<=`, `>` and `>=`. Only `int`,
+/// `uint`, `buff`, `string-ascii` and `string-utf8` values of the same type are comparable,
+/// anything else is an error as it is in Clarity.
+pub fn clarity_cmp(a: &ClarityValue, b: &ClarityValue) -> Result<Ordering, String> {
+    use Value::*;
+    match (&a.value, &b.value) {
+        (Int(a), Int(b)) => Ok(a.cmp(b)),
+        (UInt(a), UInt(b)) => Ok(a.cmp(b)),
+        (Buffer(a), Buffer(b)) => Ok(a.cmp(b)),
+        (StringASCII(a), StringASCII(b)) => Ok(a.cmp(b)),
+        (StringUTF8(a), StringUTF8(b)) => Ok(a.cmp(b)),
+        _ => Err(format!(
+            "Cannot compare {} with {}",
+            a.value.type_signature(),
+            b.value.type_signature()
+        )),
+    }
+}
+
+/// Whether two values are equal with the semantics of Clarity's `is-eq`: same type and contents.
+/// Tuple fields are matched by name, so decode order doesn't matter.
+pub fn clarity_values_equal(a: &ClarityValue, b: &ClarityValue) -> bool {
+    values_equal(a, b, false)
+}
+
+/// Same as `clarity_values_equal`, but a `buff` and a `string-ascii` with the same bytes are also
+/// equal, including when nested, for matching text that was stored as either.
+pub fn clarity_values_equal_lenient(a: &ClarityValue, b: &ClarityValue) -> bool {
+    values_equal(a, b, true)
+}
+
+fn values_equal(a: &ClarityValue, b: &ClarityValue, lenient: bool) -> bool {
+    use Value::*;
+    match (&a.value, &b.value) {
+        (Truncated(a), _) => values_equal(a, b, lenient),
+        (_, Truncated(b)) => values_equal(a, b, lenient),
+        (Int(a), Int(b)) => a == b,
+        (UInt(a), UInt(b)) => a == b,
+        (Bool(a), Bool(b)) => a == b,
+        (Buffer(a), Buffer(b)) | (StringASCII(a), StringASCII(b)) => a == b,
+        (Buffer(a), StringASCII(b)) | (StringASCII(a), Buffer(b)) => lenient && a == b,
+        (StringUTF8(a), StringUTF8(b)) => a == b,
+        (PrincipalStandard(a), PrincipalStandard(b)) => a == b,
+        (PrincipalContract(a), PrincipalContract(b)) => a == b,
+        (List(a), List(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|(a, b)| values_equal(a, b, lenient))
+        }
+        (Tuple(a), Tuple(b)) => {
+            a.len() == b.len()
+                && a.iter().all(|(name, a)| match b.get(name.as_str()) {
+                    Some(b) => values_equal(a, b, lenient),
+                    None => false,
+                })
+        }
+        (OptionalSome(a), OptionalSome(b))
+        | (ResponseOk(a), ResponseOk(b))
+        | (ResponseErr(a), ResponseErr(b)) => values_equal(a, b, lenient),
+        (OptionalNone, OptionalNone) => true,
+        (
+            Unknown {
+                type_id: a_type_id,
+                raw: a_raw,
+            },
+            Unknown {
+                type_id: b_type_id,
+                raw: b_raw,
+            },
+        ) => a_type_id == b_type_id && a_raw == b_raw,
+        _ => false,
+    }
+}
+
+/// Concatenates the items of a list of lists into a single list, e.g.
+/// `(list (list u1 u2) (list) (list u3))` becomes `(list u1 u2 u3)`.
+pub fn clarity_flatten_list(value: &ClarityValue) -> Result<ClarityValue, String> {
+    let lists = match &value.value {
+        Value::List(lists) => lists,
+        _ => {
+            return Err(format!(
+                "Expected a list of lists, found {}",
+                value.value.type_signature()
+            ))
+        }
+    };
+    let mut items = Vec::new();
+    for list in lists {
+        match &list.value {
+            Value::List(inner) => items.extend(inner.iter().cloned()),
+            _ => {
+                return Err(format!(
+                    "Expected a list of lists, found an item of type {}",
+                    list.value.type_signature()
+                ))
+            }
+        }
+    }
+    Ok(ClarityValue::new(Value::List(items)))
+}
+
+/// A new tuple with the fields of `base` and `overlay`, where `overlay`'s fields take
+/// precedence. Fields are sorted by name, so the result serializes canonically.
+pub fn clarity_tuple_merge(
+    base: &ClarityValue,
+    overlay: &ClarityValue,
+) -> Result<ClarityValue, String> {
+    match (&base.value, &overlay.value) {
+        (Value::Tuple(base_data), Value::Tuple(overlay_data)) => {
+            let mut merged = TupleData::new();
+            for (name, value) in base_data.iter().chain(overlay_data.iter()) {
+                merged.insert(name.clone(), value.clone());
+            }
+            Ok(ClarityValue::new(Value::Tuple(merged)))
+        }
+        _ => Err(format!(
+            "Cannot merge {} with {}, expected tuples",
+            base.value.type_signature(),
+            overlay.value.type_signature()
+        )),
+    }
+}
+
+/// A copy of `value` for logging, with everything nested deeper than `max_depth` replaced by a
+/// `Truncated` marker, so its `repr_string` renders those subtrees as `...`. The root is at depth
+/// 0, so a `max_depth` of 0 keeps the root and truncates its children. The markers hold the values
+/// they replaced, so the copy serializes like `value`, and encodes to JSON like it apart from the
+/// `repr` strings of the values holding markers.
+pub fn clarity_truncate_depth(value: &ClarityValue, max_depth: usize) -> ClarityValue {
+    let child = |inner: &ClarityValue| {
+        if max_depth == 0 {
+            ClarityValue {
+                serialized_bytes: inner.serialized_bytes.clone(),
+                value: Value::Truncated(Box::new(inner.clone())),
+            }
+        } else {
+            clarity_truncate_depth(inner, max_depth - 1)
+        }
+    };
+    let truncated = match &value.value {
+        Value::OptionalSome(inner) => Value::OptionalSome(Box::new(child(inner))),
+        Value::ResponseOk(inner) => Value::ResponseOk(Box::new(child(inner))),
+        Value::ResponseErr(inner) => Value::ResponseErr(Box::new(child(inner))),
+        Value::List(items) => Value::List(items.iter().map(child).collect()),
+        Value::Tuple(data) => {
+            let mut fields = TupleData::new();
+            for (name, inner) in data.iter() {
+                fields.push(name.clone(), child(inner));
+            }
+            Value::Tuple(fields)
+        }
+        _ => return value.clone(),
+    };
+    ClarityValue {
+        serialized_bytes: value.serialized_bytes.clone(),
+        value: truncated,
+    }
+}
+
+/// Flag set in the first byte of `principal_index_key` for contract principals. Address
+/// versions are below 32, so it never appears in the key of a standard principal.
+pub const CONTRACT_PRINCIPAL_KEY_FLAG: u8 = 0x80;
+
+/// A fixed size key for a standard (`address`) or contract (`address.name`) principal, for use in
+/// maps keyed by principal. Standard principals map to their version followed by their hash160.
+/// Contract principals map to their version with `CONTRACT_PRINCIPAL_KEY_FLAG` set, followed by
+/// the hash160 of the issuer's hash160 and the contract name.
+pub fn principal_index_key(principal: &str) -> Result<[u8; 21], String> {
+    let mut key = [0u8; 21];
+    match ClarityValue::principal(principal)?.value {
+        Value::PrincipalStandard(StandardPrincipalData(version, hash160)) => {
+            key[0] = version;
+            key[1..].copy_from_slice(&hash160);
+        }
+        Value::PrincipalContract(QualifiedContractIdentifier { issuer, name }) => {
+            let mut preimage = issuer.1.to_vec();
+            preimage.extend_from_slice(name.as_bytes());
+            key[0] = issuer.0 | CONTRACT_PRINCIPAL_KEY_FLAG;
+            key[1..].copy_from_slice(&hash160(&preimage));
+        }
+        _ => unreachable!(),
+    }
+    Ok(key)
+}
+
+/// The Clarity VM's `Value::size()`, the size it checks against limits like `MAX_VALUE_SIZE`:
+/// * `int` and `uint`: 16, `bool`: 1, principals: 150, the size of the principal type
+/// * `buff` and `string-ascii`: their length, `string-utf8`: 4 per character
+/// * lists, tuples, optionals and responses: the size of their type signature, where
+///   * `(buff n)` and `(string-ascii n)` are `n + 4`, `(string-utf8 n)` is `4 * n + 4`
+///   * `(list n t)` is `n * (size(t) + 1)`, taking the longest items as `t`
+///   * tuples are the sum of `2 + name length + size(field type)`
+///   * `(optional t)` is `size(t) + 1`, `(response t e)` is `max(size(t), size(e)) + 1`
+///   * the unknown inner type of `none`, and of the other side of `ok` or `err`, is 1
+///
+/// Lists without a common item type can't be VM values and are sized 0.
+pub fn clarity_runtime_size(value: &ClarityValue) -> u64 {
+    runtime_size(&value.value)
+}
+
+fn runtime_size(value: &Value) -> u64 {
+    match value {
+        Value::Truncated(inner) => runtime_size(&inner.value),
+        Value::Int(_) | Value::UInt(_) => 16,
+        Value::Bool(_) => 1,
+        Value::PrincipalStandard(_) | Value::PrincipalContract(_) => PRINCIPAL_TYPE_SIZE,
+        Value::Buffer(data) | Value::StringASCII(data) => data.len() as u64,
+        Value::StringUTF8(data) => data.len() as u64 * 4,
+        Value::List(_)
+        | Value::Tuple(_)
+        | Value::OptionalSome(_)
+        | Value::OptionalNone
+        | Value::ResponseOk(_)
+        | Value::ResponseErr(_) => ListEntryType::of(value).map_or(0, |t| t.size()),
+        Value::Unknown { raw, .. } => raw.len() as u64,
+    }
+}
+
+/// Every standard and contract principal in a value, including inside tuples, lists, optionals
+/// and responses, as address strings. Duplicates are removed, keeping first-seen order.
+pub fn clarity_collect_principals(value: &ClarityValue) -> Vec<String> {
+    let mut principals = vec![];
+    collect_principals(&value.value, &mut principals);
+    principals
+}
+
+fn collect_principals(value: &Value, principals: &mut Vec<String>) {
+    let principal = match value {
+        Value::PrincipalStandard(data) => c32_address(data.0, &data.1),
+        Value::PrincipalContract(data) => c32_address(data.issuer.0, &data.issuer.1)
+            .map(|issuer| format!("{}.{}", issuer, data.name)),
+        Value::OptionalSome(inner) | Value::ResponseOk(inner) | Value::ResponseErr(inner) => {
+            return collect_principals(&inner.value, principals);
+        }
+        Value::List(items) => {
+            for item in items {
+                collect_principals(&item.value, principals);
+            }
+            return;
+        }
+        Value::Tuple(data) => {
+            for (_name, item) in data.iter() {
+                collect_principals(&item.value, principals);
+            }
+            return;
+        }
+        _ => return,
+    };
+    // versions that can't be c32 encoded aren't valid principals, skip them
+    if let Ok(principal) = principal {
+        if !principals.contains(&principal) {
+            principals.push(principal);
+        }
+    }
+}
+
+/// Escape a string character the way stacks-node renders `string-ascii` and `string-utf8`
+/// values: `"`, `'` and `\` are backslash escaped, `\t`, `\r` and `\n` use their short
+/// escapes, and any other control or non-ASCII byte is written as `\xNN`.
+fn write_escaped_ascii(w: &mut Vec<u8>, c: u8) -> std::io::Result<()> {
+    write!(w, "{}", std::ascii::escape_default(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clarity_value::json::clarity_value_to_json;
+    use crate::hex::decode_hex;
+    use std::io::Cursor;
+
+    fn deserialize_hex(input: &str) -> ClarityValue {
+        let bytes = decode_hex(input).unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        ClarityValue::deserialize(&mut cursor, false).unwrap()
+    }
+
+    #[test]
+    fn test_repr_string_escapes() {
+        // reprs rendered by stacks-node for the same values
+        let cases = [
+            // "say \"hi\""
+            ("0x0d000000087361792022686922", r#""say \"hi\"""#),
+            // "C:\\dir"
+            ("0x0d00000006433a5c646972", r#""C:\\dir""#),
+            // "a\nb\tc\r"
+            ("0x0d00000006610a6209630d", r#""a\nb\tc\r""#),
+            // "it's\x00\x1b\x7f"
+            ("0x0d0000000769742773001b7f", r#""it\'s\x00\x1b\x7f""#),
+            // u"\"\\\n\u{e9}"
+            ("0x0e00000005225c0ac3a9", r#"u"\"\\\n\u{c3a9}""#),
+        ];
+        for (input, expected) in cases.iter() {
+            assert_eq!(deserialize_hex(input).value.repr_string(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_builder() {
+        let value = ClarityValue::tuple(vec![
+            (
+                "recipient",
+                ClarityValue::principal("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").unwrap(),
+            ),
+            ("amount", ClarityValue::uint(100)),
+            (
+                "memo",
+                ClarityValue::some(ClarityValue::buffer(vec![1, 2, 3]).unwrap()),
+            ),
+            (
+                "tags",
+                ClarityValue::list(vec![
+                    ClarityValue::string_ascii("a").unwrap(),
+                    ClarityValue::string_ascii("b").unwrap(),
+                ])
+                .unwrap(),
+            ),
+            (
+                "result",
+                ClarityValue::ok(
+                    ClarityValue::principal(
+                        "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract",
+                    )
+                    .unwrap(),
+                ),
+            ),
+        ])
+        .unwrap();
+        let expected = "(tuple (amount u100) (memo (some 0x010203)) (recipient 'SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7) (result (ok 'SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract)) (tags (list \"a\" \"b\")))";
+        assert_eq!(value.value.repr_string(), expected);
+
+        let bytes = value.serialize_to_vec();
+        let decoded = ClarityValue::deserialize(&mut Cursor::new(bytes.as_ref()), false).unwrap();
+        assert_eq!(decoded.value.repr_string(), expected);
+        assert_eq!(decoded.serialize_to_vec(), bytes);
+
+        assert_eq!(
+            ClarityValue::err(ClarityValue::int(-1)).value.repr_string(),
+            "(err -1)"
+        );
+        assert_eq!(
+            ClarityValue::string_utf8("\u{e9}")
+                .unwrap()
+                .value
+                .repr_string(),
+            "u\"\\u{c3a9}\""
+        );
+        assert!(ClarityValue::tuple(vec![]).is_err());
+        assert!(ClarityValue::tuple(vec![("1a", ClarityValue::none())]).is_err());
+        assert!(ClarityValue::tuple(vec![
+            ("a", ClarityValue::bool(true)),
+            ("a", ClarityValue::bool(false))
+        ])
+        .is_err());
+        assert!(ClarityValue::string_ascii("\u{e9}").is_err());
+        assert!(ClarityValue::buffer(vec![0; MAX_VALUE_SIZE as usize + 1]).is_err());
+        assert!(ClarityValue::principal("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8").is_err());
+        assert!(ClarityValue::principal("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.1bad").is_err());
+        // valid Clarity names, but too long for a contract name
+        let long_name = format!(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.{}",
+            "a".repeat(41)
+        );
+        assert_eq!(
+            ClarityValue::principal(&long_name).err().unwrap(),
+            format!("Invalid contract name \"{}\"", "a".repeat(41))
+        );
+        let max_name = format!(
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.{}",
+            "a".repeat(40)
+        );
+        assert!(ClarityValue::principal(&max_name).is_ok());
+    }
+
+    #[test]
+    fn test_builder_list_types() {
+        let list = ClarityValue::list;
+        let buff = |data: &[u8]| ClarityValue::buffer(data.to_vec()).unwrap();
+        let ascii = |s: &str| ClarityValue::string_ascii(s).unwrap();
+
+        assert_eq!(
+            list(vec![ClarityValue::uint(1), ascii("a")]).err().unwrap(),
+            "Invalid list, item 1 of type (string-ascii 1) doesn't match the earlier items"
+        );
+        assert!(list(vec![ClarityValue::uint(1), ClarityValue::int(1)]).is_err());
+        assert!(list(vec![buff(&[1]), ascii("a")]).is_err());
+        // lengths may differ
+        assert!(list(vec![buff(&[1]), buff(&[2, 3])]).is_ok());
+        assert!(list(vec![ascii("a"), ascii("bc")]).is_ok());
+        // standard and contract principals are both `principal`
+        assert!(list(vec![
+            ClarityValue::principal("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").unwrap(),
+            ClarityValue::principal("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract")
+                .unwrap(),
+        ])
+        .is_ok());
+
+        // none, ok and err only fix part of the type
+        let some = ClarityValue::some;
+        assert!(list(vec![ClarityValue::none(), some(ClarityValue::uint(1))]).is_ok());
+        assert!(list(vec![
+            ClarityValue::none(),
+            some(ClarityValue::uint(1)),
+            some(ascii("a"))
+        ])
+        .is_err());
+        assert!(list(vec![
+            ClarityValue::ok(ClarityValue::uint(1)),
+            ClarityValue::err(ascii("a")),
+            ClarityValue::ok(ClarityValue::uint(2)),
+        ])
+        .is_ok());
+        assert!(list(vec![
+            ClarityValue::ok(ClarityValue::uint(1)),
+            ClarityValue::err(ascii("a")),
+            ClarityValue::err(ClarityValue::uint(2)),
+        ])
+        .is_err());
+
+        // nested lists and tuples
+        let tuple = |a: ClarityValue| ClarityValue::tuple(vec![("a", a)]).unwrap();
+        assert!(list(vec![
+            tuple(ClarityValue::none()),
+            tuple(some(ClarityValue::bool(true)))
+        ])
+        .is_ok());
+        assert!(list(vec![
+            tuple(ClarityValue::uint(1)),
+            tuple(ClarityValue::int(1))
+        ])
+        .is_err());
+        assert!(list(vec![
+            tuple(ClarityValue::uint(1)),
+            ClarityValue::tuple(vec![("b", ClarityValue::uint(1))]).unwrap()
+        ])
+        .is_err());
+        let empty = list(vec![]).unwrap();
+        let uints = list(vec![ClarityValue::uint(1)]).unwrap();
+        let ints = list(vec![ClarityValue::int(1)]).unwrap();
+        assert!(list(vec![empty.clone(), uints.clone()]).is_ok());
+        assert!(list(vec![empty, uints, ints]).is_err());
+        // a decoded list isn't checked until it is nested in a built one
+        let mixed = ClarityValue::new(Value::List(vec![ClarityValue::uint(1), ascii("a")]));
+        assert!(list(vec![mixed]).is_err());
+    }
+
+    #[test]
+    fn test_principal_index_key() {
+        let standard = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7";
+        let key = principal_index_key(standard).unwrap();
+        assert_eq!(
+            encode_hex(&key).as_ref(),
+            "0x16a46ff88886c2ef9762d970b4d2c63678835bd39d"
+        );
+        assert_eq!(principal_index_key(standard).unwrap(), key);
+
+        let principals = [
+            standard,
+            "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ",
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract",
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract-2",
+            "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ.my-contract",
+            "SP000000000000000000002Q6VF78.my-contract",
+        ];
+        let keys: Vec<[u8; 21]> = principals
+            .iter()
+            .map(|p| principal_index_key(p).unwrap())
+            .collect();
+        for (i, a) in keys.iter().enumerate() {
+            for b in keys[i + 1..].iter() {
+                assert_ne!(a, b);
+            }
+        }
+        assert_eq!(keys[2][0], 22 | CONTRACT_PRINCIPAL_KEY_FLAG);
+        assert_eq!(principal_index_key(principals[2]).unwrap(), keys[2]);
+
+        assert!(principal_index_key("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8").is_err());
+        assert!(principal_index_key("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.").is_err());
+    }
+
+    #[test]
+    fn test_clarity_truncate_depth() {
+        // (list (some (ok (tuple (a u1)))) u2)
+        let bytes = decode_hex(
+            "0x0b000000020a070c00000001016101000000000000000000000000000000010100000000000000000000000000000002",
+        )
+        .unwrap();
+        let value = ClarityValue::deserialize(&mut Cursor::new(bytes.as_ref()), true).unwrap();
+        let truncated = clarity_truncate_depth(&value, 0);
+        assert_eq!(truncated.value.repr_string(), "(list ... ...)");
+        let items = match &truncated.value {
+            Value::List(items) => items,
+            _ => panic!("expected a list"),
+        };
+        match &items[0].value {
+            Value::Truncated(inner) => {
+                assert_eq!(inner.value.repr_string(), "(some (ok (tuple (a u1))))")
+            }
+            _ => panic!("expected a truncated value"),
+        }
+        assert_eq!(items[1].value.type_id(), TypePrefix::UInt.to_u8());
+        // the markers serialize as the values they replaced
+        assert_eq!(truncated.serialize_to_vec(), bytes.as_ref());
+        assert!(clarity_values_equal(&truncated, &value));
+        // and encode to JSON as them, type_id included
+        let original_items = match &value.value {
+            Value::List(items) => items,
+            _ => panic!("expected a list"),
+        };
+        assert_eq!(
+            clarity_value_to_json(&items[0]).unwrap(),
+            clarity_value_to_json(&original_items[0]).unwrap()
+        );
+
+        assert_eq!(
+            clarity_truncate_depth(&value, 2).value.repr_string(),
+            "(list (some (ok ...)) u2)"
+        );
+        assert_eq!(
+            clarity_truncate_depth(&value, 3).value.repr_string(),
+            "(list (some (ok (tuple (a ...)))) u2)"
+        );
+        assert_eq!(
+            clarity_truncate_depth(&value, 4).value.repr_string(),
+            value.value.repr_string()
+        );
+    }
+
+    #[test]
+    fn test_clarity_runtime_size() {
+        let cases = [
+            // u1
+            ("0x0100000000000000000000000000000001", 16),
+            // true
+            ("0x03", 1),
+            // (some 0x010203): (optional (buff 3))
+            ("0x0a0200000003010203", 8),
+            // (ok none): (response (optional NoType) NoType)
+            ("0x0709", 3),
+            // none
+            ("0x09", 2),
+            // u"a\u{1f600}"
+            ("0x0e0000000561f09f9880", 8),
+            // (list 1 2): (list 2 int)
+            (
+                "0x0b0000000200000000000000000000000000000000010000000000000000000000000000000002",
+                34,
+            ),
+            // (list 0x01 0x0203): (list 2 (buff 2))
+            ("0x0b000000020200000001010200000002020300", 14),
+            // (list none (some u1)): (list 2 (optional uint))
+            ("0x0b00000002090a0100000000000000000000000000000001", 36),
+            // (list)
+            ("0x0b00000000", 0),
+            // (tuple (a u1) (bb (ok none))): (2 + 1 + 16) + (2 + 2 + 3)
+            (
+                "0x0c00000002016101000000000000000000000000000000010262620709",
+                26,
+            ),
+            // 'SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7
+            ("0x0516a46ff88886c2ef9762d970b4d2c63678835bd39d", 150),
+            // 'SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR.vault
+            (
+                "0x0616982f3ec112a5f5928a5c96a914bd733793b896a5057661756c74",
+                150,
+            ),
+        ];
+        for (hex, size) in cases.iter() {
+            let value = deserialize_hex(hex);
+            assert_eq!(clarity_runtime_size(&value), *size, "{}", hex);
+        }
+    }
+
+    #[test]
+    fn test_clarity_collect_principals() {
+        // (tuple (a (list 'SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7
+        //                 'SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR.vault
+        //                 'SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR))
+        //        (b (some 'SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7))
+        //        (c (ok true)))
+        let value = deserialize_hex(
+            "0x0c0000000301610b000000030516a46ff88886c2ef9762d970b4d2c63678835bd39d0616982f3ec112a5f5928a5c96a914bd733793b896a5057661756c740516982f3ec112a5f5928a5c96a914bd733793b896a501620a0516a46ff88886c2ef9762d970b4d2c63678835bd39d01630703",
+        );
+        assert_eq!(
+            clarity_collect_principals(&value),
+            vec![
+                "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7",
+                "SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR.vault",
+                "SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR",
+            ]
+        );
+        assert!(clarity_collect_principals(&ClarityValue::new(Value::UInt(1))).is_empty());
+    }
+
+    #[test]
+    fn test_clarity_error_code() {
+        // (err u42)
+        let err = deserialize_hex("0x08010000000000000000000000000000002a");
+        assert_eq!(clarity_error_code(&err), Some(42));
+        // (ok u42)
+        let ok = deserialize_hex("0x07010000000000000000000000000000002a");
+        assert_eq!(clarity_error_code(&ok), None);
+        // (err (tuple (code u42)))
+        let err_tuple =
+            deserialize_hex("0x080c0000000104636f6465010000000000000000000000000000002a");
+        assert_eq!(clarity_error_code(&err_tuple), None);
+        // (err 42)
+        let err_int = deserialize_hex("0x08000000000000000000000000000000002a");
+        assert_eq!(clarity_error_code(&err_int), None);
+    }
+
+    #[test]
+    fn test_clarity_as_u64() {
+        let height = ClarityValue::new(Value::UInt(840_000));
+        assert_eq!(clarity_as_u64(&height).unwrap(), 840_000);
+        let max = ClarityValue::new(Value::UInt(u64::MAX as u128));
+        assert_eq!(clarity_as_u64(&max).unwrap(), u64::MAX);
+
+        let over = ClarityValue::new(Value::UInt(u64::MAX as u128 + 1));
+        assert_eq!(
+            clarity_as_u64(&over).err().unwrap(),
+            "Value u18446744073709551616 does not fit in a u64"
+        );
+        let int = ClarityValue::new(Value::Int(5));
+        assert_eq!(
+            clarity_as_u64(&int).err().unwrap(),
+            "Expected a uint, found int"
+        );
+    }
+
+    #[test]
+    fn test_clarity_flatten_list() {
+        let list = |items: Vec<ClarityValue>| ClarityValue::list(items).unwrap();
+        let nested = list(vec![
+            list(vec![ClarityValue::uint(1), ClarityValue::uint(2)]),
+            list(vec![]),
+            list(vec![ClarityValue::uint(3)]),
+        ]);
+        assert_eq!(
+            clarity_flatten_list(&nested).unwrap().value.repr_string(),
+            "(list u1 u2 u3)"
+        );
+        assert_eq!(
+            clarity_flatten_list(&list(vec![]))
+                .unwrap()
+                .value
+                .repr_string(),
+            "(list)"
+        );
+
+        let mixed = ClarityValue::new(Value::List(vec![
+            list(vec![ClarityValue::uint(1)]),
+            ClarityValue::uint(2),
+        ]));
+        assert_eq!(
+            clarity_flatten_list(&mixed).err().unwrap(),
+            "Expected a list of lists, found an item of type uint"
+        );
+        assert_eq!(
+            clarity_flatten_list(&ClarityValue::uint(1)).err().unwrap(),
+            "Expected a list of lists, found uint"
+        );
+    }
+
+    #[test]
+    fn test_clarity_tuple_merge() {
+        // (tuple (a u1) (b u2))
+        let base = deserialize_hex(
+            "0x0c000000020161010000000000000000000000000000000101620100000000000000000000000000000002",
+        );
+        // (tuple (b u3) (c u4))
+        let overlay = deserialize_hex(
+            "0x0c000000020162010000000000000000000000000000000301630100000000000000000000000000000004",
+        );
+        let merged = clarity_tuple_merge(&base, &overlay).unwrap();
+        assert_eq!(merged.value.repr_string(), "(tuple (a u1) (b u3) (c u4))");
+        assert!(merged.serialized_bytes.is_none());
+
+        assert_eq!(
+            encode_hex(&merged.serialize_to_vec()).as_ref(),
+            "0x0c00000003016101000000000000000000000000000000010162010000000000000000000000000000000301630100000000000000000000000000000004"
+        );
+
+        // fields are sorted by name whichever side they come from
+        // (tuple (z true))
+        let disjoint = deserialize_hex("0x0c00000001017a03");
+        let merged = clarity_tuple_merge(&disjoint, &base).unwrap();
+        assert_eq!(merged.value.repr_string(), "(tuple (a u1) (b u2) (z true))");
+        let merged = clarity_tuple_merge(&base, &disjoint).unwrap();
+        assert_eq!(merged.value.repr_string(), "(tuple (a u1) (b u2) (z true))");
+
+        let uint = ClarityValue::new(Value::UInt(1));
+        assert_eq!(
+            clarity_tuple_merge(&base, &uint).err().unwrap(),
+            "Cannot merge (tuple (a uint) (b uint)) with uint, expected tuples"
+        );
+        assert!(clarity_tuple_merge(&uint, &base).is_err());
+    }
+
+    #[test]
+    fn test_clarity_buff20_to_address() {
+        let hash160 = decode_hex("a46ff88886c2ef9762d970b4d2c63678835bd39d").unwrap();
+        let buff = ClarityValue::new(Value::Buffer(hash160.to_vec()));
+        assert_eq!(
+            clarity_buff20_to_address(&buff, 22).unwrap(),
+            "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7"
+        );
+        assert_eq!(
+            clarity_buff20_to_address(&buff, 26).unwrap(),
+            "ST2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQYAC0RQ"
+        );
+        assert!(clarity_buff20_to_address(&buff, 32).is_err());
+
+        let short = ClarityValue::new(Value::Buffer(hash160[..19].to_vec()));
+        assert!(clarity_buff20_to_address(&short, 22).is_err());
+        let uint = ClarityValue::new(Value::UInt(1));
+        assert!(clarity_buff20_to_address(&uint, 22).is_err());
+    }
+
+    #[test]
+    fn test_clarity_dig() {
+        use Accessor as A;
+
+        // (some (ok u1))
+        let some_ok = deserialize_hex("0x0a070100000000000000000000000000000001");
+        // (some (err u2))
+        let some_err = deserialize_hex("0x0a080100000000000000000000000000000002");
+        // none
+        let none = deserialize_hex("0x09");
+        // (ok (some (tuple (x (list u5 u6)))))
+        let nested = deserialize_hex("0x070a0c0000000101780b0000000201000000000000000000000000000000050100000000000000000000000000000006");
+
+        let dig =
+            |value, path: &[Accessor]| clarity_dig(value, path).map(|v| v.value.repr_string());
+
+        assert_eq!(dig(&some_ok, &[]), Ok("(some (ok u1))".to_string()));
+        assert_eq!(dig(&some_ok, &[A::Some, A::Ok]), Ok("u1".to_string()));
+        assert_eq!(dig(&some_err, &[A::Some, A::Err]), Ok("u2".to_string()));
+        assert_eq!(
+            dig(&some_err, &[A::Some, A::Ok]),
+            Err("Cannot dig `some.ok`: expected (ok ...), found (err u2)".to_string())
+        );
+        assert_eq!(
+            dig(&none, &[A::Some, A::Ok]),
+            Err("Cannot dig `some`: expected (some ...), found none".to_string())
+        );
+
+        assert_eq!(
+            dig(&nested, &[A::Ok, A::Some, A::Field("x"), A::Index(1)]),
+            Ok("u6".to_string())
+        );
+        assert_eq!(
+            dig(&nested, &[A::Ok, A::Some, A::Field("x"), A::Index(2)]),
+            Err("Cannot dig `ok.some.x[2]`: index out of bounds for list of length 2".to_string())
+        );
+        assert_eq!(
+            dig(&nested, &[A::Ok, A::Some, A::Field("y")]),
+            Err("Cannot dig `ok.some.y`: tuple has no field `y`".to_string())
+        );
+        assert_eq!(
+            dig(&nested, &[A::Ok, A::Some, A::Index(0)]),
+            Err(
+                "Cannot dig `ok.some[0]`: expected a list, found (tuple (x (list u5 u6)))"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_clarity_optional_tuple_field() {
+        // none
+        let none = deserialize_hex("0x09");
+        assert!(clarity_optional_tuple_field(&none, "amount")
+            .unwrap()
+            .is_none());
+
+        // (some (tuple (amount u5)))
+        let some =
+            deserialize_hex("0x0a0c0000000106616d6f756e740100000000000000000000000000000005");
+        let amount = clarity_optional_tuple_field(&some, "amount").unwrap();
+        assert_eq!(amount.unwrap().value.repr_string(), "u5");
+        assert_eq!(
+            clarity_optional_tuple_field(&some, "owner").err().unwrap(),
+            "Tuple has no field `owner`"
+        );
+
+        // (some u5)
+        let some_uint = deserialize_hex("0x0a0100000000000000000000000000000005");
+        assert_eq!(
+            clarity_optional_tuple_field(&some_uint, "amount")
+                .err()
+                .unwrap(),
+            "Expected an optional tuple, found (optional uint)"
+        );
+    }
+
+    #[test]
+    fn test_clarity_cmp() {
+        let int = |n| ClarityValue::new(Value::Int(n));
+        let uint = |n| ClarityValue::new(Value::UInt(n));
+        let buff = |b: &[u8]| ClarityValue::new(Value::Buffer(b.to_vec()));
+        let ascii = |s: &str| ClarityValue::new(Value::StringASCII(s.as_bytes().to_vec()));
+        let utf8 = |s: &str| ClarityValue::new(Value::string_utf8(s.as_bytes().to_vec()));
+
+        assert_eq!(clarity_cmp(&int(-5), &int(3)), Ok(Ordering::Less));
+        assert_eq!(clarity_cmp(&int(3), &int(3)), Ok(Ordering::Equal));
+        assert_eq!(
+            clarity_cmp(&int(i128::MAX), &int(i128::MIN)),
+            Ok(Ordering::Greater)
+        );
+
+        assert_eq!(clarity_cmp(&uint(1), &uint(2)), Ok(Ordering::Less));
+        assert_eq!(
+            clarity_cmp(&uint(u128::MAX), &uint(0)),
+            Ok(Ordering::Greater)
+        );
+
+        assert_eq!(
+            clarity_cmp(&buff(&[1, 2]), &buff(&[1, 3])),
+            Ok(Ordering::Less)
+        );
+        assert_eq!(
+            clarity_cmp(&buff(&[1, 2]), &buff(&[1])),
+            Ok(Ordering::Greater)
+        );
+        assert_eq!(clarity_cmp(&buff(&[]), &buff(&[0])), Ok(Ordering::Less));
+        assert_eq!(
+            clarity_cmp(&buff(&[0xff]), &buff(&[0x00, 0x01])),
+            Ok(Ordering::Greater)
+        );
+
+        assert_eq!(
+            clarity_cmp(&ascii("abc"), &ascii("abd")),
+            Ok(Ordering::Less)
+        );
+        assert_eq!(
+            clarity_cmp(&utf8("\u{e9}"), &utf8("z")),
+            Ok(Ordering::Greater)
+        );
+
+        assert!(clarity_cmp(&int(1), &uint(1)).is_err());
+        assert!(clarity_cmp(&ascii("a"), &utf8("a")).is_err());
+        let none = ClarityValue::new(Value::OptionalNone);
+        assert!(clarity_cmp(&none, &none).is_err());
+    }
+
+    #[test]
+    fn test_clarity_values_equal() {
+        let buff = ClarityValue::buffer(b"hello".to_vec()).unwrap();
+        let ascii = ClarityValue::string_ascii("hello").unwrap();
+        assert!(clarity_values_equal(&buff, &buff.clone()));
+        assert!(!clarity_values_equal(&buff, &ascii));
+        assert!(clarity_values_equal_lenient(&buff, &ascii));
+        assert!(clarity_values_equal_lenient(&ascii, &buff));
+        let other = ClarityValue::string_ascii("hellp").unwrap();
+        assert!(!clarity_values_equal_lenient(&buff, &other));
+
+        // nested, with fields in a different order
+        let mut fields = TupleData::new();
+        fields.push(
+            ClarityName::try_from("b".to_string()).unwrap(),
+            ClarityValue::uint(2),
+        );
+        fields.push(
+            ClarityName::try_from("a".to_string()).unwrap(),
+            ClarityValue::some(buff.clone()),
+        );
+        let reordered = ClarityValue::new(Value::Tuple(fields));
+        let sorted = ClarityValue::tuple(vec![
+            ("a", ClarityValue::some(ascii.clone())),
+            ("b", ClarityValue::uint(2)),
+        ])
+        .unwrap();
+        assert!(!clarity_values_equal(&reordered, &sorted));
+        assert!(clarity_values_equal_lenient(&reordered, &sorted));
+        let sorted_buff = ClarityValue::tuple(vec![
+            ("a", ClarityValue::some(buff)),
+            ("b", ClarityValue::uint(2)),
+        ])
+        .unwrap();
+        assert!(clarity_values_equal(&reordered, &sorted_buff));
+
+        assert!(!clarity_values_equal(
+            &ClarityValue::int(1),
+            &ClarityValue::uint(1)
+        ));
+        assert!(!clarity_values_equal_lenient(
+            &ClarityValue::ok(ClarityValue::none()),
+            &ClarityValue::err(ClarityValue::none())
+        ));
+        let list = ClarityValue::list(vec![ClarityValue::int(1)]).unwrap();
+        let longer = ClarityValue::list(vec![ClarityValue::int(1), ClarityValue::int(1)]).unwrap();
+        assert!(!clarity_values_equal(&list, &longer));
+    }
+
+    #[test]
+    fn test_flatten_response() {
+        let val = deserialize_hex("0x07070100000000000000000000000000000001");
+        assert_eq!(val.value.repr_string(), "(ok (ok u1))");
+        let (inner, wrappers) = clarity_flatten_response(&val);
+        assert_eq!(inner.value.repr_string(), "u1");
+        assert_eq!(wrappers, vec![ResponseWrapper::Ok, ResponseWrapper::Ok]);
+
+        let val = deserialize_hex("0x08070100000000000000000000000000000001");
+        assert_eq!(val.value.repr_string(), "(err (ok u1))");
+        let (inner, wrappers) = clarity_flatten_response(&val);
+        assert_eq!(inner.value.repr_string(), "u1");
+        assert_eq!(wrappers, vec![ResponseWrapper::Err, ResponseWrapper::Ok]);
+
+        let val = deserialize_hex("0x07080100000000000000000000000000000001");
+        assert_eq!(val.value.repr_string(), "(ok (err u1))");
+        let (inner, wrappers) = clarity_flatten_response(&val);
+        assert_eq!(inner.value.repr_string(), "u1");
+        assert_eq!(wrappers, vec![ResponseWrapper::Ok, ResponseWrapper::Err]);
+
+        // stops at the first non-response value
+        let val = deserialize_hex("0x070a080100000000000000000000000000000001");
+        assert_eq!(val.value.repr_string(), "(ok (some (err u1)))");
+        let (inner, wrappers) = clarity_flatten_response(&val);
+        assert_eq!(inner.value.repr_string(), "(some (err u1))");
+        assert_eq!(wrappers, vec![ResponseWrapper::Ok]);
+
+        let val = deserialize_hex("0x0100000000000000000000000000000001");
+        let (inner, wrappers) = clarity_flatten_response(&val);
+        assert_eq!(inner.value.repr_string(), "u1");
+        assert!(wrappers.is_empty());
+    }
+
+    #[test]
+    fn test_is_valid_contract_name() {
+        assert!(is_valid_contract_name("a"));
+        assert!(is_valid_contract_name("my-contract_v2"));
+        assert!(is_valid_contract_name("A-_-_9"));
+        assert!(is_valid_contract_name(
+            &"a".repeat(CONTRACT_MAX_NAME_LENGTH)
+        ));
+        assert!(is_valid_contract_name("__transient"));
+
+        assert!(!is_valid_contract_name(""));
+        assert!(!is_valid_contract_name(
+            &"a".repeat(CONTRACT_MAX_NAME_LENGTH + 1)
+        ));
+        assert!(!is_valid_contract_name("1-contract"));
+        assert!(!is_valid_contract_name("-contract"));
+        assert!(!is_valid_contract_name("_contract"));
+        assert!(!is_valid_contract_name("__other"));
+        assert!(!is_valid_contract_name("my.contract"));
+        assert!(!is_valid_contract_name("my contract"));
+        assert!(!is_valid_contract_name("contract?"));
+        assert!(!is_valid_contract_name("contract\u{e9}"));
+    }
+}
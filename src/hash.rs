@@ -0,0 +1,63 @@
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256, Sha512_256};
+
+/// Double SHA-256, as used for base58check and c32check checksums.
+pub fn sha256d(data: &[u8]) -> [u8; 32] {
+    let hash = Sha256::digest(Sha256::digest(data));
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&hash);
+    result
+}
+
+/// SHA-512/256, as used for Stacks txids and other consensus hashes.
+pub fn sha512_256(data: &[u8]) -> [u8; 32] {
+    let hash = Sha512_256::digest(data);
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&hash);
+    result
+}
+
+/// Number of bits that differ between a computed and an expected checksum. One or two bits
+/// suggests a typo, many bits suggests the input is a different value entirely.
+pub fn checksum_bit_distance(computed: u32, expected: u32) -> u32 {
+    (computed ^ expected).count_ones()
+}
+
+/// SHA-256 then RIPEMD-160, as used for Stacks and Bitcoin address hashes.
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    let hash = Ripemd160::digest(Sha256::digest(data));
+    let mut result = [0u8; 20];
+    result.copy_from_slice(&hash);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::encode_hex;
+
+    #[test]
+    fn test_sha256d() {
+        assert_eq!(
+            encode_hex(&sha256d(b"hello")).to_string(),
+            "0x9595c9df90075148eb06860365df33584b75bff782a510c6cd4883a419833d50"
+        );
+        assert_eq!(
+            encode_hex(&sha256d(b"")).to_string(),
+            "0x5df6e0e2761359d30a8275058e299fcc0381534545f55cf43e41983f5d4c9456"
+        );
+    }
+
+    #[test]
+    fn test_hash160() {
+        // secp256k1 generator point, compressed
+        let pubkey = crate::hex::decode_hex(
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        )
+        .unwrap();
+        assert_eq!(
+            encode_hex(&hash160(&pubkey)).to_string(),
+            "0x751e76e8199196d454941c45d1b3a323f1433bd6"
+        );
+    }
+}
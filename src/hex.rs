@@ -30,10 +30,59 @@ pub fn encode_hex_no_prefix(data: &[u8]) -> Box<str> {
     hex_simd::encode_to_boxed_str(data, hex_simd::AsciiCase::Lower)
 }
 
+/// Writes `0x` prefixed lowercase hex, like `encode_hex`, without allocating.
+pub fn write_hex<W: std::fmt::Write>(w: &mut W, bytes: &[u8]) -> std::fmt::Result {
+    const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+    w.write_str("0x")?;
+    let mut chunk_buf = [0u8; 128];
+    for chunk in bytes.chunks(chunk_buf.len() / 2) {
+        for (i, byte) in chunk.iter().enumerate() {
+            chunk_buf[i * 2] = HEX_CHARS[(byte >> 4) as usize];
+            chunk_buf[i * 2 + 1] = HEX_CHARS[(byte & 0x0f) as usize];
+        }
+        // only ASCII hex characters were written
+        w.write_str(std::str::from_utf8(&chunk_buf[..chunk.len() * 2]).unwrap())?;
+    }
+    Ok(())
+}
+
+/// Normalizes a hex string to lowercase without a `0x` prefix, so that different
+/// representations of the same bytes compare equal.
+pub fn canonical_hex(input: &str) -> Result<String, String> {
+    let digits = input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+        .unwrap_or(input);
+    if digits.len() % 2 == 1 {
+        return Err(format!("Invalid hex string, odd length {}", digits.len()));
+    }
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid hex string, unexpected character {:?}", c));
+    }
+    Ok(digits.to_ascii_lowercase())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_write_hex() {
+        let long_input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        for input in [
+            &b""[..],
+            b"hello world",
+            &[0x00, 0x0f, 0xf0, 0xff],
+            &long_input,
+        ]
+        .iter()
+        {
+            let mut out = String::from("prefix:");
+            write_hex(&mut out, input).unwrap();
+            assert_eq!(out, format!("prefix:{}", encode_hex(input)));
+        }
+    }
+
     #[test]
     fn test_hex_encode() {
         let input = b"hello world";
@@ -41,4 +90,23 @@ mod tests {
         let repr = hex_str.to_string();
         assert_eq!(repr, "0x68656c6c6f20776f726c64");
     }
+
+    #[test]
+    fn test_canonical_hex() {
+        for input in [
+            "0xdeadbeef",
+            "0XDEADBEEF",
+            "deadbeef",
+            "DeAdBeEf",
+            "0xDEADbeef",
+        ]
+        .iter()
+        {
+            assert_eq!(canonical_hex(input).unwrap(), "deadbeef");
+        }
+        assert_eq!(canonical_hex("0x").unwrap(), "");
+        assert!(canonical_hex("0xabc").is_err());
+        assert!(canonical_hex("0xzz").is_err());
+        assert!(canonical_hex("0x0x00").is_err());
+    }
 }
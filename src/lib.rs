@@ -6,15 +6,20 @@ use crate::address::{
     is_valid_stacks_address, stacks_address_from_parts, stacks_to_bitcoin_address,
 };
 use crate::clarity_value::{
-    decode_clarity_value, decode_clarity_value_array, decode_clarity_value_to_repr,
-    decode_clarity_value_type_name,
+    decode_clarity_print_event, decode_clarity_stx_transfer_event, decode_clarity_value,
+    decode_clarity_value_array, decode_clarity_value_to_repr, decode_clarity_value_type_name,
+    is_valid_clarity_contract_name,
 };
 use crate::memo::memo_to_string;
 use crate::post_condition::decode_tx_post_conditions;
 use crate::stacks_tx::decode_transaction;
 
 pub mod address;
+pub mod base64;
+#[cfg(feature = "cabi")]
+pub mod cabi;
 pub mod clarity_value;
+pub mod hash;
 pub mod hex;
 pub mod memo;
 pub mod neon_util;
@@ -141,11 +146,14 @@ fn main(mut cx: ModuleContext) -> NeonResult<()> {
     )?;
     cx.export_function("decodeClarityValue", decode_clarity_value)?;
     cx.export_function("decodeClarityValueList", decode_clarity_value_array)?;
+    cx.export_function("decodePrintEvent", decode_clarity_print_event)?;
+    cx.export_function("decodeStxTransferEvent", decode_clarity_stx_transfer_event)?;
     cx.export_function("decodePostConditions", decode_tx_post_conditions)?;
     cx.export_function("decodeTransaction", decode_transaction)?;
     cx.export_function("stacksToBitcoinAddress", stacks_to_bitcoin_address)?;
     cx.export_function("bitcoinToStacksAddress", bitcoin_to_stacks_address)?;
     cx.export_function("isValidStacksAddress", is_valid_stacks_address)?;
+    cx.export_function("isValidContractName", is_valid_clarity_contract_name)?;
     cx.export_function("decodeStacksAddress", decode_stacks_address)?;
     cx.export_function(
         "decodeClarityValueToPrincipal",
@@ -7,7 +7,7 @@ use crate::neon_util::arg_as_bytes;
 
 mod unicode_printable;
 
-fn memo_normalize<T: AsRef<[u8]>>(input: T) -> String {
+pub(crate) fn memo_normalize<T: AsRef<[u8]>>(input: T) -> String {
     let memo_str = String::from_utf8_lossy(input.as_ref());
     let mut result_str: String = String::with_capacity(memo_str.len());
     for g in memo_str.graphemes(true) {
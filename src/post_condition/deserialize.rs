@@ -1,11 +1,15 @@
-use byteorder::{BigEndian, ReadBytesExt};
+use byteorder::ReadBytesExt;
 use std::{
     convert::{TryFrom, TryInto},
     io::{Cursor, Read},
 };
 
+use crate::address::c32::c32_address;
 use crate::clarity_value::types::{ClarityName, ClarityValue};
-use crate::{address::stacks_address::StacksAddress, serialize_util::DeserializeError};
+use crate::{
+    address::stacks_address::StacksAddress,
+    serialize_util::{read_uint_be, DeserializeError},
+};
 
 pub enum TransactionPostCondition {
     STX(PostConditionPrincipal, FungibleConditionCode, u64),
@@ -29,6 +33,16 @@ pub enum PostConditionPrincipal {
     Contract(StacksAddress, ClarityName),
 }
 
+/// A post condition principal with its addresses as c32 strings, see
+/// `PostConditionPrincipal::resolve`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolvedPostConditionPrincipal {
+    /// The origin sentinel, when the transaction sender isn't known.
+    Origin,
+    Standard(String),
+    Contract(String),
+}
+
 #[repr(u8)]
 pub enum PostConditionPrincipalID {
     Origin = 0x01,
@@ -98,7 +112,7 @@ impl TransactionPostCondition {
             x if x == AssetInfoID::STX as u8 => {
                 let principal = PostConditionPrincipal::deserialize(fd)?;
                 let condition_u8: u8 = fd.read_u8()?;
-                let amount: u64 = fd.read_u64::<BigEndian>()?;
+                let amount: u64 = read_uint_be::<8>(fd)? as u64;
 
                 let condition_code: FungibleConditionCode =
                     condition_u8.try_into().map_err(|_| {
@@ -111,7 +125,7 @@ impl TransactionPostCondition {
                 let principal = PostConditionPrincipal::deserialize(fd)?;
                 let asset = AssetInfo::deserialize(fd)?;
                 let condition_u8: u8 = fd.read_u8()?;
-                let amount: u64 = fd.read_u64::<BigEndian>()?;
+                let amount: u64 = read_uint_be::<8>(fd)? as u64;
 
                 let condition_code: FungibleConditionCode =
                     condition_u8.try_into().map_err(|_| {
@@ -151,6 +165,33 @@ impl TransactionPostCondition {
 }
 
 impl PostConditionPrincipal {
+    /// Converts the principal to c32 strings. The origin sentinel refers to the transaction
+    /// sender, and is replaced with `origin` when it's given.
+    pub fn resolve(
+        &self,
+        origin: Option<&StacksAddress>,
+    ) -> Result<ResolvedPostConditionPrincipal, String> {
+        let resolved = match (self, origin) {
+            (PostConditionPrincipal::Origin, None) => ResolvedPostConditionPrincipal::Origin,
+            (PostConditionPrincipal::Origin, Some(address))
+            | (PostConditionPrincipal::Standard(address), _) => {
+                ResolvedPostConditionPrincipal::Standard(c32_address(
+                    address.version,
+                    &address.hash160_bytes,
+                )?)
+            }
+            (PostConditionPrincipal::Contract(address, contract_name), _) => {
+                let address = c32_address(address.version, &address.hash160_bytes)?;
+                ResolvedPostConditionPrincipal::Contract(format!(
+                    "{}.{}",
+                    address,
+                    contract_name.as_str()
+                ))
+            }
+        };
+        Ok(resolved)
+    }
+
     fn deserialize(fd: &mut Cursor<&[u8]>) -> Result<Self, DeserializeError> {
         let principal_id: u8 = fd.read_u8()?;
         let principal = match principal_id {
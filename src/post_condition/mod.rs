@@ -14,7 +14,11 @@ pub fn decode_tx_post_conditions(mut cx: FunctionContext) -> JsResult<JsObject>
     let resp_obj = cx.empty_object();
 
     // first byte is post condition mode
-    let post_condition_mode = cx.number(input_bytes[0]);
+    let post_condition_mode_byte = match input_bytes.first() {
+        Some(b) => *b,
+        None => cx.throw_error("Error reading post condition bytes: Unexpected EOF")?,
+    };
+    let post_condition_mode = cx.number(post_condition_mode_byte);
     resp_obj.set(&mut cx, "post_condition_mode", post_condition_mode)?;
 
     let array_result = if input_bytes.len() > 4 {
@@ -35,7 +39,10 @@ pub fn decode_tx_post_conditions(mut cx: FunctionContext) -> JsResult<JsObject>
         while cursor.position() < post_condition_bytes_len {
             let post_condition =
                 TransactionPostCondition::deserialize(&mut cursor).or_else(|e| {
-                    cx.throw_error(format!("Error deserializing post condition: {}", e.error))
+                    cx.throw_error(format!(
+                        "Error deserializing post condition: {}",
+                        e.offset_by(5).as_string()
+                    ))
                 })?;
             let value_obj = cx.empty_object();
             post_condition.neon_js_serialize(&mut cx, &value_obj)?;
@@ -74,4 +81,65 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_decode_truncated() {
+        let input = decode_hex("0x0103167e9152cdbbb9fef066df4e1b88b19bcb313acc901b6c69717569646974792d746f6b656e2d76356b6d6e77733563676c1608633eac058f2e6ab41613a0a537c7ea1a79cdd20f6d69616d69636f696e2d746f6b656e096d69616d69636f696e030000000000000423").unwrap();
+        for len in 0..input.len() {
+            let mut cursor = Cursor::new(&input[..len]);
+            let err = TransactionPostCondition::deserialize(&mut cursor)
+                .err()
+                .unwrap();
+            assert!(err.is_unexpected_eof(), "{}", err);
+        }
+        let mut cursor = Cursor::new(input.as_ref());
+        assert!(TransactionPostCondition::deserialize(&mut cursor).is_ok());
+    }
+
+    #[test]
+    fn test_resolve_principal() {
+        use self::deserialize::{PostConditionPrincipal, ResolvedPostConditionPrincipal};
+        use crate::address::stacks_address::StacksAddress;
+
+        fn stx_principal(hex: &str) -> PostConditionPrincipal {
+            let input = decode_hex(hex).unwrap();
+            match TransactionPostCondition::deserialize(&mut Cursor::new(input.as_ref())).unwrap() {
+                TransactionPostCondition::STX(principal, ..) => principal,
+                _ => unreachable!(),
+            }
+        }
+
+        let sender =
+            StacksAddress::from_string("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7").unwrap();
+        let origin = stx_principal("0x0001010000000000000064");
+        assert_eq!(
+            origin.resolve(None).unwrap(),
+            ResolvedPostConditionPrincipal::Origin
+        );
+        assert_eq!(
+            origin.resolve(Some(&sender)).unwrap(),
+            ResolvedPostConditionPrincipal::Standard(
+                "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7".to_string()
+            )
+        );
+
+        let standard =
+            stx_principal("0x000216a46ff88886c2ef9762d970b4d2c63678835bd39d010000000000000064");
+        assert_eq!(
+            standard.resolve(None).unwrap(),
+            ResolvedPostConditionPrincipal::Standard(
+                "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7".to_string()
+            )
+        );
+
+        let contract = stx_principal(
+            "0x000316a46ff88886c2ef9762d970b4d2c63678835bd39d0b6d792d636f6e7472616374010000000000000064",
+        );
+        assert_eq!(
+            contract.resolve(Some(&sender)).unwrap(),
+            ResolvedPostConditionPrincipal::Contract(
+                "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract".to_string()
+            )
+        );
+    }
 }
@@ -1,31 +1,69 @@
 use std::fmt::Display;
+use std::io::{Cursor, ErrorKind, Read};
+
+const UNEXPECTED_EOF_MSG: &str = "Unexpected EOF";
 
 #[derive(Debug)]
 pub struct DeserializeError {
     pub error: String,
+    /// Byte offset into the input of the value that failed to decode, when known. It isn't part of
+    /// the `Display` message, so error strings stay the same for callers matching on them.
+    pub at_offset: Option<usize>,
 }
 
 impl DeserializeError {
+    /// The message for callers outside Rust, e.g. the JS bindings, followed by the offset when
+    /// known: `Bad type prefix 0x20 at offset 37`.
     pub fn as_string(self) -> String {
-        self.error
+        match self.at_offset {
+            Some(offset) => format!("{} at offset {}", self.error, offset),
+            None => self.error,
+        }
+    }
+
+    /// Makes the offset relative to an input that the decoded bytes start `base` bytes into.
+    pub fn offset_by(mut self, base: usize) -> Self {
+        self.at_offset = self.at_offset.map(|offset| offset + base);
+        self
+    }
+
+    /// Sets the offset the error occurred at, unless an inner value already set a more precise one.
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.at_offset.get_or_insert(offset);
+        self
+    }
+
+    pub fn unexpected_eof() -> Self {
+        UNEXPECTED_EOF_MSG.to_string().into()
+    }
+
+    /// True if the error was caused by the input ending before the value was fully read.
+    pub fn is_unexpected_eof(&self) -> bool {
+        self.error.starts_with(UNEXPECTED_EOF_MSG)
     }
 }
 
 impl From<String> for DeserializeError {
     fn from(error: String) -> Self {
-        DeserializeError { error }
+        DeserializeError {
+            error,
+            at_offset: None,
+        }
     }
 }
 
 impl From<std::io::Error> for DeserializeError {
     fn from(err: std::io::Error) -> Self {
+        if err.kind() == ErrorKind::UnexpectedEof {
+            return DeserializeError::unexpected_eof();
+        }
         format!("Serialization error: {:?}", err).into()
     }
 }
 
 impl From<&str> for DeserializeError {
     fn from(err: &str) -> Self {
-        err.into()
+        err.to_string().into()
     }
 }
 
@@ -34,3 +72,64 @@ impl Display for DeserializeError {
         f.write_str(&self.error)
     }
 }
+
+/// Ensure at least `len` bytes remain in the cursor before reading (or allocating for) a
+/// length-prefixed field. Guards against truncated input and bogus length prefixes.
+pub fn ensure_remaining(fd: &Cursor<&[u8]>, len: u64) -> Result<(), DeserializeError> {
+    let remaining = (fd.get_ref().len() as u64).saturating_sub(fd.position());
+    if remaining < len {
+        Err(DeserializeError::unexpected_eof())
+    } else {
+        Ok(())
+    }
+}
+
+/// Read an `N` byte big-endian unsigned integer, for `N` up to 16. Callers narrow the result to
+/// the field's width, e.g. `read_uint_be::<4>(r)? as u32` for a length prefix.
+pub fn read_uint_be<const N: usize>(r: &mut impl Read) -> Result<u128, DeserializeError> {
+    assert!(N <= 16, "read_uint_be reads at most 16 bytes");
+    let mut buffer = [0u8; 16];
+    r.read_exact(&mut buffer[16 - N..])?;
+    Ok(u128::from_be_bytes(buffer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_uint_be() {
+        let bytes = [0xffu8; 16];
+        assert_eq!(read_uint_be::<1>(&mut &bytes[..]).unwrap(), u8::MAX as u128);
+        assert_eq!(
+            read_uint_be::<4>(&mut &bytes[..]).unwrap(),
+            u32::MAX as u128
+        );
+        assert_eq!(
+            read_uint_be::<8>(&mut &bytes[..]).unwrap(),
+            u64::MAX as u128
+        );
+        assert_eq!(read_uint_be::<16>(&mut &bytes[..]).unwrap(), u128::MAX);
+
+        let bytes = [0x01, 0x02, 0x03, 0x04, 0x05];
+        let mut cursor = Cursor::new(&bytes[..]);
+        assert_eq!(read_uint_be::<1>(&mut cursor).unwrap(), 0x01);
+        assert_eq!(read_uint_be::<4>(&mut cursor).unwrap(), 0x02030405);
+        assert_eq!(cursor.position(), 5);
+
+        let mut cursor = Cursor::new(&bytes[..]);
+        assert!(read_uint_be::<8>(&mut cursor)
+            .unwrap_err()
+            .is_unexpected_eof());
+        assert_eq!(read_uint_be::<0>(&mut &bytes[..]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_as_string() {
+        let err: DeserializeError = "Bad type prefix 0x20".into();
+        assert_eq!(err.to_string(), "Bad type prefix 0x20");
+        let err = err.with_offset(33).offset_by(4);
+        assert_eq!(err.to_string(), "Bad type prefix 0x20");
+        assert_eq!(err.as_string(), "Bad type prefix 0x20 at offset 37");
+    }
+}
@@ -1,11 +1,13 @@
-use byteorder::{BigEndian, ReadBytesExt};
+use byteorder::ReadBytesExt;
 use std::io::{Cursor, Read};
 
+use crate::address::c32::c32_address;
 use crate::address::stacks_address::StacksAddress;
 use crate::clarity_value::deserialize::TypePrefix;
 use crate::clarity_value::types::{ClarityName, ClarityValue};
-use crate::post_condition::deserialize::TransactionPostCondition;
-use crate::serialize_util::DeserializeError;
+use crate::hex::encode_hex;
+use crate::post_condition::deserialize::{AssetInfo, TransactionPostCondition};
+use crate::serialize_util::{ensure_remaining, read_uint_be, DeserializeError};
 
 pub struct StacksTransaction {
     pub version: TransactionVersion,
@@ -20,15 +22,38 @@ pub struct StacksTransaction {
 
 impl StacksTransaction {
     pub fn deserialize(fd: &mut Cursor<&[u8]>) -> Result<Self, DeserializeError> {
+        Self::deserialize_with_payload(fd, TransactionPayload::deserialize)
+    }
+
+    /// Decode a transaction with a custom payload decoder, for callers that only accept some
+    /// payloads or need to check them before they are read.
+    fn deserialize_with_payload<F>(
+        fd: &mut Cursor<&[u8]>,
+        decode_payload: F,
+    ) -> Result<Self, DeserializeError>
+    where
+        F: FnOnce(&mut Cursor<&[u8]>) -> Result<TransactionPayload, DeserializeError>,
+    {
         let version_u8 = fd.read_u8()?;
-        let chain_id: u32 = fd.read_u32::<BigEndian>()?;
+        let version = match version_u8 {
+            x if x == TransactionVersion::Mainnet as u8 => TransactionVersion::Mainnet,
+            x if x == TransactionVersion::Testnet as u8 => TransactionVersion::Testnet,
+            _ => {
+                return Err(format!(
+                    "Failed to parse transaction: invalid version byte 0x{:02x}",
+                    version_u8
+                ))?;
+            }
+        };
+        let chain_id: u32 = read_uint_be::<4>(fd)? as u32;
         let auth = TransactionAuth::deserialize(fd)?;
         let anchor_mode_u8 = fd.read_u8()?;
 
         let cursor_pos = fd.position() as usize;
         let post_condition_mode_u8 = fd.read_u8()?;
         let post_conditions: Vec<TransactionPostCondition> = {
-            let len = fd.read_u32::<BigEndian>()?;
+            let len = read_uint_be::<4>(fd)? as u32;
+            ensure_remaining(fd, len as u64)?;
             let mut results: Vec<TransactionPostCondition> = Vec::with_capacity(len as usize);
             for _ in 0..len {
                 results.push(TransactionPostCondition::deserialize(fd)?);
@@ -37,13 +62,7 @@ impl StacksTransaction {
         };
         let post_conditions_serialized = fd.get_ref()[cursor_pos..fd.position() as usize].to_vec();
 
-        let payload = TransactionPayload::deserialize(fd)?;
-
-        let version = if (version_u8 & 0x80) == 0 {
-            TransactionVersion::Mainnet
-        } else {
-            TransactionVersion::Testnet
-        };
+        let payload = decode_payload(fd)?;
 
         let anchor_mode = match anchor_mode_u8 {
             x if x == TransactionAnchorMode::OffChainOnly as u8 => {
@@ -87,6 +106,268 @@ impl StacksTransaction {
             payload,
         })
     }
+
+    /// Check the anchor mode against the payload type. Coinbase, poison-microblock and
+    /// tenure-change payloads can't be mined in a microblock, so they must be on-chain only.
+    /// A transaction failing this check parses fine, but is invalid under the consensus rules.
+    pub fn validate_anchor_mode(&self) -> Result<(), String> {
+        let payload_name = match self.payload {
+            TransactionPayload::Coinbase(_) => "coinbase",
+            TransactionPayload::PoisonMicroblock(..) => "poison-microblock",
+            TransactionPayload::TenureChange(_) => "tenure-change",
+            _ => return Ok(()),
+        };
+        if self.anchor_mode != TransactionAnchorMode::OnChainOnly {
+            return Err(format!(
+                "Invalid anchor mode {} for {} transaction, must be on-chain only ({})",
+                self.anchor_mode as u8,
+                payload_name,
+                TransactionAnchorMode::OnChainOnly as u8
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The target and arguments of a contract-call transaction.
+pub struct ContractCall {
+    pub contract_address: String,
+    pub contract_name: String,
+    pub function_name: String,
+    pub args: Vec<ClarityValue>,
+}
+
+/// Decode a serialized transaction and return its contract call, erroring if the payload is
+/// any other type.
+pub fn decode_contract_call(tx_bytes: &[u8]) -> Result<ContractCall, DeserializeError> {
+    let tx = StacksTransaction::deserialize(&mut Cursor::new(tx_bytes))?;
+    match tx.payload {
+        TransactionPayload::ContractCall(contract_call) => {
+            let contract_address = c32_address(
+                contract_call.address.version,
+                &contract_call.address.hash160_bytes,
+            )?;
+            Ok(ContractCall {
+                contract_address,
+                contract_name: contract_call.contract_name.into(),
+                function_name: contract_call.function_name.into(),
+                args: contract_call.function_args,
+            })
+        }
+        _ => Err("Transaction payload is not a contract call".into()),
+    }
+}
+
+/// Default limit on the source length returned by `decode_contract_source`.
+pub const DEFAULT_MAX_CONTRACT_SOURCE_LEN: usize = 1024 * 1024;
+
+/// The contract name, source code and Clarity version of a contract deploy transaction, erroring
+/// if the payload is any other type or the source is longer than
+/// `DEFAULT_MAX_CONTRACT_SOURCE_LEN`. The Clarity version is `None` for the unversioned smart
+/// contract payload and the version byte for a versioned one.
+pub fn decode_contract_source(
+    tx_bytes: &[u8],
+) -> Result<(String, String, Option<u8>), DeserializeError> {
+    decode_contract_source_with_limit(tx_bytes, DEFAULT_MAX_CONTRACT_SOURCE_LEN)
+}
+
+/// Same as `decode_contract_source`, with a caller provided limit on the source length. The
+/// declared length is checked before the source is read.
+pub fn decode_contract_source_with_limit(
+    tx_bytes: &[u8],
+    max_source_len: usize,
+) -> Result<(String, String, Option<u8>), DeserializeError> {
+    let tx = StacksTransaction::deserialize_with_payload(&mut Cursor::new(tx_bytes), |fd| {
+        let clarity_version = match fd.read_u8()? {
+            x if x == TransactionPayloadID::SmartContract as u8 => None,
+            x if x == TransactionPayloadID::VersionedSmartContract as u8 => Some(fd.read_u8()?),
+            _ => return Err("Transaction payload is not a smart contract".into()),
+        };
+        let name = ClarityName::deserialize(fd)?;
+        let source_len = read_uint_be::<4>(fd)? as usize;
+        if source_len > max_source_len {
+            return Err(format!(
+                "Contract source length {} exceeds the limit of {}",
+                source_len, max_source_len
+            )
+            .into());
+        }
+        ensure_remaining(fd, source_len as u64)?;
+        let mut code_body = vec![0u8; source_len];
+        fd.read_exact(&mut code_body)?;
+        let smart_contract = TransactionSmartContract {
+            name,
+            code_body: StacksString(code_body),
+        };
+        Ok(match clarity_version {
+            Some(version) => TransactionPayload::VersionedSmartContract(version, smart_contract),
+            None => TransactionPayload::SmartContract(smart_contract),
+        })
+    })?;
+    let (smart_contract, clarity_version) = match tx.payload {
+        TransactionPayload::SmartContract(smart_contract) => (smart_contract, None),
+        TransactionPayload::VersionedSmartContract(version, smart_contract) => {
+            (smart_contract, Some(version))
+        }
+        _ => unreachable!(),
+    };
+    let source = String::from_utf8(smart_contract.code_body.0)
+        .map_err(|e| format!("Contract source is not valid UTF-8: {}", e))?;
+    Ok((smart_contract.name.into(), source, clarity_version))
+}
+
+/// The fields of a tenure-change transaction that reorg analysis needs, with hashes hex encoded.
+pub struct TenureChangeSummary {
+    pub tenure_consensus_hash: String,
+    pub prev_tenure_consensus_hash: String,
+    pub burn_view_consensus_hash: String,
+    pub prev_tenure_end_block: String,
+    pub prev_tenure_blocks: u32,
+    pub cause: TenureChangeCause,
+}
+
+/// Decode a serialized transaction and summarize its tenure change, erroring if the payload is
+/// any other type.
+pub fn decode_tenure_change_summary(
+    tx_bytes: &[u8],
+) -> Result<TenureChangeSummary, DeserializeError> {
+    let tx = StacksTransaction::deserialize(&mut Cursor::new(tx_bytes))?;
+    match tx.payload {
+        TransactionPayload::TenureChange(tenure_change) => Ok(TenureChangeSummary {
+            tenure_consensus_hash: encode_hex(&tenure_change.tenure_consensus_hash.0).into(),
+            prev_tenure_consensus_hash: encode_hex(&tenure_change.prev_tenure_consensus_hash.0)
+                .into(),
+            burn_view_consensus_hash: encode_hex(&tenure_change.burn_view_consensus_hash.0).into(),
+            prev_tenure_end_block: encode_hex(&tenure_change.previous_tenure_end.0).into(),
+            prev_tenure_blocks: tenure_change.previous_tenure_blocks,
+            cause: tenure_change.cause,
+        }),
+        _ => Err("Transaction payload is not a tenure change".into()),
+    }
+}
+
+/// The hex encoded 65 byte recoverable signatures in a serialized transaction's spending
+/// conditions: the origin's, then the sponsor's. Multisig conditions contribute each of their
+/// signature auth fields in order.
+pub fn transaction_signatures(tx_bytes: &[u8]) -> Result<Vec<String>, DeserializeError> {
+    let tx = StacksTransaction::deserialize(&mut Cursor::new(tx_bytes))?;
+    let conditions = match &tx.auth {
+        TransactionAuth::Standard(origin) => vec![origin],
+        TransactionAuth::Sponsored(origin, sponsor) => vec![origin, sponsor],
+    };
+    let mut signatures = Vec::new();
+    for condition in conditions {
+        match condition {
+            TransactionSpendingCondition::Singlesig(singlesig) => {
+                signatures.push(encode_hex(&singlesig.signature.0).into());
+            }
+            TransactionSpendingCondition::Multisig(multisig) => {
+                for field in &multisig.fields {
+                    if let TransactionAuthField::Signature(_, signature) = field {
+                        signatures.push(encode_hex(&signature.0).into());
+                    }
+                }
+            }
+        }
+    }
+    Ok(signatures)
+}
+
+/// True if a serialized transaction's post condition mode is `Allow`, i.e. it may move assets
+/// beyond what its post conditions list. Wallets use this to warn that a transaction doesn't
+/// restrict token movement.
+pub fn transaction_has_open_post_conditions(tx_bytes: &[u8]) -> Result<bool, DeserializeError> {
+    let tx = StacksTransaction::deserialize(&mut Cursor::new(tx_bytes))?;
+    Ok(matches!(
+        tx.post_condition_mode,
+        TransactionPostConditionMode::Allow
+    ))
+}
+
+/// The post condition mode of a transaction and how many post conditions it lists.
+#[derive(Copy, Clone)]
+pub struct PostConditionSummary {
+    pub mode: TransactionPostConditionMode,
+    pub count: usize,
+}
+
+impl PostConditionSummary {
+    /// True for `Deny` mode with no post conditions, meaning the transaction may not move any
+    /// tokens at all. In `Allow` mode an empty list means the opposite, no restriction.
+    pub fn denies_all_transfers(&self) -> bool {
+        matches!(self.mode, TransactionPostConditionMode::Deny) && self.count == 0
+    }
+}
+
+/// The post condition mode and count of a serialized transaction.
+pub fn post_condition_summary(tx_bytes: &[u8]) -> Result<PostConditionSummary, DeserializeError> {
+    let tx = StacksTransaction::deserialize(&mut Cursor::new(tx_bytes))?;
+    Ok(PostConditionSummary {
+        mode: tx.post_condition_mode,
+        count: tx.post_conditions.len(),
+    })
+}
+
+/// Which assets a transaction's post conditions guard, see `post_condition_coverage`.
+pub struct CoverageReport {
+    pub mode: TransactionPostConditionMode,
+    /// True if any post condition is on STX.
+    pub stx: bool,
+    /// Asset identifiers, e.g. `SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-token::my-token`,
+    /// of the fungible tokens with a post condition, in first-seen order without duplicates.
+    pub fungible: Vec<String>,
+    /// Asset identifiers of the non-fungible tokens with a post condition, likewise.
+    pub nonfungible: Vec<String>,
+}
+
+impl CoverageReport {
+    /// True if `asset` has a post condition, where `asset` is `stx` or an asset identifier.
+    pub fn covers(&self, asset: &str) -> bool {
+        if asset == "stx" {
+            return self.stx;
+        }
+        self.fungible.iter().any(|id| id == asset) || self.nonfungible.iter().any(|id| id == asset)
+    }
+}
+
+/// The assets guarded by at least one post condition of a serialized transaction. Any other
+/// asset the transaction moves is unguarded, which in `Allow` mode means it can move freely.
+pub fn post_condition_coverage(tx_bytes: &[u8]) -> Result<CoverageReport, DeserializeError> {
+    let tx = StacksTransaction::deserialize(&mut Cursor::new(tx_bytes))?;
+    let mut report = CoverageReport {
+        mode: tx.post_condition_mode,
+        stx: false,
+        fungible: vec![],
+        nonfungible: vec![],
+    };
+    for post_condition in &tx.post_conditions {
+        let (ids, asset) = match post_condition {
+            TransactionPostCondition::STX(..) => {
+                report.stx = true;
+                continue;
+            }
+            TransactionPostCondition::Fungible(_, asset, ..) => (&mut report.fungible, asset),
+            TransactionPostCondition::Nonfungible(_, asset, ..) => (&mut report.nonfungible, asset),
+        };
+        let id = asset_identifier(asset)?;
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    Ok(report)
+}
+
+fn asset_identifier(asset: &AssetInfo) -> Result<String, String> {
+    let contract_address = c32_address(
+        asset.contract_address.version,
+        &asset.contract_address.hash160_bytes,
+    )?;
+    Ok(format!(
+        "{}.{}::{}",
+        contract_address,
+        asset.contract_name.as_str(),
+        asset.asset_name.as_str()
+    ))
 }
 
 impl TransactionAuth {
@@ -147,8 +428,8 @@ impl SinglesigSpendingCondition {
         let mut signer = [0u8; 20];
         fd.read_exact(&mut signer)?;
 
-        let nonce = fd.read_u64::<BigEndian>()?;
-        let tx_fee = fd.read_u64::<BigEndian>()?;
+        let nonce = read_uint_be::<8>(fd)? as u64;
+        let tx_fee = read_uint_be::<8>(fd)? as u64;
 
         let key_encoding_u8 = fd.read_u8()?;
         let key_encoding =
@@ -189,10 +470,11 @@ impl MultisigSpendingCondition {
 
         let mut signer = [0u8; 20];
         fd.read_exact(&mut signer)?;
-        let nonce = fd.read_u64::<BigEndian>()?;
-        let tx_fee = fd.read_u64::<BigEndian>()?;
+        let nonce = read_uint_be::<8>(fd)? as u64;
+        let tx_fee = read_uint_be::<8>(fd)? as u64;
         let fields: Vec<TransactionAuthField> = {
-            let len = fd.read_u32::<BigEndian>()?;
+            let len = read_uint_be::<4>(fd)? as u32;
+            ensure_remaining(fd, len as u64)?;
             let mut results: Vec<TransactionAuthField> = Vec::with_capacity(len as usize);
             for _ in 0..len {
                 results.push(TransactionAuthField::deserialize(fd)?);
@@ -200,7 +482,7 @@ impl MultisigSpendingCondition {
             results
         };
 
-        let signatures_required = fd.read_u16::<BigEndian>()?;
+        let signatures_required = read_uint_be::<2>(fd)? as u16;
 
         // read and decode _exactly_ num_signatures signature buffers
         let mut num_sigs_given: u16 = 0;
@@ -334,7 +616,7 @@ impl TransactionPayload {
         let payload = match type_id {
             x if x == TransactionPayloadID::TokenTransfer as u8 => {
                 let principal = PrincipalData::deserialize(fd)?;
-                let amount = fd.read_u64::<BigEndian>()?;
+                let amount = read_uint_be::<8>(fd)? as u64;
                 let mut memo_bytes = [0u8; 34];
                 fd.read_exact(&mut memo_bytes)?;
                 let memo = TokenTransferMemo(memo_bytes);
@@ -348,6 +630,11 @@ impl TransactionPayload {
                 let payload = TransactionSmartContract::deserialize(fd)?;
                 TransactionPayload::SmartContract(payload)
             }
+            x if x == TransactionPayloadID::VersionedSmartContract as u8 => {
+                let clarity_version = fd.read_u8()?;
+                let payload = TransactionSmartContract::deserialize(fd)?;
+                TransactionPayload::VersionedSmartContract(clarity_version, payload)
+            }
             x if x == TransactionPayloadID::PoisonMicroblock as u8 => {
                 let h1 = StacksMicroblockHeader::deserialize(fd)?;
                 let h2 = StacksMicroblockHeader::deserialize(fd)?;
@@ -359,6 +646,10 @@ impl TransactionPayload {
                 let payload = CoinbasePayload(payload_bytes);
                 TransactionPayload::Coinbase(payload)
             }
+            x if x == TransactionPayloadID::TenureChange as u8 => {
+                let payload = TransactionTenureChange::deserialize(fd)?;
+                TransactionPayload::TenureChange(payload)
+            }
             _ => {
                 return Err(format!(
                     "Failed to parse transaction -- unknown payload ID {}",
@@ -377,7 +668,8 @@ impl TransactionContractCall {
         let contract_name = ClarityName::deserialize(fd)?;
         let function_name = ClarityName::deserialize(fd)?;
         let function_args: Vec<ClarityValue> = {
-            let len = fd.read_u32::<BigEndian>()?;
+            let len = read_uint_be::<4>(fd)? as u32;
+            ensure_remaining(fd, len as u64)?;
             let mut results: Vec<ClarityValue> = Vec::with_capacity(len as usize);
             for _ in 0..len {
                 results.push(ClarityValue::deserialize(fd, true)?);
@@ -402,12 +694,59 @@ impl TransactionSmartContract {
     }
 }
 
+impl TransactionTenureChange {
+    pub fn deserialize(fd: &mut Cursor<&[u8]>) -> Result<Self, DeserializeError> {
+        let mut tenure_consensus_hash = [0u8; 20];
+        fd.read_exact(&mut tenure_consensus_hash)?;
+
+        let mut prev_tenure_consensus_hash = [0u8; 20];
+        fd.read_exact(&mut prev_tenure_consensus_hash)?;
+
+        let mut burn_view_consensus_hash = [0u8; 20];
+        fd.read_exact(&mut burn_view_consensus_hash)?;
+
+        let mut previous_tenure_end = [0u8; 32];
+        fd.read_exact(&mut previous_tenure_end)?;
+
+        let previous_tenure_blocks = read_uint_be::<4>(fd)? as u32;
+
+        let cause_u8 = fd.read_u8()?;
+        let cause = TenureChangeCause::from_u8(cause_u8).ok_or(format!(
+            "Failed to parse tenure change: unknown cause {}",
+            cause_u8
+        ))?;
+
+        let mut pubkey_hash = [0u8; 20];
+        fd.read_exact(&mut pubkey_hash)?;
+
+        Ok(TransactionTenureChange {
+            tenure_consensus_hash: ConsensusHash(tenure_consensus_hash),
+            prev_tenure_consensus_hash: ConsensusHash(prev_tenure_consensus_hash),
+            burn_view_consensus_hash: ConsensusHash(burn_view_consensus_hash),
+            previous_tenure_end: StacksBlockId(previous_tenure_end),
+            previous_tenure_blocks,
+            cause,
+            pubkey_hash: Hash160(pubkey_hash),
+        })
+    }
+}
+
+impl TenureChangeCause {
+    pub fn from_u8(n: u8) -> Option<TenureChangeCause> {
+        match n {
+            x if x == TenureChangeCause::BlockFound as u8 => Some(TenureChangeCause::BlockFound),
+            x if x == TenureChangeCause::Extended as u8 => Some(TenureChangeCause::Extended),
+            _ => None,
+        }
+    }
+}
+
 impl StacksMicroblockHeader {
     pub fn deserialize(fd: &mut Cursor<&[u8]>) -> Result<Self, DeserializeError> {
         let cursor_pos = fd.position() as usize;
 
         let version = fd.read_u8()?;
-        let sequence = fd.read_u16::<BigEndian>()?;
+        let sequence = read_uint_be::<2>(fd)? as u16;
 
         let mut prev_block_bytes = [0u8; 32];
         fd.read_exact(&mut prev_block_bytes)?;
@@ -436,7 +775,8 @@ impl StacksMicroblockHeader {
 
 impl StacksString {
     pub fn deserialize(fd: &mut Cursor<&[u8]>) -> Result<Self, DeserializeError> {
-        let len = fd.read_u32::<BigEndian>()?;
+        let len = read_uint_be::<4>(fd)? as u32;
+        ensure_remaining(fd, len as u64)?;
         let mut bytes: Vec<u8> = vec![0u8; len as usize];
         fd.read_exact(&mut bytes)?;
         Ok(StacksString(bytes))
@@ -454,14 +794,9 @@ impl PrincipalData {
             TypePrefix::PrincipalStandard => Ok(PrincipalData::Standard(
                 StandardPrincipalData::deserialize(fd)?,
             )),
-            TypePrefix::PrincipalContract => {
-                let issuer = StandardPrincipalData::deserialize(fd)?;
-                let name = ClarityName::deserialize(fd)?;
-                Ok(PrincipalData::Contract(QualifiedContractIdentifier {
-                    issuer,
-                    name,
-                }))
-            }
+            TypePrefix::PrincipalContract => Ok(PrincipalData::Contract(
+                QualifiedContractIdentifier::deserialize(fd)?,
+            )),
             _ => Err("Bad principal prefix".into()),
         }
     }
@@ -477,6 +812,14 @@ impl StandardPrincipalData {
     }
 }
 
+impl QualifiedContractIdentifier {
+    pub fn deserialize(fd: &mut Cursor<&[u8]>) -> Result<Self, DeserializeError> {
+        let issuer = StandardPrincipalData::deserialize(fd)?;
+        let name = ClarityName::deserialize_contract_name(fd)?;
+        Ok(QualifiedContractIdentifier { issuer, name })
+    }
+}
+
 #[repr(u8)]
 #[derive(Copy, Clone)]
 pub enum TransactionVersion {
@@ -484,6 +827,25 @@ pub enum TransactionVersion {
     Testnet = 0x80,
 }
 
+pub const CHAIN_ID_MAINNET: u32 = 0x00000001;
+pub const CHAIN_ID_TESTNET: u32 = 0x80000000;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum NetworkId {
+    Mainnet,
+    Testnet,
+    /// A devnet or other network with a non-standard chain ID.
+    Custom(u32),
+}
+
+pub fn chain_id_network(chain_id: u32) -> NetworkId {
+    match chain_id {
+        CHAIN_ID_MAINNET => NetworkId::Mainnet,
+        CHAIN_ID_TESTNET => NetworkId::Testnet,
+        _ => NetworkId::Custom(chain_id),
+    }
+}
+
 #[repr(u8)]
 #[derive(PartialEq, Copy, Clone)]
 pub enum TransactionAnchorMode {
@@ -572,13 +934,16 @@ pub enum TransactionAuthFieldID {
 }
 
 #[repr(u8)]
-#[derive(PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Copy, Clone)]
 pub enum TransactionPublicKeyEncoding {
     // ways we can encode a public key
     Compressed = 0x00,
     Uncompressed = 0x01,
 }
 
+/// Whether a singlesig spending condition's signer public key is compressed or uncompressed.
+pub type KeyEncoding = TransactionPublicKeyEncoding;
+
 #[repr(u8)]
 #[derive(PartialEq)]
 pub enum TransactionPayloadID {
@@ -587,18 +952,46 @@ pub enum TransactionPayloadID {
     ContractCall = 2,
     PoisonMicroblock = 3,
     Coinbase = 4,
+    VersionedSmartContract = 6,
+    TenureChange = 7,
 }
 
 pub enum TransactionPayload {
     TokenTransfer(PrincipalData, u64, TokenTransferMemo),
     ContractCall(TransactionContractCall),
     SmartContract(TransactionSmartContract),
+    /// A smart contract deploy pinned to a Clarity version, e.g. `2` for Clarity 2.
+    VersionedSmartContract(u8, TransactionSmartContract),
     PoisonMicroblock(StacksMicroblockHeader, StacksMicroblockHeader),
     Coinbase(CoinbasePayload),
+    TenureChange(TransactionTenureChange),
 }
 
 pub struct CoinbasePayload(pub [u8; 32]);
 
+#[repr(u8)]
+#[derive(PartialEq, Copy, Clone)]
+pub enum TenureChangeCause {
+    BlockFound = 0x00, // a valid winning block-commit, the tenure starts
+    Extended = 0x01,   // the current tenure's execution budget is extended
+}
+
+pub struct ConsensusHash(pub [u8; 20]);
+
+pub struct StacksBlockId(pub [u8; 32]);
+
+pub struct Hash160(pub [u8; 20]);
+
+pub struct TransactionTenureChange {
+    pub tenure_consensus_hash: ConsensusHash,
+    pub prev_tenure_consensus_hash: ConsensusHash,
+    pub burn_view_consensus_hash: ConsensusHash,
+    pub previous_tenure_end: StacksBlockId,
+    pub previous_tenure_blocks: u32,
+    pub cause: TenureChangeCause,
+    pub pubkey_hash: Hash160,
+}
+
 pub struct TransactionSmartContract {
     pub name: ClarityName,
     pub code_body: StacksString,
@@ -645,6 +1038,28 @@ mod tests {
     use super::*;
     use crate::hex::decode_hex;
 
+    /// Singlesig token transfer, anchor mode any, post condition mode deny, no post conditions.
+    const TOKEN_TRANSFER_TX: &str = "0x80800000000400aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa000000000000000500000000000000c80011111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111110302000000000005161111111111111111111111111111111111111111000000000000007b00000000000000000000000000000000000000000000000000000000000000000000";
+    /// Offset of the payload in `TOKEN_TRANSFER_TX`, after the 4 byte empty post condition list.
+    const TOKEN_TRANSFER_PAYLOAD_OFFSET: usize = 115;
+    /// `TOKEN_TRANSFER_TX` with a block-found tenure change payload. Synthetic, the auth and the
+    /// filler hashes aren't from a chain transaction.
+    // TODO: replace with a captured Nakamoto tenure-change transaction
+    const TENURE_CHANGE_TX: &str = "0x80800000000400aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa000000000000000500000000000000c80011111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111110102000000000701010101010101010101010101010101010101010202020202020202020202020202020202020202030303030303030303030303030303030303030304040404040404040404040404040404040404040404040404040404040404040000000a000505050505050505050505050505050505050505";
+
+    /// `TOKEN_TRANSFER_TX` with the given serialized post conditions and payload, keeping the
+    /// token transfer payload if `payload` is `None`.
+    fn splice_token_transfer(post_conditions: &[Vec<u8>], payload: Option<&[u8]>) -> Vec<u8> {
+        let bytes = decode_hex(TOKEN_TRANSFER_TX).unwrap();
+        let mut spliced = bytes[..TOKEN_TRANSFER_PAYLOAD_OFFSET - 4].to_vec();
+        spliced.extend_from_slice(&(post_conditions.len() as u32).to_be_bytes());
+        for post_condition in post_conditions {
+            spliced.extend_from_slice(post_condition);
+        }
+        spliced.extend_from_slice(payload.unwrap_or(&bytes[TOKEN_TRANSFER_PAYLOAD_OFFSET..]));
+        spliced
+    }
+
     #[test]
     fn test_decode_bug() {
         let input = b"0x00000000010400982f3ec112a5f5928a5c96a914bd733793b896a5000000000000053000000000000002290000c85889dad0d5b08a997a93a28a7c93eb22c324e5f8992dc93e37865ef4f3e0d65383beefeffc4871a2facbc4b590ddf887c80de6638ed4e2ec0e633d1e130f230301000000000216982f3ec112a5f5928a5c96a914bd733793b896a51861726b6164696b6f2d676f7665726e616e63652d76332d310770726f706f7365000000060616982f3ec112a5f5928a5c96a914bd733793b896a51d61726b6164696b6f2d7374616b652d706f6f6c2d64696b6f2d76312d32010000000000000000000000000000ef8801000000000000000000000000000003f00e00000028414950313020557064617465204c54567320616e64204c69717569646174696f6e20526174696f730e0000003168747470733a2f2f6769746875622e636f6d2f61726b6164696b6f2d64616f2f61726b6164696b6f2f70756c6c2f3439330b000000010c0000000507616464726573730516982f3ec112a5f5928a5c96a914bd733793b896a50863616e2d6275726e040863616e2d6d696e7404046e616d650d0000002b61697031302d61726b6164696b6f2d7570646174652d74766c2d6c69717569646174696f6e2d726174696f0e7175616c69666965642d6e616d650616982f3ec112a5f5928a5c96a914bd733793b896a52b61697031302d61726b6164696b6f2d7570646174652d74766c2d6c69717569646174696f6e2d726174696f";
@@ -653,4 +1068,374 @@ mod tests {
         let tx = StacksTransaction::deserialize(&mut cursor);
         assert!(tx.is_ok());
     }
+
+    #[test]
+    fn test_transaction_signatures() {
+        let singlesig = "0x00000000010400982f3ec112a5f5928a5c96a914bd733793b896a5000000000000053000000000000002290000c85889dad0d5b08a997a93a28a7c93eb22c324e5f8992dc93e37865ef4f3e0d65383beefeffc4871a2facbc4b590ddf887c80de6638ed4e2ec0e633d1e130f230301000000000216982f3ec112a5f5928a5c96a914bd733793b896a51861726b6164696b6f2d676f7665726e616e63652d76332d310770726f706f7365000000060616982f3ec112a5f5928a5c96a914bd733793b896a51d61726b6164696b6f2d7374616b652d706f6f6c2d64696b6f2d76312d32010000000000000000000000000000ef8801000000000000000000000000000003f00e00000028414950313020557064617465204c54567320616e64204c69717569646174696f6e20526174696f730e0000003168747470733a2f2f6769746875622e636f6d2f61726b6164696b6f2d64616f2f61726b6164696b6f2f70756c6c2f3439330b000000010c0000000507616464726573730516982f3ec112a5f5928a5c96a914bd733793b896a50863616e2d6275726e040863616e2d6d696e7404046e616d650d0000002b61697031302d61726b6164696b6f2d7570646174652d74766c2d6c69717569646174696f6e2d726174696f0e7175616c69666965642d6e616d650616982f3ec112a5f5928a5c96a914bd733793b896a52b61697031302d61726b6164696b6f2d7570646174652d74766c2d6c69717569646174696f6e2d726174696f";
+        assert_eq!(
+            transaction_signatures(&decode_hex(singlesig).unwrap()).unwrap(),
+            vec!["0x00c85889dad0d5b08a997a93a28a7c93eb22c324e5f8992dc93e37865ef4f3e0d65383beefeffc4871a2facbc4b590ddf887c80de6638ed4e2ec0e633d1e130f23"]
+        );
+
+        // 2-of-3 multisig with signature, public key, signature auth fields
+        let multisig = "0x00000000010401eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee000000000000000100000000000000c80000000302a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a10002bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb02a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a200020302000000000005161111111111111111111111111111111111111111000000000000007b00000000000000000000000000000000000000000000000000000000000000000000";
+        let signatures = transaction_signatures(&decode_hex(multisig).unwrap()).unwrap();
+        assert_eq!(
+            signatures,
+            vec![
+                format!("0x{}", "a1".repeat(65)),
+                format!("0x{}", "a2".repeat(65))
+            ]
+        );
+
+        assert!(transaction_signatures(&decode_hex(multisig).unwrap()[..100]).is_err());
+    }
+
+    #[test]
+    fn test_validate_anchor_mode() {
+        let anchor_mode_offset = 109;
+        let bytes = decode_hex(TOKEN_TRANSFER_TX).unwrap();
+        assert_eq!(bytes[anchor_mode_offset], TransactionAnchorMode::Any as u8);
+        let tx = StacksTransaction::deserialize(&mut Cursor::new(bytes.as_ref())).unwrap();
+        assert!(tx.validate_anchor_mode().is_ok());
+
+        // same transaction with a coinbase payload
+        let mut coinbase = vec![TransactionPayloadID::Coinbase as u8];
+        coinbase.extend_from_slice(&[0x09; 32]);
+        let mut bytes = splice_token_transfer(&[], Some(&coinbase));
+        for (anchor_mode, valid) in [
+            (TransactionAnchorMode::OnChainOnly, true),
+            (TransactionAnchorMode::OffChainOnly, false),
+            (TransactionAnchorMode::Any, false),
+        ]
+        .iter()
+        {
+            bytes[anchor_mode_offset] = *anchor_mode as u8;
+            // the transaction parses either way
+            let tx = StacksTransaction::deserialize(&mut Cursor::new(bytes.as_ref())).unwrap();
+            assert_eq!(tx.validate_anchor_mode().is_ok(), *valid);
+        }
+        let tx = StacksTransaction::deserialize(&mut Cursor::new(bytes.as_ref())).unwrap();
+        assert_eq!(
+            tx.validate_anchor_mode().unwrap_err(),
+            "Invalid anchor mode 3 for coinbase transaction, must be on-chain only (1)"
+        );
+    }
+
+    #[test]
+    fn test_transaction_has_open_post_conditions() {
+        let post_condition_mode_offset = 110;
+        let mut bytes = decode_hex(TOKEN_TRANSFER_TX).unwrap().to_vec();
+        assert_eq!(
+            bytes[post_condition_mode_offset],
+            TransactionPostConditionMode::Deny as u8
+        );
+        assert!(!transaction_has_open_post_conditions(&bytes).unwrap());
+
+        bytes[post_condition_mode_offset] = TransactionPostConditionMode::Allow as u8;
+        assert!(transaction_has_open_post_conditions(&bytes).unwrap());
+
+        bytes[post_condition_mode_offset] = 0x03;
+        assert!(transaction_has_open_post_conditions(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_post_condition_summary() {
+        let post_condition_mode_offset = 110;
+        let mut bytes = decode_hex(TOKEN_TRANSFER_TX).unwrap().to_vec();
+        let summary = post_condition_summary(&bytes).unwrap();
+        assert!(matches!(summary.mode, TransactionPostConditionMode::Deny));
+        assert_eq!(summary.count, 0);
+        assert!(summary.denies_all_transfers());
+
+        bytes[post_condition_mode_offset] = TransactionPostConditionMode::Allow as u8;
+        let summary = post_condition_summary(&bytes).unwrap();
+        assert!(matches!(summary.mode, TransactionPostConditionMode::Allow));
+        assert_eq!(summary.count, 0);
+        assert!(!summary.denies_all_transfers());
+
+        // deny mode with one STX post condition: origin sends exactly 123 uSTX
+        let mut stx_condition = vec![0x00, 0x01, 0x01];
+        stx_condition.extend_from_slice(&123u64.to_be_bytes());
+        let with_condition = splice_token_transfer(&[stx_condition], None);
+        let summary = post_condition_summary(&with_condition).unwrap();
+        assert!(matches!(summary.mode, TransactionPostConditionMode::Deny));
+        assert_eq!(summary.count, 1);
+        assert!(!summary.denies_all_transfers());
+
+        assert!(post_condition_summary(&bytes[..TOKEN_TRANSFER_PAYLOAD_OFFSET]).is_err());
+    }
+
+    #[test]
+    fn test_post_condition_coverage() {
+        let bytes = decode_hex(TOKEN_TRANSFER_TX).unwrap();
+        let report = post_condition_coverage(&bytes).unwrap();
+        assert!(matches!(report.mode, TransactionPostConditionMode::Deny));
+        assert!(!report.stx);
+        assert!(report.fungible.is_empty() && report.nonfungible.is_empty());
+
+        // the same transaction with two post conditions on one fungible token and one on an NFT,
+        // but none on STX
+        let contract = "16a46ff88886c2ef9762d970b4d2c63678835bd39d09746f6b656e732d7631";
+        let fungible = decode_hex(format!("0101{}05746f6b656e010000000000000064", contract))
+            .unwrap()
+            .to_vec();
+        let nonfungible = decode_hex(format!(
+            "0201{}036e6674010000000000000000000000000000000110",
+            contract
+        ))
+        .unwrap()
+        .to_vec();
+        let with_conditions =
+            splice_token_transfer(&[fungible.clone(), fungible, nonfungible], None);
+
+        let report = post_condition_coverage(&with_conditions).unwrap();
+        assert!(!report.stx);
+        assert_eq!(
+            report.fungible,
+            vec!["SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.tokens-v1::token"]
+        );
+        assert_eq!(
+            report.nonfungible,
+            vec!["SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.tokens-v1::nft"]
+        );
+        assert!(report.covers("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.tokens-v1::token"));
+        assert!(report.covers("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.tokens-v1::nft"));
+        assert!(!report.covers("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.tokens-v1::other"));
+        // the transfer moves STX without a covering post condition
+        assert!(!report.covers("stx"));
+    }
+
+    #[test]
+    fn test_decode_contract_source() {
+        assert_eq!(
+            decode_contract_source(&decode_hex(TOKEN_TRANSFER_TX).unwrap())
+                .err()
+                .unwrap()
+                .to_string(),
+            "Transaction payload is not a smart contract"
+        );
+
+        // mainnet deploy of SP277HZA8AGXV42MZKDW5B2NNN61RHQ42MTAHVNB1.frozen-stacks-pops-v1,
+        // tx 0xd2e36176900e65b01684d651f8faa2794a835a05173a3f0596600bf53f6d4e58
+        let input = "0x000000000104008e78fd48543bb20a9f9b78558ab5a98388dc82a600000000000000ff00000000000b71b000009539c9b4514276db6376d2525251446719e884b0a996ac9018815fe9043fcc751798f7f02ea01c1d1673d163ab18bd048da828bc2f1716666b54ea93370d4031030100000000011566726f7a656e2d737461636b732d706f70732d763100000b4428646566696e652d6e6f6e2d66756e6769626c652d746f6b656e2066726f7a656e2d737461636b732d706f70732075696e74290a0a3b3b2053746f726167650a28646566696e652d6d617020746f6b656e2d636f756e74207072696e636970616c2075696e74290a0a3b3b20446566696e6520436f6e7374616e74730a28646566696e652d636f6e7374616e7420434f4e54524143542d4f574e45522074782d73656e646572290a28646566696e652d636f6e7374616e74204552522d4e4f542d415554484f52495a45442028657272207534303129290a28646566696e652d636f6e7374616e74204552522d4d455441444154412d46524f5a454e2028657272207535303529290a28646566696e652d636f6e7374616e74204552522d4d494e542d414c52454144592d5345542028657272207535303629290a0a3b3b20446566696e65205661726961626c65730a28646566696e652d646174612d766172206c6173742d69642075696e74207530290a28646566696e652d646174612d766172206d657461646174612d66726f7a656e20626f6f6c2066616c7365290a28646566696e652d646174612d76617220626173652d7572692028737472696e672d617363696920323536292022697066733a2f2f516d617948436f593235656e7234586d4251787956464b535539746b52507936344a79774e4444614b3963384d542f66726f7a656e2d737461636b732d706f70732d7b69647d2e6a736f6e22290a28646566696e652d636f6e7374616e7420636f6e74726163742d7572692022697066733a2f2f516d617948436f593235656e7234586d4251787956464b535539746b52507936344a79774e4444614b3963384d542f66726f7a656e2d737461636b732d706f70732e6a736f6e22290a28646566696e652d6d6170206d696e742d6164647265737320626f6f6c207072696e636970616c290a0a3b3b20546f6b656e20636f756e7420666f72206163636f756e740a28646566696e652d726561642d6f6e6c7920286765742d62616c616e636520286163636f756e74207072696e636970616c29290a20202864656661756c742d746f2075300a20202020286d61702d6765743f20746f6b656e2d636f756e74206163636f756e742929290a0a3b3b205349503030393a205472616e7366657220746f6b656e20746f206120737065636966696564207072696e636970616c0a28646566696e652d7075626c696320287472616e73666572202869642075696e7429202873656e646572207072696e636970616c292028726563697069656e74207072696e636970616c29290a20204552522d4e4f542d415554484f52495a4544290a0a3b3b205349503030393a2047657420746865206f776e6572206f66207468652073706563696669656420746f6b656e2049440a28646566696e652d726561642d6f6e6c7920286765742d6f776e6572202869642075696e7429290a20203b3b204d616b65207375726520746f207265706c6163652066726f7a656e2d737461636b732d706f70730a2020286f6b20286e66742d6765742d6f776e65723f2066726f7a656e2d737461636b732d706f70732069642929290a0a3b3b205349503030393a2047657420746865206c61737420746f6b656e2049440a28646566696e652d726561642d6f6e6c7920286765742d6c6173742d746f6b656e2d6964290a2020286f6b20287661722d676574206c6173742d69642929290a0a3b3b205349503030393a204765742074686520746f6b656e205552492e20596f752063616e2073657420697420746f20616e79206f74686572205552490a28646566696e652d726561642d6f6e6c7920286765742d746f6b656e2d757269202869642075696e7429290a2020286f6b2028736f6d6520287661722d67657420626173652d757269292929290a0a28646566696e652d726561642d6f6e6c7920286765742d636f6e74726163742d757269290a2020286f6b20636f6e74726163742d75726929290a0a3b3b204d696e74206e6577204e46540a3b3b2063616e206f6e6c792062652063616c6c65642066726f6d20746865204d696e740a28646566696e652d7075626c696320286d696e7420286e65772d6f776e6572207072696e636970616c29202869642075696e7429290a2020286c6574200a2020202028286f776e65722d62616c616e636520286765742d62616c616e6365206e65772d6f776e65722929290a20202020286173736572747321202863616c6c65642d66726f6d2d6d696e7429204552522d4e4f542d415554484f52495a4544290a20202020287472792120286e66742d6d696e743f2066726f7a656e2d737461636b732d706f7073206964206e65772d6f776e657229290a20202020286d61702d73657420746f6b656e2d636f756e740a2020202020206e65772d6f776e65720a202020202020282b206f776e65722d62616c616e636520753129290a20202020286f6b2074727565290a2020290a290a0a28646566696e652d7075626c696320286275726e202869642075696e742920286f776e6572207072696e636970616c29290a2020286c6574200a2020202028286f776e65722d62616c616e636520286765742d62616c616e6365206f776e65722929290a20202020286173736572747321202863616c6c65642d66726f6d2d6d696e7429204552522d4e4f542d415554484f52495a4544290a20202020287472792120286e66742d6275726e3f2066726f7a656e2d737461636b732d706f7073206964206f776e657229290a20202020286d61702d73657420746f6b656e2d636f756e740a2020202020206f776e65720a202020202020282d206f776e65722d62616c616e636520753129290a20202020286f6b2074727565290a2020290a290a0a3b3b205365742062617365207572690a28646566696e652d7075626c696320287365742d626173652d75726920286e65772d626173652d7572692028737472696e672d6173636969203235362929290a202028626567696e0a20202020286173736572747321202869732d657120636f6e74726163742d63616c6c657220434f4e54524143542d4f574e455229204552522d4e4f542d415554484f52495a4544290a2020202028617373657274732120286e6f7420287661722d676574206d657461646174612d66726f7a656e2929204552522d4d455441444154412d46524f5a454e290a20202020287661722d73657420626173652d757269206e65772d626173652d757269290a20202020286f6b20747275652929290a0a3b3b20467265657a65206d657461646174610a28646566696e652d7075626c69632028667265657a652d6d65746164617461290a202028626567696e0a20202020286173736572747321202869732d657120636f6e74726163742d63616c6c657220434f4e54524143542d4f574e455229204552522d4e4f542d415554484f52495a4544290a20202020287661722d736574206d657461646174612d66726f7a656e2074727565290a20202020286f6b20747275652929290a0a3b3b204d616e61676520746865204d696e740a28646566696e652d70726976617465202863616c6c65642d66726f6d2d6d696e74290a20202869732d657120636f6e74726163742d63616c6c65722028756e777261702120286d61702d6765743f206d696e742d616464726573732074727565292066616c73652929290a0a3b3b2063616e206f6e6c792062652063616c6c6564206f6e63650a28646566696e652d7075626c696320287365742d6d696e742d61646472657373290a202028626567696e0a2020202028617373657274732120286d61702d696e73657274206d696e742d6164647265737320747275652074782d73656e64657229204552522d4d494e542d414c52454144592d534554290a20202020286f6b20287072696e742074782d73656e64657229292929";
+        let bytes = decode_hex(input).unwrap();
+        let (name, source, clarity_version) = decode_contract_source(&bytes).unwrap();
+        assert_eq!(name, "frozen-stacks-pops-v1");
+        assert_eq!(source.len(), 2884);
+        assert!(source.starts_with("(define-non-fungible-token frozen-stacks-pops uint)\n"));
+        assert!(source.ends_with("(ok (print tx-sender))))"));
+        assert!(clarity_version.is_none());
+
+        assert!(decode_contract_source_with_limit(&bytes, 2884).is_ok());
+        let limit_err = "Contract source length 2884 exceeds the limit of 2883";
+        assert_eq!(
+            decode_contract_source_with_limit(&bytes, 2883)
+                .err()
+                .unwrap()
+                .to_string(),
+            limit_err
+        );
+        // the declared length is rejected before the source is read
+        let truncated = &bytes[..bytes.len() - 2000];
+        assert_eq!(
+            decode_contract_source_with_limit(truncated, 2883)
+                .err()
+                .unwrap()
+                .to_string(),
+            limit_err
+        );
+        assert!(decode_contract_source(truncated)
+            .err()
+            .unwrap()
+            .is_unexpected_eof());
+
+        // the same deploy as a Clarity 2 versioned payload (ID 6, then the version byte)
+        assert_eq!(bytes[TOKEN_TRANSFER_PAYLOAD_OFFSET], 1);
+        let mut versioned = bytes[..TOKEN_TRANSFER_PAYLOAD_OFFSET].to_vec();
+        versioned.extend_from_slice(&[6, 2]);
+        versioned.extend_from_slice(&bytes[TOKEN_TRANSFER_PAYLOAD_OFFSET + 1..]);
+        let (name, versioned_source, clarity_version) = decode_contract_source(&versioned).unwrap();
+        assert_eq!(name, "frozen-stacks-pops-v1");
+        assert_eq!(versioned_source, source);
+        assert_eq!(clarity_version, Some(2));
+        let tx = StacksTransaction::deserialize(&mut Cursor::new(&versioned[..])).unwrap();
+        match tx.payload {
+            TransactionPayload::VersionedSmartContract(2, smart_contract) => {
+                assert_eq!(smart_contract.name.as_str(), "frozen-stacks-pops-v1");
+                assert_eq!(smart_contract.code_body.0, source.as_bytes());
+            }
+            _ => panic!("expected a versioned smart contract payload"),
+        }
+        assert!(decode_contract_source_with_limit(&versioned, 2883).is_err());
+
+        // a deploy spliced into the token transfer
+        let code = "(define-read-only (hello) (ok \"world\"))";
+        let mut payload = vec![TransactionPayloadID::SmartContract as u8, 11];
+        payload.extend_from_slice(b"hello-world");
+        payload.extend_from_slice(&(code.len() as u32).to_be_bytes());
+        payload.extend_from_slice(code.as_bytes());
+        let (name, source, _) =
+            decode_contract_source(&splice_token_transfer(&[], Some(&payload))).unwrap();
+        assert_eq!(name, "hello-world");
+        assert_eq!(source, code);
+    }
+
+    #[test]
+    fn test_decode_key_encoding() {
+        // singlesig p2pkh, key encoding byte after the fee
+        let key_encoding_offset = 43;
+        let decode_key_encoding = |bytes: &[u8]| -> Result<KeyEncoding, DeserializeError> {
+            let tx = StacksTransaction::deserialize(&mut Cursor::new(bytes))?;
+            match tx.auth {
+                TransactionAuth::Standard(TransactionSpendingCondition::Singlesig(cond)) => {
+                    Ok(cond.key_encoding)
+                }
+                _ => panic!("expected singlesig standard auth"),
+            }
+        };
+
+        let mut bytes = decode_hex(TOKEN_TRANSFER_TX).unwrap().to_vec();
+        assert_eq!(
+            decode_key_encoding(&bytes).unwrap(),
+            KeyEncoding::Compressed
+        );
+        bytes[key_encoding_offset] = 0x01;
+        assert_eq!(
+            decode_key_encoding(&bytes).unwrap(),
+            KeyEncoding::Uncompressed
+        );
+        bytes[key_encoding_offset] = 0x02;
+        let err = decode_key_encoding(&bytes).unwrap_err();
+        assert!(
+            err.to_string().contains("unknown key encoding 2"),
+            "{}",
+            err
+        );
+
+        // p2wpkh requires a compressed key
+        bytes[key_encoding_offset] = 0x01;
+        bytes[6] = SinglesigHashMode::P2WPKH as u8;
+        assert!(decode_key_encoding(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_decode_contract_call() {
+        let input = "0x00000000010400982f3ec112a5f5928a5c96a914bd733793b896a5000000000000053000000000000002290000c85889dad0d5b08a997a93a28a7c93eb22c324e5f8992dc93e37865ef4f3e0d65383beefeffc4871a2facbc4b590ddf887c80de6638ed4e2ec0e633d1e130f230301000000000216982f3ec112a5f5928a5c96a914bd733793b896a51861726b6164696b6f2d676f7665726e616e63652d76332d310770726f706f7365000000060616982f3ec112a5f5928a5c96a914bd733793b896a51d61726b6164696b6f2d7374616b652d706f6f6c2d64696b6f2d76312d32010000000000000000000000000000ef8801000000000000000000000000000003f00e00000028414950313020557064617465204c54567320616e64204c69717569646174696f6e20526174696f730e0000003168747470733a2f2f6769746875622e636f6d2f61726b6164696b6f2d64616f2f61726b6164696b6f2f70756c6c2f3439330b000000010c0000000507616464726573730516982f3ec112a5f5928a5c96a914bd733793b896a50863616e2d6275726e040863616e2d6d696e7404046e616d650d0000002b61697031302d61726b6164696b6f2d7570646174652d74766c2d6c69717569646174696f6e2d726174696f0e7175616c69666965642d6e616d650616982f3ec112a5f5928a5c96a914bd733793b896a52b61697031302d61726b6164696b6f2d7570646174652d74766c2d6c69717569646174696f6e2d726174696f";
+        let bytes = decode_hex(input).unwrap();
+        let call = decode_contract_call(&bytes).unwrap();
+        assert_eq!(
+            call.contract_address,
+            "SP2C2YFP12AJZB4MABJBAJ55XECVS7E4PMMZ89YZR"
+        );
+        assert_eq!(call.contract_name, "arkadiko-governance-v3-1");
+        assert_eq!(call.function_name, "propose");
+        assert_eq!(call.args.len(), 6);
+        assert_eq!(call.args[1].value.repr_string(), "u61320");
+        assert!(call.args.iter().all(|arg| arg.serialized_bytes.is_some()));
+
+        let bytes = decode_hex(TENURE_CHANGE_TX).unwrap();
+        match decode_contract_call(&bytes) {
+            Err(err) => assert_eq!(
+                err.to_string(),
+                "Transaction payload is not a contract call"
+            ),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn test_decode_tenure_change() {
+        let bytes = decode_hex(TENURE_CHANGE_TX).unwrap();
+        let mut cursor = Cursor::new(bytes.as_ref());
+        let tx = StacksTransaction::deserialize(&mut cursor).unwrap();
+        assert_eq!(cursor.position(), bytes.len() as u64);
+        match tx.payload {
+            TransactionPayload::TenureChange(payload) => {
+                assert_eq!(payload.tenure_consensus_hash.0, [0x01; 20]);
+                assert_eq!(payload.prev_tenure_consensus_hash.0, [0x02; 20]);
+                assert_eq!(payload.burn_view_consensus_hash.0, [0x03; 20]);
+                assert_eq!(payload.previous_tenure_end.0, [0x04; 32]);
+                assert_eq!(payload.previous_tenure_blocks, 10);
+                assert!(payload.cause == TenureChangeCause::BlockFound);
+                assert_eq!(payload.pubkey_hash.0, [0x05; 20]);
+            }
+            _ => panic!("expected tenure change payload"),
+        }
+
+        let summary = decode_tenure_change_summary(&bytes).unwrap();
+        assert_eq!(
+            summary.tenure_consensus_hash,
+            format!("0x{}", "01".repeat(20))
+        );
+        assert_eq!(
+            summary.prev_tenure_consensus_hash,
+            format!("0x{}", "02".repeat(20))
+        );
+        assert_eq!(
+            summary.burn_view_consensus_hash,
+            format!("0x{}", "03".repeat(20))
+        );
+        assert_eq!(
+            summary.prev_tenure_end_block,
+            format!("0x{}", "04".repeat(32))
+        );
+        assert_eq!(summary.prev_tenure_blocks, 10);
+        assert!(summary.cause == TenureChangeCause::BlockFound);
+
+        // unknown cause byte
+        let mut bad_cause = bytes.to_vec();
+        let cause_index = bad_cause.len() - 21;
+        bad_cause[cause_index] = 0x02;
+        let mut cursor = Cursor::new(bad_cause.as_ref());
+        assert!(StacksTransaction::deserialize(&mut cursor).is_err());
+    }
+
+    #[test]
+    fn test_decode_version_byte() {
+        let mut bytes = decode_hex(TENURE_CHANGE_TX).unwrap().to_vec();
+
+        for version in [0x00u8, 0x80].iter() {
+            bytes[0] = *version;
+            let tx = StacksTransaction::deserialize(&mut Cursor::new(bytes.as_ref())).unwrap();
+            assert_eq!(tx.version as u8, *version);
+        }
+
+        for version in [0x01u8, 0x7f, 0x81, 0xff].iter() {
+            bytes[0] = *version;
+            let err = StacksTransaction::deserialize(&mut Cursor::new(bytes.as_ref()))
+                .err()
+                .unwrap();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "Failed to parse transaction: invalid version byte 0x{:02x}",
+                    version
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_chain_id_network() {
+        assert_eq!(chain_id_network(0x00000001), NetworkId::Mainnet);
+        assert_eq!(chain_id_network(0x80000000), NetworkId::Testnet);
+        assert_eq!(chain_id_network(0x12345678), NetworkId::Custom(0x12345678));
+        assert_eq!(chain_id_network(0), NetworkId::Custom(0));
+
+        let bytes = decode_hex(TENURE_CHANGE_TX).unwrap();
+        let tx = StacksTransaction::deserialize(&mut Cursor::new(bytes.as_ref())).unwrap();
+        assert_eq!(chain_id_network(tx.chain_id), NetworkId::Testnet);
+    }
+
+    #[test]
+    fn test_decode_truncated() {
+        let input = "0x00000000010400982f3ec112a5f5928a5c96a914bd733793b896a5000000000000053000000000000002290000c85889dad0d5b08a997a93a28a7c93eb22c324e5f8992dc93e37865ef4f3e0d65383beefeffc4871a2facbc4b590ddf887c80de6638ed4e2ec0e633d1e130f230301000000000216982f3ec112a5f5928a5c96a914bd733793b896a51861726b6164696b6f2d676f7665726e616e63652d76332d310770726f706f7365000000060616982f3ec112a5f5928a5c96a914bd733793b896a51d61726b6164696b6f2d7374616b652d706f6f6c2d64696b6f2d76312d32010000000000000000000000000000ef8801000000000000000000000000000003f00e00000028414950313020557064617465204c54567320616e64204c69717569646174696f6e20526174696f730e0000003168747470733a2f2f6769746875622e636f6d2f61726b6164696b6f2d64616f2f61726b6164696b6f2f70756c6c2f3439330b000000010c0000000507616464726573730516982f3ec112a5f5928a5c96a914bd733793b896a50863616e2d6275726e040863616e2d6d696e7404046e616d650d0000002b61697031302d61726b6164696b6f2d7570646174652d74766c2d6c69717569646174696f6e2d726174696f0e7175616c69666965642d6e616d650616982f3ec112a5f5928a5c96a914bd733793b896a52b61697031302d61726b6164696b6f2d7570646174652d74766c2d6c69717569646174696f6e2d726174696f";
+        let bytes = decode_hex(input).unwrap();
+        for len in 0..bytes.len() {
+            let mut cursor = Cursor::new(&bytes[..len]);
+            let err = StacksTransaction::deserialize(&mut cursor).err().unwrap();
+            assert!(err.is_unexpected_eof(), "{}", err);
+        }
+    }
 }
@@ -0,0 +1,284 @@
+use crate::hex::encode_hex;
+
+use super::deserialize::{
+    PrincipalData, StacksTransaction, TransactionAuth, TransactionAuthField, TransactionPayload,
+    TransactionSpendingCondition,
+};
+
+/// A field that differs between two transactions. `path` uses the same field names as the
+/// `decodeTransaction` JS output, e.g. `auth.origin_condition.nonce`. A value is `None` when
+/// the field only exists in one of the transactions, e.g. when their payload types differ.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxFieldDiff {
+    pub path: String,
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+/// Compare two transactions field-by-field, returning the fields that differ in the order
+/// they appear in the serialized transaction.
+pub fn diff_transactions(a: &StacksTransaction, b: &StacksTransaction) -> Vec<TxFieldDiff> {
+    let a_fields = tx_fields(a);
+    let b_fields = tx_fields(b);
+    let mut diffs = Vec::new();
+    for (path, a_value) in &a_fields {
+        let b_value = b_fields.iter().find(|(p, _)| p == path).map(|(_, v)| v);
+        if b_value != Some(a_value) {
+            diffs.push(TxFieldDiff {
+                path: path.clone(),
+                a: Some(a_value.clone()),
+                b: b_value.cloned(),
+            });
+        }
+    }
+    for (path, b_value) in &b_fields {
+        if !a_fields.iter().any(|(p, _)| p == path) {
+            diffs.push(TxFieldDiff {
+                path: path.clone(),
+                a: None,
+                b: Some(b_value.clone()),
+            });
+        }
+    }
+    diffs
+}
+
+fn tx_fields(tx: &StacksTransaction) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    let mut push = |path: &str, value: String| fields.push((path.to_string(), value));
+
+    push("version", (tx.version as u8).to_string());
+    push("chain_id", tx.chain_id.to_string());
+    match &tx.auth {
+        TransactionAuth::Standard(origin_condition) => {
+            push("auth.type_id", "4".to_string());
+            spending_condition_fields(&mut push, "auth.origin_condition", origin_condition);
+        }
+        TransactionAuth::Sponsored(origin_condition, sponsor_condition) => {
+            push("auth.type_id", "5".to_string());
+            spending_condition_fields(&mut push, "auth.origin_condition", origin_condition);
+            spending_condition_fields(&mut push, "auth.sponsor_condition", sponsor_condition);
+        }
+    }
+    push("anchor_mode", (tx.anchor_mode as u8).to_string());
+    push(
+        "post_condition_mode",
+        (tx.post_condition_mode as u8).to_string(),
+    );
+    push(
+        "post_conditions_buffer",
+        hex(&tx.post_conditions_serialized),
+    );
+
+    match &tx.payload {
+        TransactionPayload::TokenTransfer(recipient, amount, memo) => {
+            push("payload.type_id", "0".to_string());
+            push("payload.recipient", principal_string(recipient));
+            push("payload.amount", amount.to_string());
+            push("payload.memo_hex", hex(&memo.0));
+        }
+        TransactionPayload::SmartContract(smart_contract) => {
+            push("payload.type_id", "1".to_string());
+            push(
+                "payload.contract_name",
+                smart_contract.name.as_str().to_string(),
+            );
+            push(
+                "payload.code_body",
+                String::from_utf8_lossy(&smart_contract.code_body.0).into_owned(),
+            );
+        }
+        TransactionPayload::VersionedSmartContract(clarity_version, smart_contract) => {
+            push("payload.type_id", "6".to_string());
+            push("payload.clarity_version", clarity_version.to_string());
+            push(
+                "payload.contract_name",
+                smart_contract.name.as_str().to_string(),
+            );
+            push(
+                "payload.code_body",
+                String::from_utf8_lossy(&smart_contract.code_body.0).into_owned(),
+            );
+        }
+        TransactionPayload::ContractCall(contract_call) => {
+            push("payload.type_id", "2".to_string());
+            push(
+                "payload.address",
+                hex(&[
+                    &[contract_call.address.version][..],
+                    &contract_call.address.hash160_bytes[..],
+                ]
+                .concat()),
+            );
+            push(
+                "payload.contract_name",
+                contract_call.contract_name.as_str().to_string(),
+            );
+            push(
+                "payload.function_name",
+                contract_call.function_name.as_str().to_string(),
+            );
+            push(
+                "payload.function_args.length",
+                contract_call.function_args.len().to_string(),
+            );
+            for (i, arg) in contract_call.function_args.iter().enumerate() {
+                push(
+                    &format!("payload.function_args[{}]", i),
+                    arg.value.repr_string(),
+                );
+            }
+        }
+        TransactionPayload::PoisonMicroblock(header_1, header_2) => {
+            push("payload.type_id", "3".to_string());
+            push(
+                "payload.microblock_header_1",
+                hex(&header_1.serialized_bytes),
+            );
+            push(
+                "payload.microblock_header_2",
+                hex(&header_2.serialized_bytes),
+            );
+        }
+        TransactionPayload::Coinbase(coinbase) => {
+            push("payload.type_id", "4".to_string());
+            push("payload.payload_buffer", hex(&coinbase.0));
+        }
+        TransactionPayload::TenureChange(tenure_change) => {
+            push("payload.type_id", "7".to_string());
+            push(
+                "payload.tenure_consensus_hash",
+                hex(&tenure_change.tenure_consensus_hash.0),
+            );
+            push(
+                "payload.prev_tenure_consensus_hash",
+                hex(&tenure_change.prev_tenure_consensus_hash.0),
+            );
+            push(
+                "payload.burn_view_consensus_hash",
+                hex(&tenure_change.burn_view_consensus_hash.0),
+            );
+            push(
+                "payload.previous_tenure_end",
+                hex(&tenure_change.previous_tenure_end.0),
+            );
+            push(
+                "payload.previous_tenure_blocks",
+                tenure_change.previous_tenure_blocks.to_string(),
+            );
+            push("payload.cause", (tenure_change.cause as u8).to_string());
+            push("payload.pubkey_hash", hex(&tenure_change.pubkey_hash.0));
+        }
+    }
+    fields
+}
+
+fn spending_condition_fields(
+    push: &mut impl FnMut(&str, String),
+    prefix: &str,
+    condition: &TransactionSpendingCondition,
+) {
+    let mut push_field = |name: &str, value: String| push(&format!("{}.{}", prefix, name), value);
+    match condition {
+        TransactionSpendingCondition::Singlesig(singlesig) => {
+            push_field("hash_mode", (singlesig.hash_mode as u8).to_string());
+            push_field("signer", hex(&singlesig.signer));
+            push_field("nonce", singlesig.nonce.to_string());
+            push_field("tx_fee", singlesig.tx_fee.to_string());
+            push_field("key_encoding", (singlesig.key_encoding as u8).to_string());
+            push_field("signature", hex(&singlesig.signature.0));
+        }
+        TransactionSpendingCondition::Multisig(multisig) => {
+            push_field("hash_mode", (multisig.hash_mode as u8).to_string());
+            push_field("signer", hex(&multisig.signer));
+            push_field("nonce", multisig.nonce.to_string());
+            push_field("tx_fee", multisig.tx_fee.to_string());
+            push_field("fields.length", multisig.fields.len().to_string());
+            for (i, field) in multisig.fields.iter().enumerate() {
+                let value = match field {
+                    TransactionAuthField::PublicKey(pubkey) => hex(&pubkey.key.0),
+                    TransactionAuthField::Signature(_, sig) => hex(&sig.0),
+                };
+                push_field(&format!("fields[{}]", i), value);
+            }
+            push_field(
+                "signatures_required",
+                multisig.signatures_required.to_string(),
+            );
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    encode_hex(bytes).into()
+}
+
+fn principal_string(principal: &PrincipalData) -> String {
+    match principal {
+        PrincipalData::Standard(standard) => hex(&[&[standard.0][..], &standard.1[..]].concat()),
+        PrincipalData::Contract(contract) => format!(
+            "{}.{}",
+            hex(&[&[contract.issuer.0][..], &contract.issuer.1[..]].concat()),
+            contract.name.as_str()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::decode_hex;
+    use std::io::Cursor;
+
+    const TOKEN_TRANSFER_TX: &str = "0x80800000000400aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa000000000000000500000000000000c80011111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111110302000000000005161111111111111111111111111111111111111111000000000000007b00000000000000000000000000000000000000000000000000000000000000000000";
+
+    fn decode_tx(bytes: &[u8]) -> StacksTransaction {
+        let mut cursor = Cursor::new(bytes);
+        StacksTransaction::deserialize(&mut cursor).unwrap()
+    }
+
+    #[test]
+    fn test_diff_nonce() {
+        let bytes = decode_hex(TOKEN_TRANSFER_TX).unwrap();
+        let mut other_bytes = bytes.to_vec();
+        // last byte of the origin nonce
+        other_bytes[34] = 0x06;
+
+        let a = decode_tx(&bytes);
+        let b = decode_tx(&other_bytes);
+        assert!(diff_transactions(&a, &a).is_empty());
+        assert_eq!(
+            diff_transactions(&a, &b),
+            vec![TxFieldDiff {
+                path: "auth.origin_condition.nonce".to_string(),
+                a: Some("5".to_string()),
+                b: Some("6".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_payload_type() {
+        let a = decode_tx(&decode_hex(TOKEN_TRANSFER_TX).unwrap());
+        let mut coinbase_bytes = decode_hex(TOKEN_TRANSFER_TX).unwrap().to_vec();
+        coinbase_bytes.truncate(coinbase_bytes.len() - 65);
+        coinbase_bytes.push(0x04);
+        coinbase_bytes.extend_from_slice(&[0x09; 32]);
+        let b = decode_tx(&coinbase_bytes);
+
+        let diffs = diff_transactions(&a, &b);
+        let paths: Vec<&str> = diffs.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "payload.type_id",
+                "payload.recipient",
+                "payload.amount",
+                "payload.memo_hex",
+                "payload.payload_buffer"
+            ]
+        );
+        assert_eq!(diffs[1].b, None);
+        assert_eq!(diffs[4].a, None);
+    }
+}
@@ -0,0 +1,470 @@
+use std::convert::TryFrom;
+
+use serde_json::{json, Map, Value};
+
+use crate::address::c32::c32_address;
+use crate::address::stacks_address::{AddressHashMode, StacksAddress};
+use crate::clarity_value::types::ClarityValue;
+use crate::hex::encode_hex;
+use crate::post_condition::deserialize::{
+    AssetInfo, FungibleConditionCode, NonfungibleConditionCode, PostConditionPrincipal,
+    TransactionPostCondition,
+};
+
+use super::deserialize::{
+    PrincipalData, StacksTransaction, TenureChangeCause, TransactionAnchorMode, TransactionAuth,
+    TransactionPayload, TransactionPostConditionMode, TransactionSpendingCondition,
+    TransactionVersion,
+};
+
+/// Render a transaction in the Hiro API transaction schema, limited to the fields that come from
+/// the transaction itself, so block, status and event fields are left out. `tx_id` is the
+/// sha512/256 hash of the serialized transaction.
+///
+/// Principals are C32 strings, amounts and fees are decimal strings, and the payload is in the
+/// object named by `tx_type`, e.g. `token_transfer` or `contract_call`. Contract call arguments
+/// are `{ hex, repr, name, type }` with `type` taken from the value, while `name` and
+/// `function_signature` are empty since they need the contract ABI.
+pub fn transaction_to_json(tx: &StacksTransaction, tx_id: &[u8; 32]) -> Result<Value, String> {
+    let mut json = Map::new();
+    json.insert("tx_id".into(), json!(encode_hex(tx_id)));
+    let (origin_condition, sponsor_condition) = match &tx.auth {
+        TransactionAuth::Standard(origin_condition) => (origin_condition, None),
+        TransactionAuth::Sponsored(origin_condition, sponsor_condition) => {
+            (origin_condition, Some(sponsor_condition))
+        }
+    };
+    let (origin_nonce, origin_fee) = nonce_and_fee(origin_condition);
+    let sender_address = signer_address(origin_condition, tx.version)?;
+    json.insert("nonce".into(), json!(origin_nonce));
+    json.insert("sender_address".into(), json!(sender_address));
+    json.insert("sponsored".into(), json!(sponsor_condition.is_some()));
+    // a sponsored transaction's fee is paid, and set, by the sponsor
+    let fee_rate = match sponsor_condition {
+        Some(sponsor_condition) => {
+            let (sponsor_nonce, sponsor_fee) = nonce_and_fee(sponsor_condition);
+            json.insert("sponsor_nonce".into(), json!(sponsor_nonce));
+            json.insert(
+                "sponsor_address".into(),
+                json!(signer_address(sponsor_condition, tx.version)?),
+            );
+            sponsor_fee
+        }
+        None => origin_fee,
+    };
+    json.insert("fee_rate".into(), json!(fee_rate.to_string()));
+    let post_condition_mode = match tx.post_condition_mode {
+        TransactionPostConditionMode::Allow => "allow",
+        TransactionPostConditionMode::Deny => "deny",
+    };
+    json.insert("post_condition_mode".into(), json!(post_condition_mode));
+    let post_conditions = tx
+        .post_conditions
+        .iter()
+        .map(post_condition_json)
+        .collect::<Result<Vec<_>, _>>()?;
+    json.insert("post_conditions".into(), json!(post_conditions));
+    let anchor_mode = match tx.anchor_mode {
+        TransactionAnchorMode::OnChainOnly => "on_chain_only",
+        TransactionAnchorMode::OffChainOnly => "off_chain_only",
+        TransactionAnchorMode::Any => "any",
+    };
+    json.insert("anchor_mode".into(), json!(anchor_mode));
+    let (tx_type, payload_key, payload) = payload_json(&tx.payload, &sender_address)?;
+    json.insert("tx_type".into(), json!(tx_type));
+    json.insert(payload_key.into(), payload);
+    Ok(Value::Object(json))
+}
+
+fn c32_address_string(version: u8, hash160: &[u8]) -> Result<String, String> {
+    c32_address(version, hash160).map_err(|e| format!("Error converting to C32 address: {}", e))
+}
+
+fn nonce_and_fee(condition: &TransactionSpendingCondition) -> (u64, u64) {
+    match condition {
+        TransactionSpendingCondition::Singlesig(data) => (data.nonce, data.tx_fee),
+        TransactionSpendingCondition::Multisig(data) => (data.nonce, data.tx_fee),
+    }
+}
+
+fn signer_address(
+    condition: &TransactionSpendingCondition,
+    tx_version: TransactionVersion,
+) -> Result<String, String> {
+    let (hash_mode, signer) = match condition {
+        TransactionSpendingCondition::Singlesig(data) => (data.hash_mode as u8, &data.signer),
+        TransactionSpendingCondition::Multisig(data) => (data.hash_mode as u8, &data.signer),
+    };
+    let address_hash_mode = AddressHashMode::try_from(hash_mode)?;
+    let version = match tx_version {
+        TransactionVersion::Mainnet => address_hash_mode.to_version_mainnet(),
+        TransactionVersion::Testnet => address_hash_mode.to_version_testnet(),
+    };
+    let address = StacksAddress::new(version, *signer);
+    c32_address_string(address.version, &address.hash160_bytes)
+}
+
+/// `{ hex, repr }`, the API's rendering of a Clarity value.
+fn clarity_value_json(val: &ClarityValue) -> Result<Map<String, Value>, String> {
+    let bytes = val
+        .serialized_bytes
+        .as_ref()
+        .ok_or("Clarity value was deserialized without bytes")?;
+    let mut json = Map::new();
+    json.insert("hex".into(), json!(encode_hex(bytes)));
+    json.insert("repr".into(), json!(val.value.repr_string()));
+    Ok(json)
+}
+
+fn post_condition_principal_json(principal: &PostConditionPrincipal) -> Result<Value, String> {
+    Ok(match principal {
+        PostConditionPrincipal::Origin => json!({ "type_id": "principal_origin" }),
+        PostConditionPrincipal::Standard(address) => json!({
+            "type_id": "principal_standard",
+            "address": c32_address_string(address.version, &address.hash160_bytes)?,
+        }),
+        PostConditionPrincipal::Contract(address, contract_name) => json!({
+            "type_id": "principal_contract",
+            "address": c32_address_string(address.version, &address.hash160_bytes)?,
+            "contract_name": contract_name.as_str(),
+        }),
+    })
+}
+
+fn asset_info_json(asset_info: &AssetInfo) -> Result<Value, String> {
+    let contract_address = &asset_info.contract_address;
+    Ok(json!({
+        "asset_name": asset_info.asset_name.as_str(),
+        "contract_address": c32_address_string(contract_address.version, &contract_address.hash160_bytes)?,
+        "contract_name": asset_info.contract_name.as_str(),
+    }))
+}
+
+fn fungible_condition_name(condition: FungibleConditionCode) -> &'static str {
+    match condition {
+        FungibleConditionCode::SentEq => "sent_equal_to",
+        FungibleConditionCode::SentGt => "sent_greater_than",
+        FungibleConditionCode::SentGe => "sent_greater_than_or_equal_to",
+        FungibleConditionCode::SentLt => "sent_less_than",
+        FungibleConditionCode::SentLe => "sent_less_than_or_equal_to",
+    }
+}
+
+fn nonfungible_condition_name(condition: NonfungibleConditionCode) -> &'static str {
+    match condition {
+        NonfungibleConditionCode::Sent => "sent",
+        NonfungibleConditionCode::NotSent => "not_sent",
+    }
+}
+
+fn post_condition_json(post_condition: &TransactionPostCondition) -> Result<Value, String> {
+    Ok(match post_condition {
+        TransactionPostCondition::STX(principal, fungible_condition, amount) => json!({
+            "type": "stx",
+            "condition_code": fungible_condition_name(*fungible_condition),
+            "amount": amount.to_string(),
+            "principal": post_condition_principal_json(principal)?,
+        }),
+        TransactionPostCondition::Fungible(principal, asset_info, fungible_condition, amount) => {
+            json!({
+                "type": "fungible",
+                "condition_code": fungible_condition_name(*fungible_condition),
+                "amount": amount.to_string(),
+                "principal": post_condition_principal_json(principal)?,
+                "asset": asset_info_json(asset_info)?,
+            })
+        }
+        TransactionPostCondition::Nonfungible(
+            principal,
+            asset_info,
+            asset_value,
+            nonfungible_condition,
+        ) => json!({
+            "type": "non_fungible",
+            "condition_code": nonfungible_condition_name(*nonfungible_condition),
+            "principal": post_condition_principal_json(principal)?,
+            "asset": asset_info_json(asset_info)?,
+            "asset_value": clarity_value_json(asset_value)?,
+        }),
+    })
+}
+
+/// The payload's `tx_type`, the name of the payload object, and the object. Deployed contracts
+/// are named after `sender_address`.
+fn payload_json(
+    payload: &TransactionPayload,
+    sender_address: &str,
+) -> Result<(&'static str, &'static str, Value), String> {
+    Ok(match payload {
+        TransactionPayload::TokenTransfer(recipient, amount, memo) => {
+            let recipient_address = match recipient {
+                PrincipalData::Standard(principal) => {
+                    c32_address_string(principal.0, &principal.1)?
+                }
+                PrincipalData::Contract(contract_identifier) => {
+                    let issuer = &contract_identifier.issuer;
+                    format!(
+                        "{}.{}",
+                        c32_address_string(issuer.0, &issuer.1)?,
+                        contract_identifier.name
+                    )
+                }
+            };
+            (
+                "token_transfer",
+                "token_transfer",
+                json!({
+                    "recipient_address": recipient_address,
+                    "amount": amount.to_string(),
+                    "memo": encode_hex(&memo.0),
+                }),
+            )
+        }
+        TransactionPayload::ContractCall(contract_call) => {
+            let address = &contract_call.address;
+            let contract_id = format!(
+                "{}.{}",
+                c32_address_string(address.version, &address.hash160_bytes)?,
+                contract_call.contract_name
+            );
+            let function_args = contract_call
+                .function_args
+                .iter()
+                .map(|arg| {
+                    let mut arg_json = clarity_value_json(arg)?;
+                    arg_json.insert("name".into(), json!(""));
+                    arg_json.insert("type".into(), json!(arg.value.type_signature()));
+                    Ok(Value::Object(arg_json))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            (
+                "contract_call",
+                "contract_call",
+                json!({
+                    "contract_id": contract_id,
+                    "function_name": contract_call.function_name.as_str(),
+                    "function_signature": "",
+                    "function_args": function_args,
+                }),
+            )
+        }
+        TransactionPayload::SmartContract(smart_contract) => (
+            "smart_contract",
+            "smart_contract",
+            json!({
+                "clarity_version": null,
+                "contract_id": format!("{}.{}", sender_address, smart_contract.name),
+                "source_code": String::from_utf8_lossy(&smart_contract.code_body.0),
+            }),
+        ),
+        TransactionPayload::VersionedSmartContract(clarity_version, smart_contract) => (
+            "smart_contract",
+            "smart_contract",
+            json!({
+                "clarity_version": clarity_version,
+                "contract_id": format!("{}.{}", sender_address, smart_contract.name),
+                "source_code": String::from_utf8_lossy(&smart_contract.code_body.0),
+            }),
+        ),
+        TransactionPayload::PoisonMicroblock(h1, h2) => (
+            "poison_microblock",
+            "poison_microblock",
+            json!({
+                "microblock_header_1": encode_hex(&h1.serialized_bytes),
+                "microblock_header_2": encode_hex(&h2.serialized_bytes),
+            }),
+        ),
+        TransactionPayload::Coinbase(buf) => (
+            "coinbase",
+            "coinbase_payload",
+            json!({
+                "data": encode_hex(&buf.0),
+                "alt_recipient": null,
+                "vrf_proof": null,
+            }),
+        ),
+        TransactionPayload::TenureChange(tenure_change) => {
+            let cause = match tenure_change.cause {
+                TenureChangeCause::BlockFound => "block_found",
+                TenureChangeCause::Extended => "extended",
+            };
+            (
+                "tenure_change",
+                "tenure_change_payload",
+                json!({
+                    "tenure_consensus_hash": encode_hex(&tenure_change.tenure_consensus_hash.0),
+                    "prev_tenure_consensus_hash": encode_hex(&tenure_change.prev_tenure_consensus_hash.0),
+                    "burn_view_consensus_hash": encode_hex(&tenure_change.burn_view_consensus_hash.0),
+                    "previous_tenure_end": encode_hex(&tenure_change.previous_tenure_end.0),
+                    "previous_tenure_blocks": tenure_change.previous_tenure_blocks,
+                    "cause": cause,
+                    "pubkey_hash": encode_hex(&tenure_change.pubkey_hash.0),
+                }),
+            )
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::sha512_256;
+    use crate::hex::decode_hex;
+    use std::io::Cursor;
+
+    fn tx_json(input: &str) -> Value {
+        let bytes = decode_hex(input).unwrap();
+        let tx = StacksTransaction::deserialize(&mut Cursor::new(bytes.as_ref())).unwrap();
+        transaction_to_json(&tx, &sha512_256(&bytes)).unwrap()
+    }
+
+    // mainnet transactions from tests/tx-decode.test.ts, rendered in the API schema
+
+    #[test]
+    fn test_token_transfer_to_json() {
+        let json = tx_json("0x00000000010400d27548439b50c8d8ca86756151bd6e07059510a0000000000000002f00000000000027100000ffdf728bc8d1423ba751e164a153b160c48980e727c98971816096534ee6b3a40e81b31786a547cc64fcb52d592939ec5a5886d8ec8053a91e22e76cb9ef4d4403020000000000051634ddc468605d7e5b65f2970002ae55b554f5dda400000000001e848000000000000000000000000000000000000000000000000000000000000000000000");
+        assert_eq!(
+            json,
+            json!({
+                "tx_id": "0xb81f2fdcc66926d846a4cfdad426a66a2a5a183208bffcbc1e0c1a453e662171",
+                "nonce": 47,
+                "fee_rate": "10000",
+                "sender_address": "SP397AJ23KD8CHP6AGSTP2MDXDR3GB58GM1NCJD22",
+                "sponsored": false,
+                "post_condition_mode": "deny",
+                "post_conditions": [],
+                "anchor_mode": "any",
+                "tx_type": "token_transfer",
+                "token_transfer": {
+                    "recipient_address": "SPTDVH38C1EQWPV5YABG00NEAPTN9XEXMJHSEW92",
+                    "amount": "2000000",
+                    "memo": "0x00000000000000000000000000000000000000000000000000000000000000000000"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_contract_call_to_json() {
+        // with an NFT post condition
+        let json = tx_json("0x0000000001040089f5fd1f719e4449c980de38e3504be6770a2698000000000000014500000000000001f400008b510c9e20dc22040953d9d7eabf2038008fa4d89a5a6cb78bb9d513e75cd0df3924af9ce3b5f185705bc2f6ba3071710ec6a8803ed6da4addc40a05a01ee0f503020000000102021689f5fd1f719e4449c980de38e3504be6770a269816a6a7a70f41adbe8eae708ed7ec2cbf41a272182014626974636f696e2d6d6f6e6b6579732d6c61627314626974636f696e2d6d6f6e6b6579732d6c61627301000000000000000000000000000008ba1002162bcf9762d5b90bc36dc1b4759b1727690f92ddd30e6d61726b6574706c6163652d76340a6c6973742d6173736574000000040616a6a7a70f41adbe8eae708ed7ec2cbf41a272182014626974636f696e2d6d6f6e6b6579732d6c61627301000000000000000000000000000008ba010000000000000000000000000c84588001000000000000000000000000000000c8");
+        assert_eq!(
+            json,
+            json!({
+                "tx_id": "0x49bcdba540d5c486f6f7a71de639a128a65f0378d7571e20c75a61661dd5b469",
+                "nonce": 325,
+                "fee_rate": "500",
+                "sender_address": "SP24ZBZ8ZE6F48JE9G3F3HRTG9FK7E2H6K2QZ3Q1K",
+                "sponsored": false,
+                "post_condition_mode": "deny",
+                "post_conditions": [
+                    {
+                        "type": "non_fungible",
+                        "condition_code": "sent",
+                        "principal": {
+                            "type_id": "principal_standard",
+                            "address": "SP24ZBZ8ZE6F48JE9G3F3HRTG9FK7E2H6K2QZ3Q1K"
+                        },
+                        "asset": {
+                            "asset_name": "bitcoin-monkeys-labs",
+                            "contract_address": "SP2KAF9RF86PVX3NEE27DFV1CQX0T4WGR41X3S45C",
+                            "contract_name": "bitcoin-monkeys-labs"
+                        },
+                        "asset_value": {
+                            "hex": "0x01000000000000000000000000000008ba",
+                            "repr": "u2234"
+                        }
+                    }
+                ],
+                "anchor_mode": "any",
+                "tx_type": "contract_call",
+                "contract_call": {
+                    "contract_id": "SPNWZ5V2TPWGQGVDR6T7B6RQ4XMGZ4PXTEE0VQ0S.marketplace-v4",
+                    "function_name": "list-asset",
+                    "function_signature": "",
+                    "function_args": [
+                        {
+                            "hex": "0x0616a6a7a70f41adbe8eae708ed7ec2cbf41a272182014626974636f696e2d6d6f6e6b6579732d6c616273",
+                            "repr": "'SP2KAF9RF86PVX3NEE27DFV1CQX0T4WGR41X3S45C.bitcoin-monkeys-labs",
+                            "name": "",
+                            "type": "principal"
+                        },
+                        {
+                            "hex": "0x01000000000000000000000000000008ba",
+                            "repr": "u2234",
+                            "name": "",
+                            "type": "uint"
+                        },
+                        {
+                            "hex": "0x010000000000000000000000000c845880",
+                            "repr": "u210000000",
+                            "name": "",
+                            "type": "uint"
+                        },
+                        {
+                            "hex": "0x01000000000000000000000000000000c8",
+                            "repr": "u200",
+                            "name": "",
+                            "type": "uint"
+                        }
+                    ]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_coinbase_to_json() {
+        let json = tx_json("0x00000000010400b00de0cc7b5e518f7d1e43d6e5ecbd52e0cd0c2f0000000000001ddc00000000000000000001827095db6a9de80e51323bc96a926874999472634fbdfd5adc36df48e2d6b01c7505244fba66e8812b761a2ea1f1e98253139db1ccd1c7ffaa14208dd489bf69010200000000040000000000000000000000000000000000000000000000000000000000000000");
+        assert_eq!(
+            json,
+            json!({
+                "tx_id": "0xd59d655128d67e33c6e9c20c7cca309b91da352358639092e13ce74fc0a6a5d4",
+                "nonce": 7644,
+                "fee_rate": "0",
+                "sender_address": "SP2R0VR6CFDF533VX3S1XDSFCQN9E1K8C5WS5Q9JC",
+                "sponsored": false,
+                "post_condition_mode": "deny",
+                "post_conditions": [],
+                "anchor_mode": "on_chain_only",
+                "tx_type": "coinbase",
+                "coinbase_payload": {
+                    "data": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                    "alt_recipient": null,
+                    "vrf_proof": null
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_sponsored_to_json() {
+        let json = tx_json("0x000000000105001f6000287c9ed40ba39b27fc4257ff30cda4e91d000000000000005c0000000000000000000188f35fb5444ebdfcfced4b267aa76e60b90fc38ff7f7c8d770fc21407a26650a78aded3af77a5f0d7ca2ba7dabeff0579ec1d891e715c55237cfd5737e42378000661506d48705f932af21abcff23046b216886e840000000000000a1a000000000007a12000003fe006c2b46c5c6a16bc0af6f065c63d6e6c5a40e51fec26a9768caab0acd7ca42401d9c8030f120d2005388c1d04c602a3a9177eb26534a4fd6a3897686b1490302000000010002161f6000287c9ed40ba39b27fc4257ff30cda4e91d0100000000bda999040216debc095099629badb11b9d5335e874d12f1f1d450e73656e642d6d616e792d6d656d6f0973656e642d6d616e79000000010b0000001e0c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f05162c34acd88561d6905f635e8eb59a36395b8c42a1047573747801000000000000000000000000002e640b0c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f0516f1818ba832287fc71375c3e78bae2c8c64f6bb01047573747801000000000000000000000000008d51b10c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f0516267248c930110d411248d9ff16e63a8ddfa0c641047573747801000000000000000000000000121d39d80c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f0516e73e15214dab160f2b78c935279e99a4a8ce83fc047573747801000000000000000000000000003beba90c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f05166176366f88666f63ee0280db409fc8bcdc5eccbd047573747801000000000000000000000000056fb9430c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f0516cb8de306c17412fc869c8337fa7b6b6f3a17cc160475737478010000000000000000000000000014018b0c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f0516477b4de6bfcf52f8cfec6854720b7a6982193eb704757374780100000000000000000000000005f518e20c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f051668210b7f12b4ee43f374da9a59a51ecc8d0d89230475737478010000000000000000000000000029a8780c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f05163b8e6ef49d23e432100aeca022c89555b7209aad04757374780100000000000000000000000010152ebb0c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f051694401aeb5474061ac390f723cb1826feb1b90448047573747801000000000000000000000000482a34380c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f051686e0feea7bd59ae51dd0d2e47c7220406348e73804757374780100000000000000000000000000c938550c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f0516a52961a0d54cb3b9ce9a7092440dff4573cff58c04757374780100000000000000000000000000f19d340c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f0516130b7aee04f3eee627e103d3c33aea1d5339087d047573747801000000000000000000000000032aadd60c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f05165db3d733033684784ebfa931b8e5f228bb247c4d04757374780100000000000000000000000000adf7280c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f051600da0575e398c7b3d64f3df9d32ec57b9c0af3f704757374780100000000000000000000000000adf7280c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f051607b7982ea3aaff67270d86e62436b0d08ea8d17804757374780100000000000000000000000010bc307b0c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f0516fd048b0a4e906580e4a5281ebef59f2d60585ac4047573747801000000000000000000000000076efb580c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f05164ecd67a6d4ca15c0cb9cf7cc0bc0594ba10ce4ee047573747801000000000000000000000000016a6c4c0c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f0516f6641fafd4cdb742214558a43a5effeeb6061d76047573747801000000000000000000000000002a5b460c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f05166af9a2a4e295c9b23b7304f4bd14082b7101e7c304757374780100000000000000000000000002b299d90c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f05166c317c39e65133aa7a414eb75e516dc59536f55004757374780100000000000000000000000018b0beba0c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f0516e13a94997d076788fa000588d7bfb053b2cfa3c3047573747801000000000000000000000000003b2c660c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f05165b7ee1d697120fbe6a761df99cddb46774cf1ca50475737478010000000000000000000000000005da110c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f051688e4220b60f2222e115d8a8b1e46b09aed368854047573747801000000000000000000000000056d4c270c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f051609973469a88ffcbf70651d8078442b9eedd47f590475737478010000000000000000000000000002e6410c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f05162bbf6ea8291431ec60fcb86c066ff375071ce7d8047573747801000000000000000000000000001e57d60c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f0516fbd72ce502f8a46db5a6e473a4e2c8830bc1a43904757374780100000000000000000000000001a593880c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f051667a3c0f1b06075e9f7b9fa4d3e5d6492041c295a0475737478010000000000000000000000000005cb390c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f0516cba8da4a454ae7ad02cb71fb523ddd720d73ad3a0475737478010000000000000000000000000176f41c0c00000003046d656d6f0200000017726577617264206379636c6520233236207061796f757402746f05169bb88a9c3db00f126f08f380d28b348458ebb6f7047573747801000000000000000000000000025c3d73");
+        assert_eq!(
+            json["tx_id"],
+            "0x3b1236287da14f8ffaa0e99ced3ce354986107ae08569fe7f1a497adfd37e2c7"
+        );
+        assert_eq!(
+            json["sender_address"],
+            "SPFP0018FJFD82X3KCKZRGJQZWRCV9793QTGE87M"
+        );
+        assert_eq!(json["nonce"], 92);
+        assert_eq!(json["sponsored"], true);
+        assert_eq!(
+            json["sponsor_address"],
+            "SP1K1A1PMGW2ZJCNF46NWZWHG8TS1D23EGH1KNK60"
+        );
+        assert_eq!(json["sponsor_nonce"], 2586);
+        assert_eq!(json["fee_rate"], "500000");
+        assert_eq!(json["tx_type"], "contract_call");
+        assert_eq!(
+            json["contract_call"]["contract_id"],
+            "SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE.send-many-memo"
+        );
+        assert_eq!(json["post_conditions"][0]["type"], "stx");
+        assert_eq!(
+            json["post_conditions"][0]["condition_code"],
+            "sent_equal_to"
+        );
+    }
+}
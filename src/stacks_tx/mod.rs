@@ -6,14 +6,17 @@ use crate::hex::encode_hex;
 use crate::neon_util::*;
 
 use self::deserialize::StacksTransaction;
-mod deserialize;
+pub mod deserialize;
+pub mod diff;
+pub mod json;
 mod neon_encoder;
+pub mod patch;
 
 pub fn decode_transaction(mut cx: FunctionContext) -> JsResult<JsObject> {
     let (tx, tx_id_bytes) = arg_as_bytes(&mut cx, 0, |val_bytes| {
         let mut cursor = Cursor::new(val_bytes);
         let tx = StacksTransaction::deserialize(&mut cursor)
-            .or_else(|e| Err(format!("Failed to decode transaction: {:?}\n", &e)))?;
+            .map_err(|e| format!("Failed to decode transaction: {}\n", e.as_string()))?;
         let tx_id_bytes = Sha512_256::digest(val_bytes);
         Ok((tx, tx_id_bytes))
     })
@@ -19,7 +19,7 @@ use super::deserialize::{
     StacksTransaction, StandardPrincipalData, TransactionAuth, TransactionAuthField,
     TransactionAuthFieldID, TransactionAuthFlags, TransactionContractCall, TransactionPayload,
     TransactionPayloadID, TransactionPublicKeyEncoding, TransactionSmartContract,
-    TransactionSpendingCondition, TransactionVersion,
+    TransactionSpendingCondition, TransactionTenureChange, TransactionVersion,
 };
 
 struct TxSerializationContext {
@@ -477,6 +477,15 @@ impl NeonJsSerialize for TransactionPayload {
 
                 smart_contract.neon_js_serialize(cx, obj, extra_ctx)?;
             }
+            TransactionPayload::VersionedSmartContract(clarity_version, ref smart_contract) => {
+                let type_id = cx.number(TransactionPayloadID::VersionedSmartContract as u8);
+                obj.set(cx, "type_id", type_id)?;
+
+                let clarity_version = cx.number(clarity_version);
+                obj.set(cx, "clarity_version", clarity_version)?;
+
+                smart_contract.neon_js_serialize(cx, obj, extra_ctx)?;
+            }
             TransactionPayload::PoisonMicroblock(ref h1, ref h2) => {
                 let type_id = cx.number(TransactionPayloadID::PoisonMicroblock as u8);
                 obj.set(cx, "type_id", type_id)?;
@@ -496,6 +505,12 @@ impl NeonJsSerialize for TransactionPayload {
                 let payload_buffer = cx.string(encode_hex(&buf.0));
                 obj.set(cx, "payload_buffer", payload_buffer)?;
             }
+            TransactionPayload::TenureChange(ref tenure_change) => {
+                let type_id = cx.number(TransactionPayloadID::TenureChange as u8);
+                obj.set(cx, "type_id", type_id)?;
+
+                tenure_change.neon_js_serialize(cx, obj, extra_ctx)?;
+            }
         }
         Ok(())
     }
@@ -609,6 +624,38 @@ impl NeonJsSerialize for TransactionSmartContract {
     }
 }
 
+impl NeonJsSerialize for TransactionTenureChange {
+    fn neon_js_serialize(
+        &self,
+        cx: &mut FunctionContext,
+        obj: &Handle<JsObject>,
+        _extra_ctx: &(),
+    ) -> NeonResult<()> {
+        let tenure_consensus_hash = cx.string(encode_hex(&self.tenure_consensus_hash.0));
+        obj.set(cx, "tenure_consensus_hash", tenure_consensus_hash)?;
+
+        let prev_tenure_consensus_hash = cx.string(encode_hex(&self.prev_tenure_consensus_hash.0));
+        obj.set(cx, "prev_tenure_consensus_hash", prev_tenure_consensus_hash)?;
+
+        let burn_view_consensus_hash = cx.string(encode_hex(&self.burn_view_consensus_hash.0));
+        obj.set(cx, "burn_view_consensus_hash", burn_view_consensus_hash)?;
+
+        let previous_tenure_end = cx.string(encode_hex(&self.previous_tenure_end.0));
+        obj.set(cx, "previous_tenure_end", previous_tenure_end)?;
+
+        let previous_tenure_blocks = cx.number(self.previous_tenure_blocks);
+        obj.set(cx, "previous_tenure_blocks", previous_tenure_blocks)?;
+
+        let cause = cx.number(self.cause as u8);
+        obj.set(cx, "cause", cause)?;
+
+        let pubkey_hash = cx.string(encode_hex(&self.pubkey_hash.0));
+        obj.set(cx, "pubkey_hash", pubkey_hash)?;
+
+        Ok(())
+    }
+}
+
 impl NeonJsSerialize for StacksMicroblockHeader {
     fn neon_js_serialize(
         &self,
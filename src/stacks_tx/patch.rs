@@ -0,0 +1,181 @@
+use byteorder::ReadBytesExt;
+use std::io::Cursor;
+
+use crate::hash::sha512_256;
+use crate::serialize_util::DeserializeError;
+
+use super::deserialize::{StacksTransaction, TransactionAuthFlags, TransactionSpendingCondition};
+
+/// Offset of the auth type byte, after the version byte and chain ID.
+const AUTH_TYPE_OFFSET: usize = 5;
+/// Offset of the nonce within a spending condition, after the hash mode and signer.
+const SPENDING_CONDITION_NONCE_OFFSET: usize = 21;
+
+/// Overwrite the sponsor spending condition's fee and nonce in a serialized sponsored
+/// transaction, leaving every other byte unchanged. Note that this invalidates the sponsor's
+/// signature, which must be re-signed afterwards.
+pub fn patch_sponsor_fee_nonce(
+    tx_bytes: &mut [u8],
+    new_fee: u64,
+    new_nonce: u64,
+) -> Result<(), DeserializeError> {
+    let sponsor_offset = {
+        let mut cursor = Cursor::new(&tx_bytes[..]);
+        cursor.set_position(AUTH_TYPE_OFFSET as u64);
+        let auth_type = cursor.read_u8()?;
+        if auth_type != TransactionAuthFlags::AuthSponsored as u8 {
+            return Err(format!(
+                "Failed to patch sponsor: transaction is not sponsored, auth flags {}",
+                auth_type
+            )
+            .into());
+        }
+        // skip the origin condition, then validate the sponsor condition
+        TransactionSpendingCondition::deserialize(&mut cursor)?;
+        let sponsor_offset = cursor.position() as usize;
+        TransactionSpendingCondition::deserialize(&mut cursor)?;
+        sponsor_offset
+    };
+
+    let nonce_offset = sponsor_offset + SPENDING_CONDITION_NONCE_OFFSET;
+    tx_bytes[nonce_offset..nonce_offset + 8].copy_from_slice(&new_nonce.to_be_bytes());
+    tx_bytes[nonce_offset + 8..nonce_offset + 16].copy_from_slice(&new_fee.to_be_bytes());
+    Ok(())
+}
+
+/// Offset of the key encoding within a singlesig spending condition, after the fee.
+const SINGLESIG_KEY_ENCODING_OFFSET: usize = 37;
+
+/// A spending condition's bytes as they are in the initial sighash: the nonce and fee are zero,
+/// and the signature (singlesig) or auth fields (multisig) are cleared.
+fn cleared_spending_condition(condition: &TransactionSpendingCondition, bytes: &[u8]) -> Vec<u8> {
+    // hash mode and signer, followed by the zeroed nonce and fee
+    let mut cleared = bytes[..SPENDING_CONDITION_NONCE_OFFSET].to_vec();
+    cleared.extend_from_slice(&[0; 16]);
+    match condition {
+        TransactionSpendingCondition::Singlesig(_) => {
+            cleared.push(bytes[SINGLESIG_KEY_ENCODING_OFFSET]);
+            cleared.extend_from_slice(&[0; 65]);
+        }
+        TransactionSpendingCondition::Multisig(_) => {
+            // no auth fields, followed by the signatures required
+            cleared.extend_from_slice(&0u32.to_be_bytes());
+            cleared.extend_from_slice(&bytes[bytes.len() - 2..]);
+        }
+    }
+    cleared
+}
+
+/// The initial sighash of a serialized transaction, the hash signers start from: the txid of the
+/// transaction with the origin condition cleared (see `cleared_spending_condition`) and, for
+/// sponsored transactions, the sponsor condition replaced by an empty p2pkh singlesig
+/// condition. Each signer then hashes it with the auth flag, their fee and nonce to get the hash
+/// they sign.
+pub fn transaction_sighash(tx_bytes: &[u8]) -> Result<[u8; 32], DeserializeError> {
+    StacksTransaction::deserialize(&mut Cursor::new(tx_bytes))?;
+
+    let mut cursor = Cursor::new(tx_bytes);
+    cursor.set_position(AUTH_TYPE_OFFSET as u64 + 1);
+    let origin = TransactionSpendingCondition::deserialize(&mut cursor)?;
+    let origin_end = cursor.position() as usize;
+    let auth_end = if tx_bytes[AUTH_TYPE_OFFSET] == TransactionAuthFlags::AuthSponsored as u8 {
+        TransactionSpendingCondition::deserialize(&mut cursor)?;
+        cursor.position() as usize
+    } else {
+        origin_end
+    };
+
+    let mut initial = tx_bytes[..=AUTH_TYPE_OFFSET].to_vec();
+    initial.extend(cleared_spending_condition(
+        &origin,
+        &tx_bytes[AUTH_TYPE_OFFSET + 1..origin_end],
+    ));
+    if auth_end != origin_end {
+        // p2pkh hash mode, zero signer, nonce and fee, compressed key encoding, no signature
+        initial.extend_from_slice(&[0; 1 + 20 + 16 + 1 + 65]);
+    }
+    initial.extend_from_slice(&tx_bytes[auth_end..]);
+    Ok(sha512_256(&initial))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::{decode_hex, encode_hex};
+    use crate::stacks_tx::deserialize::{StacksTransaction, TransactionAuth};
+
+    const SPONSORED_TX: &str = "0x80800000000500aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa000000000000000500000000000000c800111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111111100bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb000000000000000700000000000000640022222222222222222222222222222222222222222222222222222222222222222222222222222222222222222222222222222222222222222222222222222222220302000000000005161111111111111111111111111111111111111111000000000000007b00000000000000000000000000000000000000000000000000000000000000000000";
+
+    fn fee_nonce(condition: &TransactionSpendingCondition) -> (u64, u64) {
+        match condition {
+            TransactionSpendingCondition::Singlesig(cond) => (cond.tx_fee, cond.nonce),
+            TransactionSpendingCondition::Multisig(cond) => (cond.tx_fee, cond.nonce),
+        }
+    }
+
+    #[test]
+    fn test_patch_sponsor_fee_nonce() {
+        let original = decode_hex(SPONSORED_TX).unwrap();
+        let mut bytes = original.to_vec();
+        patch_sponsor_fee_nonce(&mut bytes, 5000, 42).unwrap();
+
+        let tx = StacksTransaction::deserialize(&mut Cursor::new(bytes.as_ref())).unwrap();
+        match &tx.auth {
+            TransactionAuth::Sponsored(origin, sponsor) => {
+                assert_eq!(fee_nonce(origin), (200, 5));
+                assert_eq!(fee_nonce(sponsor), (5000, 42));
+            }
+            _ => panic!("expected sponsored auth"),
+        }
+
+        // only the sponsor nonce and fee bytes changed
+        let changed: Vec<usize> = (0..bytes.len())
+            .filter(|i| bytes[*i] != original[*i])
+            .collect();
+        assert!(
+            changed.iter().all(|i| (130..146).contains(i)),
+            "{:?}",
+            changed
+        );
+    }
+
+    #[test]
+    fn test_patch_not_sponsored() {
+        let mut bytes = decode_hex(SPONSORED_TX).unwrap().to_vec();
+        bytes[AUTH_TYPE_OFFSET] = TransactionAuthFlags::AuthStandard as u8;
+        let unchanged = bytes.clone();
+        assert!(patch_sponsor_fee_nonce(&mut bytes, 1, 1).is_err());
+        assert_eq!(bytes, unchanged);
+
+        let mut truncated = decode_hex(SPONSORED_TX).unwrap()[..140].to_vec();
+        assert!(patch_sponsor_fee_nonce(&mut truncated, 1, 1).is_err());
+    }
+
+    #[test]
+    fn test_transaction_sighash() {
+        // mainnet contract call, the signature recovers to the signer over this sighash
+        let tx = "0x00000000010400b1907a5f15e25dfbb7487a032d37f61205d6127600000000000001ba0000000000002710000004fbc868d0ca4a9145f43a396c796bcbb2888a5519afe08ab80afc3e1d4f86607e66f795e59efa824c12782870f8fa29aaf03eb0380fde6e70b8bbf2e2a5f8c8030200000002010216b1907a5f15e25dfbb7487a032d37f61205d6127616e685b016b3b6cd9ebf35f38e5ae29392e2acd51d176167653030302d676f7665726e616e63652d746f6b656e04616c65780100000012f2fd9506000316e685b016b3b6cd9ebf35f38e5ae29392e2acd51d0a616c65782d7661756c7403000000002c9ad3150216e685b016b3b6cd9ebf35f38e5ae29392e2acd51d1766697865642d7765696768742d706f6f6c2d76312d30310b737761702d68656c706572000000060616e685b016b3b6cd9ebf35f38e5ae29392e2acd51d176167653030302d676f7665726e616e63652d746f6b656e0616e685b016b3b6cd9ebf35f38e5ae29392e2acd51d0a746f6b656e2d777374780100000000000000000000000002faf0800100000000000000000000000002faf08001000000000000000000000012f2fd95060a010000000000000000000000116c7a7446";
+        let sighash = transaction_sighash(&decode_hex(tx).unwrap()).unwrap();
+        assert_eq!(
+            encode_hex(&sighash).as_ref(),
+            "0x611073bc9690ed9570c178730df3f88d9f4f0b6a73a7ea5d3dd5e995d0a5893e"
+        );
+
+        // the sighash doesn't depend on the nonce, fee or signature of either condition
+        let original = decode_hex(SPONSORED_TX).unwrap();
+        let sighash = transaction_sighash(&original).unwrap();
+        let mut bytes = original.to_vec();
+        patch_sponsor_fee_nonce(&mut bytes, 5000, 42).unwrap();
+        // origin nonce and signature
+        bytes[6 + SPENDING_CONDITION_NONCE_OFFSET] ^= 1;
+        bytes[60] ^= 1;
+        // sponsor signer
+        bytes[125] ^= 1;
+        assert_eq!(transaction_sighash(&bytes).unwrap(), sighash);
+        // the origin signer is part of it
+        bytes[7] ^= 1;
+        assert_ne!(transaction_sighash(&bytes).unwrap(), sighash);
+
+        assert!(transaction_sighash(&original[..100]).is_err());
+    }
+}
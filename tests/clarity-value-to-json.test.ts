@@ -1,4 +1,4 @@
-import { ClarityTypeID, decodeClarityValue } from '../index.js';
+import { ClarityTypeID, ClarityValueBuffer, ClarityValueOptionalSome, ClarityValueTuple, decodeClarityValue } from '../index.js';
 
 test('decode clarity value 1 - from hex', () => {
   const val = decodeClarityValue('0x070b00000004070c000000020e656e7469746c65642d746f6b656e0100000000000000000000000021db1fb309746f2d72657475726e0100000000000000000000000000000000070c000000020e656e7469746c65642d746f6b656e010000000000000000000000001babc71009746f2d72657475726e0100000000000000000000000000000000070c000000020e656e7469746c65642d746f6b656e01000000000000000000000000180b562d09746f2d72657475726e0100000000000000000000000000000000070c000000020e656e7469746c65642d746f6b656e01000000000000000000000000154fd89209746f2d72657475726e0100000000000000000000000000000000');
@@ -432,3 +432,43 @@ test('decode principal with large contract name', () => {
     type_id: ClarityTypeID.PrincipalContract
   }));
 });
+
+test('decode clarity value - buffer encoding option', () => {
+  const hex = '0x020000000b68656c6c6f20776f726c64';
+  expect(decodeClarityValue(hex)).toEqual({
+    buffer: '0x68656c6c6f20776f726c64',
+    hex: '0x020000000b68656c6c6f20776f726c64',
+    repr: '0x68656c6c6f20776f726c64',
+    type_id: ClarityTypeID.Buffer,
+  });
+  expect(decodeClarityValue(hex, { buffer_encoding: 'hex' })).toEqual(decodeClarityValue(hex));
+  expect(decodeClarityValue(hex, { buffer_encoding: 'base64' })).toEqual({
+    buffer: 'aGVsbG8gd29ybGQ=',
+    hex: '0x020000000b68656c6c6f20776f726c64',
+    repr: '0x68656c6c6f20776f726c64',
+    type_id: ClarityTypeID.Buffer,
+  });
+  expect(() => decodeClarityValue(hex, { buffer_encoding: 'utf8' as any })).toThrow();
+});
+
+test('decode clarity value - buffers as bytes option', () => {
+  // (tuple (a 0x0102) (b (some 0x68656c6c6f)))
+  const hex = '0x0c0000000201610200000002010201620a020000000568656c6c6f';
+  const val = decodeClarityValue<ClarityValueTuple>(hex, { buffers_as_bytes: true });
+  const a = val.data.a as ClarityValueBuffer;
+  const b = (val.data.b as ClarityValueOptionalSome<ClarityValueBuffer>).value;
+  expect(Buffer.isBuffer(a.buffer)).toBe(true);
+  expect(a.buffer).toEqual(Buffer.from([1, 2]));
+  expect(b.buffer).toEqual(Buffer.from('hello'));
+  expect(a.hex).toBe('0x02000000020102');
+  expect(a.repr).toBe('0x0102');
+
+  expect(decodeClarityValue(hex, { buffers_as_bytes: false })).toEqual(decodeClarityValue(hex));
+  expect(() => decodeClarityValue(hex, { buffers_as_bytes: true, buffer_encoding: 'hex' })).toThrow();
+});
+
+test('decode clarity value - error offset', () => {
+  // (list u1 <0x20>), the bad type byte is at offset 22
+  const hex = '0x0b00000002010000000000000000000000000000000120';
+  expect(() => decodeClarityValue(hex)).toThrow(/Bad type prefix 0x20 at offset 22$/);
+});
@@ -14,3 +14,10 @@ test('decode clarity value to repr string from Buffer', () => {
   const repr = decodeClarityValueToRepr(Buffer.from('0a010000000000000000000000116c7a7446', 'hex'));
   expect(repr).toBe('(some u74834408518)');
 });
+
+test('decode clarity value to repr string - escaped string characters', () => {
+  expect(decodeClarityValueToRepr('0x0d000000087361792022686922')).toBe('"say \\"hi\\""');
+  expect(decodeClarityValueToRepr('0x0d00000006433a5c646972')).toBe('"C:\\\\dir"');
+  expect(decodeClarityValueToRepr('0x0d00000006610a6209630d')).toBe('"a\\nb\\tc\\r"');
+  expect(decodeClarityValueToRepr('0x0e00000005225c0ac3a9')).toBe('u"\\"\\\\\\n\\u{c3a9}"');
+});
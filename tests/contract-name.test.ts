@@ -0,0 +1,14 @@
+import { isValidContractName } from '../index.js';
+
+test('valid contract names', () => {
+  expect(isValidContractName('a')).toBe(true);
+  expect(isValidContractName('my-contract_v2')).toBe(true);
+  expect(isValidContractName('a'.repeat(40))).toBe(true);
+});
+
+test('invalid contract names', () => {
+  expect(isValidContractName('')).toBe(false);
+  expect(isValidContractName('a'.repeat(41))).toBe(false);
+  expect(isValidContractName('1-contract')).toBe(false);
+  expect(isValidContractName('my.contract')).toBe(false);
+});
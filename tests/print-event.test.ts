@@ -0,0 +1,44 @@
+import { decodePrintEvent, decodeStxTransferEvent } from '../index.js';
+
+test('decode print event', () => {
+  const event = decodePrintEvent('0x0d0000000568656c6c6f', 'SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract');
+  expect(event).toEqual({
+    "type": "contract_event",
+    "contract_event": {
+      "contract_identifier": "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract",
+      "topic": "print",
+      "value": {
+        "data": "hello",
+        "hex": "0x0d0000000568656c6c6f",
+        "repr": "\"hello\"",
+        "type_id": 13
+      },
+      "raw_value": "0x0d0000000568656c6c6f"
+    }
+  });
+});
+
+test('decode print event with invalid contract identifier', () => {
+  expect(() => decodePrintEvent('0x0d0000000568656c6c6f', 'SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7')).toThrow();
+});
+
+test('decode stx transfer event with memo', () => {
+  const event = decodeStxTransferEvent(JSON.stringify({
+    type: 'stx_transfer_event',
+    stx_transfer_event: {
+      sender: 'SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract',
+      recipient: 'SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE',
+      amount: '1000',
+      memo: '68656c6c6f20776f726c640000000000',
+    },
+  }));
+  expect(event).toEqual({
+    "type": "stx_transfer_event",
+    "stx_transfer_event": {
+      "sender": "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7.my-contract",
+      "recipient": "SP3FBR2AGK5H9QBDH3EEN6DF8EK8JY7RX8QJ5SVTE",
+      "amount": "1000",
+      "memo": "hello world"
+    }
+  });
+});